@@ -130,7 +130,7 @@ where
             )));
         }
 
-        drw.register_font(&config.font);
+        drw.register_font(&config.font)?;
 
         let default_style = TextStyle {
             font: config.font.clone(),
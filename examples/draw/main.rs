@@ -74,9 +74,9 @@ fn simple_draw() -> Result<()> {
         Region::new(0, 0, w, HEIGHT as u32),
         false,
     )?;
-    drw.register_font(PROFONT);
-    drw.register_font(SERIF);
-    drw.register_font(FIRA);
+    drw.register_font(PROFONT)?;
+    drw.register_font(SERIF)?;
+    drw.register_font(FIRA)?;
 
     let mut ctx = drw.context_for(id)?;
 
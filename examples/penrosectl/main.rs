@@ -0,0 +1,28 @@
+/**
+ * penrose :: penrosectl
+ * A tiny command line client for sending commands to a running penrose instance that is
+ * listening for them using WindowManager::listen_for_commands. The arguments are joined together
+ * and sent as a single command, for example:
+ *
+ *   $ cargo run --example penrosectl --features ipc -- focus-workspace 2
+ *   $ cargo run --example penrosectl --features ipc -- state
+ */
+use penrose::core::ipc::{default_socket_path, send_command, Command};
+use std::{env, process};
+
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    let result = args
+        .join(" ")
+        .parse::<Command>()
+        .and_then(|cmd| send_command(default_socket_path(), &cmd));
+
+    match result {
+        Ok(response) => println!("{}", response),
+        Err(e) => {
+            eprintln!("{}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -153,6 +153,10 @@ impl TestXConn {
         remaining
     }
 
+    pub fn set_events(&mut self, events: Vec<XEvent>) {
+        self.events.set(events);
+    }
+
     pub fn set_screen_count(&mut self, n: u32) {
         self.n_screens.set(n);
     }
@@ -327,5 +331,15 @@ __impl_stub_xcon! {
             self.add_call("is_managed_client", strings!(c.id()));
             true
         }
+
+        fn mock_grab_keyboard(&self) -> Result<()> {
+            self.add_call("grab_keyboard", vec![]);
+            Ok(())
+        }
+
+        fn mock_ungrab_keyboard(&self) -> Result<()> {
+            self.add_call("ungrab_keyboard", vec![]);
+            Ok(())
+        }
     }
 }
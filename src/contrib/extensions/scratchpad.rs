@@ -22,6 +22,21 @@ use std::{cell::RefCell, fmt, rc::Rc};
 /// existing client, after that 'toggle' will show/hide the client on the active screen. If the
 /// client is removed, calling 'toggle' again will spawn a new client in the same way.
 ///
+/// By default the first client to appear after spawning 'prog' is captured. Calling
+/// [with_class][Scratchpad::with_class] restricts this to clients with a matching WM_CLASS,
+/// which also allows the scratchpad to reclaim its program if it is restarted outside of
+/// 'toggle' while the scratchpad is empty. The scratchpad follows focus: it is always shown on
+/// the active screen, above the workspace that is currently focused.
+///
+/// ```
+/// # use penrose::__test_helpers::TestKeyHandler;
+/// use penrose::contrib::extensions::Scratchpad;
+///
+/// let sp = Scratchpad::new("st -c scratch", 0.8, 0.8).with_class("scratch");
+/// let hook = sp.get_hook(); // pass this to the WindowManager along with your other hooks
+/// let toggle: TestKeyHandler = sp.toggle(); // bind this to a key
+/// ```
+///
 /// [1]: crate::core::client::Client
 #[derive(Clone, PartialEq)]
 pub struct Scratchpad {
@@ -29,6 +44,7 @@ pub struct Scratchpad {
     pending: Rc<RefCell<bool>>,
     visible: Rc<RefCell<bool>>,
     prog: String,
+    class: Option<String>,
     w: f32,
     h: f32,
 }
@@ -40,6 +56,7 @@ impl fmt::Debug for Scratchpad {
             .field("pending", &self.pending.borrow())
             .field("visible", &self.visible.borrow())
             .field("prog", &self.prog)
+            .field("class", &self.class)
             .field("w", &self.w)
             .field("h", &self.h)
             .finish()
@@ -63,17 +80,41 @@ impl Scratchpad {
             pending: Rc::new(RefCell::new(false)),
             visible: Rc::new(RefCell::new(false)),
             prog: prog.into(),
+            class: None,
             w,
             h,
         }
     }
 
+    /// Only capture clients whose WM_CLASS matches 'class', rather than the first client that
+    /// appears after spawning the program. A matching client appearing while this scratchpad
+    /// has no client will be captured and shown even if it was not spawned by 'toggle'.
+    pub fn with_class<S>(mut self, class: S) -> Scratchpad
+    where
+        S: Into<String>,
+    {
+        self.class = Some(class.into());
+        self
+    }
+
+    fn should_capture(&self, class: &str) -> bool {
+        if self.client.borrow().is_some() {
+            return false;
+        }
+
+        match self.class {
+            Some(ref c) => c == class,
+            None => *self.pending.borrow(),
+        }
+    }
+
     fn boxed_clone(&self) -> Box<Self> {
         Box::new(Self {
             client: Rc::clone(&self.client),
             pending: Rc::clone(&self.pending),
             visible: Rc::clone(&self.visible),
             prog: self.prog.clone(),
+            class: self.class.clone(),
             w: self.w,
             h: self.h,
         })
@@ -139,8 +180,9 @@ impl Scratchpad {
 impl<X: XConn> Hook<X> for Scratchpad {
     fn new_client(&mut self, wm: &mut WindowManager<X>, id: Xid) -> Result<()> {
         let c = wm.client_mut(&Selector::WinId(id)).unwrap();
-        if *self.pending.borrow() && self.client.borrow().is_none() {
+        if self.should_capture(c.class()) {
             self.pending.replace(false);
+            self.visible.replace(false);
             self.client.replace(Some(c.id()));
             c.externally_managed();
             c.set_floating(true);
@@ -170,6 +212,12 @@ impl<X: XConn> Hook<X> for Scratchpad {
         _: usize,
         screen_index: usize,
     ) -> Result<()> {
+        // Only follow the active screen so that layouts being applied elsewhere (such as a
+        // client closing on another monitor) do not pull the scratchpad away from the user
+        if screen_index != wm.active_screen_index() {
+            return Ok(());
+        }
+
         if let Some(id) = *self.client.borrow() {
             if *self.visible.borrow() {
                 if let Some(region) = wm.screen_size(screen_index) {
@@ -1,13 +1,18 @@
 //! Setting up and responding to user defined key/mouse bindings
 use crate::{
-    core::{data_types::Point, manager::WindowManager, xconnection::Xid},
+    core::{
+        data_types::Point,
+        helpers::keycodes_from_xmodmap,
+        manager::WindowManager,
+        xconnection::{XConn, Xid},
+    },
     PenroseError, Result,
 };
 
 #[cfg(feature = "keysyms")]
 use penrose_keysyms::XKeySym;
 
-use std::{collections::HashMap, convert::TryFrom};
+use std::{cell::RefCell, collections::HashMap, convert::TryFrom, fmt, rc::Rc};
 
 use strum::EnumIter;
 
@@ -25,6 +30,117 @@ pub type MouseBindings<X> = HashMap<(MouseEventKind, MouseState), MouseEventHand
 
 pub(crate) type CodeMap = HashMap<String, u8>;
 
+// Keys that can be pressed while waiting for the next key in a key chain without ending it
+const MODIFIER_KEY_NAMES: &[&str] = &[
+    "Shift_L",
+    "Shift_R",
+    "Control_L",
+    "Control_R",
+    "Alt_L",
+    "Alt_R",
+    "Meta_L",
+    "Meta_R",
+    "Super_L",
+    "Super_R",
+    "Hyper_L",
+    "Hyper_R",
+    "ISO_Level3_Shift",
+    "Mode_switch",
+    "Caps_Lock",
+    "Num_Lock",
+];
+
+/// Create a [KeyEventHandler] that starts a key chain using the given bindings.
+///
+/// When the returned handler is run, the keyboard is grabbed and the next key press is looked up
+/// in `bindings` rather than your top level [KeyBindings] before the grab is released. Pressing
+/// Escape or any key that is not bound in the chain cancels it without running anything, as does
+/// not pressing a key within the `key_chain_timeout_ms` set in your [Config][1]. Modifier keys can
+/// be pressed freely while the chain is waiting and chains can be nested if needed.
+///
+/// The timeout is checked each time an event is received from the X server so the grab is
+/// released by the first event after the timeout has passed.
+///
+/// # Example
+/// ```no_run
+/// # #[macro_use] extern crate penrose;
+/// # use penrose::{core::bindings::{key_chain, KeyBindings}, XcbConnection};
+/// # fn example() -> KeyBindings<XcbConnection> {
+/// let key_bindings = gen_keybindings! {
+///     "M-w" => key_chain(gen_keybindings! {
+///         "f" => run_external!("firefox");
+///         "t" => run_external!("st");
+///     });
+/// };
+/// # key_bindings
+/// # }
+/// ```
+///
+/// [1]: crate::core::config::Config
+pub fn key_chain<X: XConn + 'static>(bindings: KeyBindings<X>) -> KeyEventHandler<X> {
+    let chain = KeyChain::new(bindings, &keycodes_from_xmodmap());
+    Box::new(move |wm: &mut WindowManager<X>| wm.start_key_chain(chain.clone()))
+}
+
+// The bindings for a key chain along with the key codes that need special handling while it is
+// waiting for its next key press.
+pub(crate) struct KeyChain<X: XConn> {
+    bindings: Rc<RefCell<KeyBindings<X>>>,
+    escape: Option<KeyCodeValue>,
+    modifiers: Rc<Vec<KeyCodeValue>>,
+}
+
+impl<X: XConn> KeyChain<X> {
+    pub(crate) fn new(bindings: KeyBindings<X>, codes: &CodeMap) -> Self {
+        Self {
+            bindings: Rc::new(RefCell::new(bindings)),
+            escape: codes.get("Escape").copied(),
+            modifiers: Rc::new(
+                MODIFIER_KEY_NAMES
+                    .iter()
+                    .flat_map(|name| codes.get(*name).copied())
+                    .collect(),
+            ),
+        }
+    }
+
+    pub(crate) fn is_escape(&self, k: &KeyCode) -> bool {
+        self.escape == Some(k.code)
+    }
+
+    pub(crate) fn is_modifier(&self, k: &KeyCode) -> bool {
+        self.modifiers.contains(&k.code)
+    }
+
+    pub(crate) fn bindings(&self) -> &RefCell<KeyBindings<X>> {
+        &self.bindings
+    }
+}
+
+// Not derived as that would require X: Clone
+impl<X: XConn> Clone for KeyChain<X> {
+    fn clone(&self) -> Self {
+        Self {
+            bindings: Rc::clone(&self.bindings),
+            escape: self.escape,
+            modifiers: Rc::clone(&self.modifiers),
+        }
+    }
+}
+
+impl<X: XConn> fmt::Debug for KeyChain<X> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyChain")
+            .field(
+                "bindings",
+                &self.bindings.borrow().keys().collect::<Vec<_>>(),
+            )
+            .field("escape", &self.escape)
+            .field("modifiers", &self.modifiers)
+            .finish()
+    }
+}
+
 /// Abstraction layer for working with key presses
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum KeyPress {
@@ -1,5 +1,8 @@
 //! Metadata around X clients and manipulating them
-use crate::core::xconnection::{Atom, Prop, WmHints, WmNormalHints, XClientProperties, Xid};
+use crate::core::{
+    data_types::Region,
+    xconnection::{Atom, Prop, WmHints, WmHintsFlags, WmNormalHints, XClientProperties, Xid},
+};
 
 /**
  * Meta-data around a client window that we are handling.
@@ -25,6 +28,12 @@ pub struct Client {
     pub(crate) mapped: bool,
     pub(crate) urgent: bool,
     pub(crate) wm_managed: bool,
+    // The last known position of this client while it was floating
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) floating_region: Option<Region>,
+    // The number of unmaps we have requested for this client that are yet to be notified
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) pending_unmaps: usize,
 }
 
 impl Client {
@@ -59,6 +68,9 @@ impl Client {
             Ok(Prop::WmNormalHints(hints)) => Some(hints),
             _ => None,
         };
+        let urgent = wm_hints
+            .as_ref()
+            .is_some_and(|h| h.flags.contains(WmHintsFlags::URGENCY_HINT));
         let wm_protocols = match conn.get_prop(id, Atom::WmProtocols.as_ref()) {
             Ok(Prop::Atom(protocols)) => protocols,
             _ => vec![],
@@ -77,8 +89,10 @@ impl Client {
             accepts_focus,
             fullscreen: false,
             mapped: false,
-            urgent: false,
+            urgent,
             wm_managed: true,
+            floating_region: None,
+            pending_unmaps: 0,
         }
     }
 
@@ -102,6 +116,11 @@ impl Client {
         self.fullscreen
     }
 
+    /// Whether or not this client is currently requesting the user's attention
+    pub fn is_urgent(&self) -> bool {
+        self.urgent
+    }
+
     /// The current workspace index that this client is showing on
     pub fn workspace(&self) -> usize {
         self.workspace
@@ -126,6 +145,15 @@ impl Client {
         self.wm_class.get(0).map(AsRef::as_ref).unwrap_or("unknown")
     }
 
+    // The region this client should occupy when positioned in 'r', respecting its size hints.
+    // Tiled clients only honour their hints if they have a fixed size.
+    pub(crate) fn constrained_region(&self, r: Region) -> Region {
+        match self.wm_normal_hints {
+            Some(ref hints) if self.floating || hints.is_fixed_size() => hints.apply_to(&r),
+            _ => r,
+        }
+    }
+
     /// Mark this client as not being managed by the WindowManager directly
     pub fn externally_managed(&mut self) {
         self.wm_managed = false;
@@ -136,3 +164,55 @@ impl Client {
         self.wm_managed = true;
     }
 }
+
+/// A rule for placing new clients based on their WM_CLASS.
+///
+/// Rules are registered on the [Config][1] and checked in order when a new client is mapped: the
+/// first rule that matches the client is applied and any remaining rules are ignored.
+///
+/// ```
+/// use penrose::{core::client::ClientRule, Config};
+///
+/// let mut config_builder = Config::default().builder();
+/// let config = config_builder
+///     .client_rules(vec![
+///         ClientRule::new("firefox", Some(1), false),
+///         ClientRule::new("keepassxc", None, true),
+///     ])
+///     .build()
+///     .expect("failed to build config");
+/// ```
+///
+/// [1]: crate::core::config::Config
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct ClientRule {
+    /// The WM_CLASS to match: either the instance or class name of the client may match. Clients
+    /// that have no WM_CLASS set are matched against their WM_NAME instead.
+    pub class: String,
+    /// The index of the workspace that matching clients should be placed on, rather than the
+    /// active workspace. Indices that are out of range are ignored.
+    pub workspace: Option<usize>,
+    /// Whether or not matching clients should be floating
+    pub floating: bool,
+}
+
+impl ClientRule {
+    /// Create a new [ClientRule] for clients with the given WM_CLASS
+    pub fn new(class: impl Into<String>, workspace: Option<usize>, floating: bool) -> Self {
+        Self {
+            class: class.into(),
+            workspace,
+            floating,
+        }
+    }
+
+    /// Whether or not this rule applies to the given [Client]
+    pub fn matches(&self, client: &Client) -> bool {
+        if client.wm_class.is_empty() {
+            client.wm_name == self.class
+        } else {
+            client.wm_class.contains(&self.class)
+        }
+    }
+}
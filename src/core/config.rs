@@ -1,6 +1,9 @@
 //! User facing configuration of the penrose [WindowManager][crate::core::manager::WindowManager].
 use crate::{
-    core::layout::{side_stack, Layout, LayoutConf},
+    core::{
+        client::ClientRule,
+        layout::{side_stack, Layout, LayoutConf},
+    },
     draw::{Color, DrawError},
 };
 
@@ -68,6 +71,9 @@ __with_builder_and_getters! {
     /// the window classes that will always be considered floating
     VecImplInto floating_classes: String; => vec!["dmenu", "dunst"];
 
+    /// the [ClientRule]s used to place new clients, checked in order with the first match winning
+    Concrete client_rules: Vec<ClientRule>; => vec![];
+
     /// the [Layout] functions to be used by each [Workspace][crate::core::workspace::Workspace]
     ///
     /// # Constraints
@@ -86,6 +92,8 @@ __with_builder_and_getters! {
     Concrete border_px: u32; => 2;
     /// the gap between tiled windows in pixels
     Concrete gap_px: u32; => 5;
+    /// an additional gap between tiled windows and the edges of the screen in pixels
+    Concrete outer_gap_px: u32; => 0;
     /// the percentage of the screen to grow the main region by when incrementing
     Concrete main_ratio_step: f32; => 0.05;
     /// whether or not space should be reserved for a status bar
@@ -94,6 +102,12 @@ __with_builder_and_getters! {
     Concrete top_bar: bool; => true;
     /// the height of the space to be reserved for a status bar in pixels
     Concrete bar_height: u32; => 18;
+    /// how long to wait for the next key of a [key chain][crate::core::bindings::key_chain]
+    /// before cancelling it, in milliseconds. A value of 0 will wait indefinitely
+    Concrete key_chain_timeout_ms: u64; => 2000;
+    /// whether or not moving the pointer over a client gives it focus. When disabled, focus is
+    /// only changed by key bindings and other explicit requests
+    Concrete focus_follow_mouse: bool; => true;
 }
 
 impl Config {
@@ -20,9 +20,25 @@ pub enum WinType {
     InputOnly,
     /// A regular window. The [Atom] passed should be a
     /// valid _NET_WM_WINDOW_TYPE (this is not enforced)
+    ///
+    /// Passing [Atom::NetWindowTypeDesktop] will create a window that is stacked below all other
+    /// windows, allowing for desktop widgets to be drawn over the wallpaper. Note that some window
+    /// managers ignore this window type and will treat the window as a regular client.
     InputOutput(Atom),
 }
 
+impl WinType {
+    /// The _NET_WM_WINDOW_TYPE atom name for this window type, if it has one.
+    ///
+    /// Only [WinType::InputOutput] windows carry an EWMH window type.
+    pub fn ewmh_name(&self) -> Option<&'static str> {
+        match self {
+            WinType::InputOutput(a) => Some(a.into()),
+            _ => None,
+        }
+    }
+}
+
 /// A relative position along the horizontal and vertical axes
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
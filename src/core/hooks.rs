@@ -136,6 +136,7 @@ use crate::{
 pub enum HookName {
     Startup,
     NewClient(Xid),
+    ClientClosed(Xid),
     RemoveClient(Xid),
     ClientAddedToWorkspace(Xid, usize),
     ClientNameUpdated(Xid, String, bool),
@@ -224,6 +225,27 @@ pub trait Hook<X: XConn> {
         Ok(())
     }
 
+    /// # Trigger Point
+    ///
+    /// Called *before* a [Client][2] is removed from internal [WindowManager] state when its
+    /// window has been unmapped or destroyed by the program that owns it. This is not called for
+    /// windows that penrose itself has unmapped, such as when switching workspaces or applying a
+    /// layout that hides some clients.
+    ///
+    /// # Example Uses
+    ///
+    /// The client is still available through [WindowManager::client][1] when this hook is called
+    /// so it can be used to clean up any external state that was derived from its properties.
+    /// Use [remove_client][3] if you only need to know that the client has gone.
+    ///
+    /// [1]: crate::core::manager::WindowManager::client
+    /// [2]: crate::core::client::Client
+    /// [3]: Hook::remove_client
+    #[allow(unused_variables)]
+    fn client_closed(&mut self, wm: &mut WindowManager<X>, id: Xid) -> Result<()> {
+        Ok(())
+    }
+
     /// # Trigger Point
     ///
     /// Called *after* a [Client][3] is removed from internal [WindowManager] state, either through
@@ -419,15 +441,18 @@ pub trait Hook<X: XConn> {
     /// # Trigger Point
     ///
     /// Called when the list of known [Screens][1] is updated via the [detect_screens][2] method on
-    /// the `WindowManager`.
+    /// the `WindowManager`. This happens automatically when monitors are connected, disconnected
+    /// or resized, after [randr_notify][3] has been called.
     ///
     /// # Example Uses
     ///
     /// Tracking Screen sizes and details without needing to poll / check every time your hook is
-    /// called.
+    /// called. The built in status bar uses this to re-create its windows at the new screen
+    /// positions.
     ///
     /// [1]: crate::core::screen::Screen
     /// [2]: crate::core::manager::WindowManager::detect_screens
+    /// [3]: Hook::randr_notify
     #[allow(unused_variables)]
     fn screens_updated(&mut self, wm: &mut WindowManager<X>, dimensions: &[Region]) -> Result<()> {
         Ok(())
@@ -0,0 +1,454 @@
+//! Controlling a running [WindowManager] over a Unix domain socket
+//!
+//! Only available when the `ipc` feature is enabled.
+//!
+//! # Overview
+//!
+//! Calling [listen_for_commands][1] before starting the main event loop opens a socket that
+//! external programs (shell scripts, status bars etc) can use to send [Command]s to penrose
+//! without needing to recompile your key bindings. Each connection to the socket sends a single
+//! command as a line of text and receives a single line in response before it is closed:
+//!
+//!   - `ok` if the command was run successfully
+//!   - a JSON encoded [StateSnapshot] for the `state` command
+//!   - `error: <message>` if the command could not be parsed or failed to run
+//!
+//! Commands are run from the main event loop in between handling X events so they have the same
+//! access to the [WindowManager] as a key binding would.
+//!
+//! ```no_run
+//! use penrose::{
+//!     core::{bindings::{KeyBindings, MouseBindings}, ipc::default_socket_path},
+//!     xcb::new_xcb_backed_window_manager,
+//!     Config, logging_error_handler,
+//! };
+//!
+//! # fn example(key_bindings: KeyBindings<penrose::XcbConnection>) -> penrose::Result<()> {
+//! let mut wm = new_xcb_backed_window_manager(Config::default(), vec![], logging_error_handler())?;
+//! wm.listen_for_commands(default_socket_path())?;
+//! wm.grab_keys_and_run(key_bindings, MouseBindings::new())
+//! # }
+//! ```
+//!
+//! The [send_command] helper can then be used to drive penrose from another process (see the
+//! `penrosectl` example):
+//!
+//! ```no_run
+//! use penrose::core::ipc::{default_socket_path, send_command, Command};
+//!
+//! # fn example() -> penrose::Result<()> {
+//! send_command(default_socket_path(), &Command::FocusWorkspace(2))?;
+//! let json = send_command(default_socket_path(), &Command::State)?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! [1]: crate::core::manager::WindowManager::listen_for_commands
+use crate::{
+    core::{
+        helpers::spawn,
+        manager::WindowManager,
+        ring::{Direction, Selector},
+        xconnection::{XConn, Xid},
+    },
+    PenroseError, Result,
+};
+
+use std::{
+    env, fmt, fs,
+    io::{BufRead, BufReader, ErrorKind, Read, Write},
+    os::unix::{
+        fs::FileTypeExt,
+        io::{AsRawFd, RawFd},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+const ERROR_PREFIX: &str = "error: ";
+
+// Clients that connect but never send a command must not block the event loop indefinitely
+const CLIENT_TIMEOUT: Duration = Duration::from_millis(200);
+
+/// The default location for the IPC socket: `$XDG_RUNTIME_DIR/penrose-0.sock` when `DISPLAY` is
+/// `:0`, falling back to the system temp directory if `XDG_RUNTIME_DIR` is not set.
+pub fn default_socket_path() -> PathBuf {
+    let dir = env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(env::temp_dir);
+    let display = env::var("DISPLAY")
+        .unwrap_or_default()
+        .replace(':', "-")
+        .replace('/', "_");
+
+    dir.join(format!("penrose{}.sock", display))
+}
+
+/// Send a single [Command] to the penrose instance listening on `path` and return its response.
+///
+/// Error responses are returned as [PenroseError::Raw].
+pub fn send_command(path: impl AsRef<Path>, command: &Command) -> Result<String> {
+    let mut stream = UnixStream::connect(path)?;
+    writeln!(stream, "{}", command)?;
+
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    let response = response.trim_end();
+
+    match response.strip_prefix(ERROR_PREFIX) {
+        Some(msg) => Err(PenroseError::Raw(msg.to_string())),
+        None => Ok(response.to_string()),
+    }
+}
+
+/// A command that can be sent to a running [WindowManager] over its IPC socket.
+///
+/// Commands are sent as a single line of text: the [Display][fmt::Display] and [FromStr]
+/// implementations convert between the two. Workspace indices start at 0, matching
+/// `_NET_CURRENT_DESKTOP` and tools such as `wmctrl`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Command {
+    /// `client-to-workspace <index>`: move the focused client to the given workspace
+    ClientToWorkspace(usize),
+    /// `cycle-layout forward|backward`: cycle the layout of the active workspace
+    CycleLayout(Direction),
+    /// `focus-workspace <index>`: show the given workspace on the focused screen
+    FocusWorkspace(usize),
+    /// `spawn <program> [args...]`: run an external program
+    Spawn(String),
+    /// `state`: respond with a JSON encoded [StateSnapshot]
+    State,
+}
+
+impl Command {
+    /// Run this command against the given [WindowManager], returning the response text if this
+    /// command has one.
+    pub fn run<X: XConn>(self, wm: &mut WindowManager<X>) -> Result<Option<String>> {
+        let check_index = |wm: &WindowManager<X>, ix: usize| {
+            if ix < wm.all_workspaces(&Selector::Any).len() {
+                Ok(ix)
+            } else {
+                Err(PenroseError::UnknownWorkspace(ix))
+            }
+        };
+
+        match self {
+            Self::ClientToWorkspace(ix) => {
+                let ix = check_index(wm, ix)?;
+                wm.client_to_workspace(&Selector::Index(ix))?
+            }
+            Self::CycleLayout(direction) => wm.cycle_layout(direction)?,
+            Self::FocusWorkspace(ix) => {
+                let ix = check_index(wm, ix)?;
+                wm.focus_workspace(&Selector::Index(ix))?
+            }
+            Self::Spawn(cmd) => spawn(cmd)?,
+            Self::State => {
+                let json = serde_json::to_string(&StateSnapshot::new(wm))
+                    .map_err(|e| perror!("unable to serialize state: {}", e))?;
+                return Ok(Some(json));
+            }
+        }
+
+        Ok(None)
+    }
+}
+
+impl fmt::Display for Command {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::ClientToWorkspace(ix) => write!(f, "client-to-workspace {}", ix),
+            Self::CycleLayout(Direction::Forward) => write!(f, "cycle-layout forward"),
+            Self::CycleLayout(Direction::Backward) => write!(f, "cycle-layout backward"),
+            Self::FocusWorkspace(ix) => write!(f, "focus-workspace {}", ix),
+            Self::Spawn(cmd) => write!(f, "spawn {}", cmd),
+            Self::State => write!(f, "state"),
+        }
+    }
+}
+
+impl FromStr for Command {
+    type Err = PenroseError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let s = s.trim();
+        let (cmd, args) = match s.split_once(char::is_whitespace) {
+            Some((cmd, args)) => (cmd, args.trim()),
+            None => (s, ""),
+        };
+
+        match (cmd, args) {
+            ("client-to-workspace", ix) => Ok(Self::ClientToWorkspace(ix.parse()?)),
+            ("cycle-layout", "forward") => Ok(Self::CycleLayout(Direction::Forward)),
+            ("cycle-layout", "backward") => Ok(Self::CycleLayout(Direction::Backward)),
+            ("focus-workspace", ix) => Ok(Self::FocusWorkspace(ix.parse()?)),
+            ("spawn", cmd) if !cmd.is_empty() => Ok(Self::Spawn(cmd.to_string())),
+            ("state", "") => Ok(Self::State),
+            _ => Err(perror!("unknown command: '{}'", s)),
+        }
+    }
+}
+
+/// A summary of the current [WindowManager] state for use by external programs such as status
+/// bars, returned as JSON by [Command::State].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StateSnapshot {
+    /// Each workspace known to the [WindowManager], in order
+    pub workspaces: Vec<WorkspaceSnapshot>,
+    /// The index of the workspace being shown on each screen
+    pub screens: Vec<usize>,
+    /// The index of the focused screen
+    pub focused_screen: usize,
+    /// The focused client, if there is one
+    pub focused_client: Option<ClientSnapshot>,
+}
+
+impl StateSnapshot {
+    /// Take a snapshot of the current state of the given [WindowManager]
+    pub fn new<X: XConn>(wm: &WindowManager<X>) -> Self {
+        let urgent = wm.urgent_windows();
+        let workspaces = wm
+            .all_workspaces(&Selector::Any)
+            .into_iter()
+            .map(|ws| WorkspaceSnapshot {
+                name: ws.name().to_string(),
+                layout: ws.layout_symbol().to_string(),
+                clients: ws.client_ids(),
+                focused_client: ws.focused_client(),
+                urgent: ws.iter().any(|id| urgent.contains(id)),
+            })
+            .collect();
+
+        let focused_client = wm
+            .focused_client_id()
+            .and_then(|id| wm.client(&Selector::WinId(id)))
+            .map(|c| ClientSnapshot {
+                id: c.id(),
+                name: c.wm_name().to_string(),
+                class: c.wm_class().to_string(),
+            });
+
+        Self {
+            workspaces,
+            screens: wm.focused_workspaces(),
+            focused_screen: wm.active_screen_index(),
+            focused_client,
+        }
+    }
+}
+
+/// The state of a single workspace within a [StateSnapshot]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WorkspaceSnapshot {
+    /// The name of the workspace
+    pub name: String,
+    /// The symbol of the active layout
+    pub layout: String,
+    /// The IDs of the clients on this workspace
+    pub clients: Vec<Xid>,
+    /// The focused client on this workspace, if there is one
+    pub focused_client: Option<Xid>,
+    /// Whether or not any client on this workspace is requesting the user's attention
+    pub urgent: bool,
+}
+
+/// The focused client within a [StateSnapshot]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ClientSnapshot {
+    /// The X window ID of the client
+    pub id: Xid,
+    /// The WM_NAME of the client
+    pub name: String,
+    /// The WM_CLASS of the client
+    pub class: String,
+}
+
+// The listening side of the IPC socket. The socket file is removed when this is dropped.
+#[derive(Debug)]
+pub(crate) struct IpcServer {
+    listener: UnixListener,
+    path: PathBuf,
+}
+
+impl IpcServer {
+    pub(crate) fn bind(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        if UnixStream::connect(&path).is_ok() {
+            return Err(perror!("{} is in use by another process", path.display()));
+        }
+
+        // A socket left behind by a previous run that did not exit cleanly
+        if is_socket(&path) {
+            fs::remove_file(&path)?;
+        }
+
+        let listener = UnixListener::bind(&path)?;
+        listener.set_nonblocking(true)?;
+
+        Ok(Self { listener, path })
+    }
+
+    // Run every command that is currently waiting to be read from the socket
+    pub(crate) fn handle_pending<X: XConn>(&self, wm: &mut WindowManager<X>) -> Result<()> {
+        loop {
+            let stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return Ok(()),
+                Err(e) => return Err(e.into()),
+            };
+
+            if let Err(e) = handle_connection(stream, wm) {
+                warn!("unable to handle IPC command: {}", e);
+            }
+        }
+    }
+}
+
+impl AsRawFd for IpcServer {
+    fn as_raw_fd(&self) -> RawFd {
+        self.listener.as_raw_fd()
+    }
+}
+
+impl Drop for IpcServer {
+    fn drop(&mut self) {
+        if let Err(e) = fs::remove_file(&self.path) {
+            warn!("unable to remove IPC socket {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+fn is_socket(path: &Path) -> bool {
+    matches!(fs::symlink_metadata(path), Ok(m) if m.file_type().is_socket())
+}
+
+fn handle_connection<X: XConn>(stream: UnixStream, wm: &mut WindowManager<X>) -> Result<()> {
+    stream.set_nonblocking(false)?;
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    debug!(command = line.trim(), "running IPC command");
+
+    let response = match line.parse::<Command>().and_then(|cmd| cmd.run(wm)) {
+        Ok(Some(response)) => response,
+        Ok(None) => "ok".to_string(),
+        Err(e) => format!("{}{}", ERROR_PREFIX, e),
+    };
+
+    Ok(writeln!(&stream, "{}", response)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        __test_helpers::{n_clients, test_key_bindings, test_mouse_bindings, TestXConn},
+        core::config::Config,
+        logging_error_handler, Backward, Forward,
+    };
+
+    use std::{process, thread};
+
+    fn test_wm() -> WindowManager<TestXConn> {
+        let conn = TestXConn::new(2, vec![], vec![]);
+        let mut wm = WindowManager::new(Config::default(), conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+
+        wm
+    }
+
+    test_cases! {
+        parse_command;
+        args: (s: &str, expected: Command);
+
+        case: client_to_workspace => ("client-to-workspace 3", Command::ClientToWorkspace(3));
+        case: cycle_layout_forward => ("cycle-layout forward", Command::CycleLayout(Forward));
+        case: cycle_layout_backward => ("cycle-layout backward", Command::CycleLayout(Backward));
+        case: focus_workspace => ("focus-workspace 2", Command::FocusWorkspace(2));
+        case: spawn => ("spawn st -e htop", Command::Spawn("st -e htop".into()));
+        case: state => ("state", Command::State);
+        case: surrounding_whitespace => ("  focus-workspace   2 \n", Command::FocusWorkspace(2));
+
+        body: {
+            let cmd: Command = s.parse().unwrap();
+            assert_eq!(cmd, expected);
+            assert_eq!(cmd.to_string().parse::<Command>().unwrap(), expected);
+        }
+    }
+
+    test_cases! {
+        parse_invalid_command;
+        args: (s: &str);
+
+        case: empty => ("");
+        case: unknown => ("reticulate-splines");
+        case: missing_index => ("focus-workspace");
+        case: invalid_index => ("focus-workspace two");
+        case: invalid_direction => ("cycle-layout sideways");
+        case: missing_program => ("spawn");
+        case: unexpected_args => ("state please");
+
+        body: {
+            assert!(s.parse::<Command>().is_err());
+        }
+    }
+
+    #[test]
+    fn out_of_range_workspaces_are_an_error() {
+        let mut wm = test_wm();
+
+        assert!(matches!(
+            Command::FocusWorkspace(42).run(&mut wm),
+            Err(PenroseError::UnknownWorkspace(42))
+        ));
+    }
+
+    #[test]
+    fn state_snapshot() {
+        let mut wm = test_wm();
+        wm.conn_mut().set_events(n_clients(2));
+        wm.grab_keys_and_run(test_key_bindings(), test_mouse_bindings())
+            .unwrap();
+        Command::FocusWorkspace(3).run(&mut wm).unwrap();
+
+        let json = Command::State.run(&mut wm).unwrap().unwrap();
+        let snapshot: StateSnapshot = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(snapshot, StateSnapshot::new(&wm));
+        assert_eq!(snapshot.screens, vec![3, 1]);
+        assert_eq!(snapshot.workspaces.len(), 9);
+        assert_eq!(snapshot.workspaces[0].clients, vec![1, 0]);
+        assert_eq!(snapshot.workspaces[0].focused_client, Some(1));
+    }
+
+    #[test]
+    fn commands_are_run_from_the_socket() {
+        let path = env::temp_dir().join(format!("penrose-ipc-test-{}.sock", process::id()));
+        let server = IpcServer::bind(&path).unwrap();
+        let mut wm = test_wm();
+
+        let client_path = path.clone();
+        let client = thread::spawn(move || {
+            let ok = send_command(&client_path, &Command::FocusWorkspace(2));
+            let err = send_command(&client_path, &Command::FocusWorkspace(42));
+            (ok, err)
+        });
+
+        while !client.is_finished() {
+            server.handle_pending(&mut wm).unwrap();
+            thread::sleep(Duration::from_millis(1));
+        }
+        let (ok, err) = client.join().unwrap();
+
+        assert_eq!(ok.unwrap(), "ok");
+        assert!(matches!(err, Err(PenroseError::Raw(msg)) if msg.contains("42")));
+        assert_eq!(wm.active_workspace().name(), "3");
+
+        drop(server);
+        assert!(!path.exists());
+    }
+}
@@ -62,6 +62,11 @@ use crate::core::{
 
 use std::{cmp, fmt};
 
+/// The smallest main area ratio that can be set using [Layout::update_main_ratio]
+pub const MIN_MAIN_RATIO: f32 = 0.05;
+/// The largest main area ratio that can be set using [Layout::update_main_ratio]
+pub const MAX_MAIN_RATIO: f32 = 0.95;
+
 /// When and how a Layout should be applied.
 ///
 /// The default layout config that only triggers when clients are added / removed and follows user
@@ -185,6 +190,26 @@ impl Layout {
         }
     }
 
+    /// A [monocle] layout that shows only the focused client, using the full screen.
+    ///
+    /// The layout follows focus so that cycling between clients maps the newly focused client
+    /// and unmaps the one that previously held focus. Gaps are dropped as there is only ever a
+    /// single visible client.
+    pub fn monocle(symbol: impl Into<String>) -> Self {
+        Self {
+            symbol: symbol.into(),
+            conf: LayoutConf {
+                floating: false,
+                gapless: true,
+                follow_focus: true,
+                allow_wrapping: true,
+            },
+            f: Some(monocle),
+            max_main: 1,
+            ratio: 1.0,
+        }
+    }
+
     // NOTE: Used when rehydrating from serde based deserialization. The layout will panic if
     //       used before setting the LayoutFunc. See [WindowManager::hydrate_and_init]
     #[cfg(feature = "serde")]
@@ -215,18 +240,19 @@ impl Layout {
     }
 
     /// Increase/decrease the size of the main area relative to secondary.
-    /// (clamps at 1.0 and 0.0 respectively)
+    /// (clamps at [MAX_MAIN_RATIO] and [MIN_MAIN_RATIO] respectively)
     pub fn update_main_ratio(&mut self, change: Change, step: f32) {
         match change {
             Change::More => self.ratio += step,
             Change::Less => self.ratio -= step,
         }
 
-        if self.ratio < 0.0 {
-            self.ratio = 0.0
-        } else if self.ratio > 1.0 {
-            self.ratio = 1.0;
-        }
+        self.ratio = self.ratio.clamp(MIN_MAIN_RATIO, MAX_MAIN_RATIO);
+    }
+
+    /// The current size of the main area relative to secondary
+    pub fn ratio(&self) -> f32 {
+        self.ratio
     }
 }
 
@@ -336,8 +362,11 @@ pub fn bottom_stack(
         .collect()
 }
 
-/// A simple monolve layout that places uses the maximum available space for the focused client and
+/// A simple monocle layout that uses the maximum available space for the focused client and
 /// unmaps all other windows.
+///
+/// This needs to be re-run whenever focus changes in order to show the newly focused client so
+/// it should be used with `follow_focus` set in its [LayoutConf]: see [Layout::monocle].
 pub fn monocle(
     clients: &[&Client],
     focused: Option<Xid>,
@@ -362,3 +391,160 @@ pub fn monocle(
         Vec::new()
     }
 }
+
+/// A spiral layout that gives each client half of the space remaining after the clients before
+/// it have been placed, working inwards clockwise from the left hand side of the screen. The
+/// last client fills whatever space is left.
+///
+/// The first split uses `ratio` to size the main client, clamped to between [MIN_MAIN_RATIO] and
+/// [MAX_MAIN_RATIO]: `max_main` is not meaningful for this layout and is ignored.
+pub fn fibonacci(
+    clients: &[&Client],
+    _: Option<Xid>,
+    monitor_region: &Region,
+    _: u32,
+    ratio: f32,
+) -> Vec<ResizeAction> {
+    let n = clients.len();
+    let ratio = ratio.clamp(MIN_MAIN_RATIO, MAX_MAIN_RATIO);
+    let mut remaining = *monitor_region;
+
+    clients
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if i == n - 1 {
+                return (c.id(), Some(remaining));
+            }
+
+            let frac = if i == 0 { ratio } else { 0.5 };
+            let (r, rest) = spiral_split(&remaining, i % 4, frac);
+            remaining = rest;
+
+            (c.id(), Some(r))
+        })
+        .collect()
+}
+
+// Split 'r' into the region for a single client and the space remaining after it, taking 'frac'
+// of 'r' from the left, top, right or bottom depending on 'side' (0-3)
+fn spiral_split(r: &Region, side: usize, frac: f32) -> (Region, Region) {
+    let (x, y, w, h) = r.values();
+    let pw = ((w as f32) * frac) as u32;
+    let ph = ((h as f32) * frac) as u32;
+
+    match side {
+        0 => (Region::new(x, y, pw, h), Region::new(x + pw, y, w - pw, h)),
+        1 => (Region::new(x, y, w, ph), Region::new(x, y + ph, w, h - ph)),
+        2 => (
+            Region::new(x + w - pw, y, pw, h),
+            Region::new(x, y, w - pw, h),
+        ),
+        _ => (
+            Region::new(x, y + h - ph, w, ph),
+            Region::new(x, y, w, h - ph),
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::xconnection::MockXConn;
+
+    fn fibonacci_regions(n: u32, ratio: f32) -> Vec<Option<Region>> {
+        let conn = MockXConn::new(vec![], vec![], vec![]);
+        let clients: Vec<Client> = (1..=n).map(|id| Client::new(&conn, id, 0, &[])).collect();
+        let refs: Vec<&Client> = clients.iter().collect();
+
+        fibonacci(&refs, None, &Region::new(0, 0, 100, 100), 1, ratio)
+            .into_iter()
+            .map(|(_, r)| r)
+            .collect()
+    }
+
+    test_cases! {
+        update_main_ratio;
+        args: (change: Change, step: f32, expected: f32);
+
+        case: increase => (Change::More, 0.1, 0.6);
+        case: decrease => (Change::Less, 0.1, 0.4);
+        case: clamped_high => (Change::More, 1.0, MAX_MAIN_RATIO);
+        case: clamped_low => (Change::Less, 1.0, MIN_MAIN_RATIO);
+
+        body: {
+            let mut l = Layout::new("t", LayoutConf::default(), side_stack, 1, 0.5);
+            l.update_main_ratio(change, step);
+            assert!((l.ratio() - expected).abs() < f32::EPSILON);
+        }
+    }
+
+    #[test]
+    fn monocle_only_positions_the_focused_client() {
+        let conn = MockXConn::new(vec![], vec![], vec![]);
+        let clients: Vec<Client> = (1..=3).map(|id| Client::new(&conn, id, 0, &[])).collect();
+        let refs: Vec<&Client> = clients.iter().collect();
+        let r = Region::new(0, 0, 100, 100);
+
+        assert_eq!(
+            monocle(&refs, Some(2), &r, 1, 0.6),
+            vec![(1, None), (2, Some(r)), (3, None)]
+        );
+    }
+
+    #[test]
+    fn fibonacci_with_no_clients_is_empty() {
+        assert!(fibonacci_regions(0, 0.5).is_empty());
+    }
+
+    #[test]
+    fn fibonacci_with_one_client_fills_the_region() {
+        assert_eq!(
+            fibonacci_regions(1, 0.6),
+            vec![Some(Region::new(0, 0, 100, 100))]
+        );
+    }
+
+    #[test]
+    fn fibonacci_first_split_uses_the_ratio() {
+        assert_eq!(
+            fibonacci_regions(2, 0.6),
+            vec![
+                Some(Region::new(0, 0, 60, 100)),
+                Some(Region::new(60, 0, 40, 100)),
+            ]
+        );
+    }
+
+    test_cases! {
+        fibonacci_clamps_the_ratio;
+        args: (ratio: f32, expected: Vec<Option<Region>>);
+
+        case: above_one => (1.5, vec![
+            Some(Region::new(0, 0, 95, 100)),
+            Some(Region::new(95, 0, 5, 100)),
+        ]);
+        case: negative => (-0.5, vec![
+            Some(Region::new(0, 0, 5, 100)),
+            Some(Region::new(5, 0, 95, 100)),
+        ]);
+
+        body: {
+            assert_eq!(fibonacci_regions(2, ratio), expected);
+        }
+    }
+
+    #[test]
+    fn fibonacci_spirals_inwards() {
+        assert_eq!(
+            fibonacci_regions(5, 0.5),
+            vec![
+                Some(Region::new(0, 0, 50, 100)),
+                Some(Region::new(50, 0, 50, 50)),
+                Some(Region::new(75, 50, 25, 50)),
+                Some(Region::new(50, 75, 25, 25)),
+                Some(Region::new(50, 50, 25, 25)),
+            ]
+        );
+    }
+}
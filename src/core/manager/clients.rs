@@ -115,7 +115,6 @@ impl Clients {
         prev
     }
 
-    #[allow(dead_code)]
     pub fn clear_focused(&mut self) {
         self.focused_client_id = None
     }
@@ -7,7 +7,7 @@ use crate::core::{
     manager::state::WmState,
     xconnection::{
         Atom, ClientMessage, ConfigureEvent, PointerChange, PropertyEvent, XAtomQuerier, XEvent,
-        Xid,
+        XState, Xid,
     },
 };
 
@@ -57,6 +57,8 @@ pub enum EventAction {
     SetActiveClient(Xid),
     /// The active workspace should be set to this index
     SetActiveWorkspace(usize),
+    /// A client is requesting (or no longer requesting) the user's attention
+    SetClientUrgent(Xid, bool),
     /// The active screen should be set based on point location
     SetScreenFromPoint(Option<Point>),
     /// An X window should be set fullscreen
@@ -69,7 +71,7 @@ pub enum EventAction {
 
 pub(super) fn process_next_event<X>(event: XEvent, state: &WmState, conn: &X) -> Vec<EventAction>
 where
-    X: XAtomQuerier,
+    X: XState,
 {
     match event {
         // Direct 1-n mappings of XEvents -> EventActions
@@ -77,40 +79,53 @@ where
         XEvent::Expose(_) => vec![], // FIXME: work out if this needs handling in the WindowManager
         XEvent::FocusIn(id) => vec![EventAction::FocusIn(id)],
         XEvent::KeyPress(code) => vec![EventAction::RunKeyBinding(code)],
-        XEvent::Leave(p) => vec![
-            EventAction::ClientFocusLost(p.id),
-            EventAction::SetScreenFromPoint(Some(p.abs)),
-        ],
         XEvent::MouseEvent(evt) => vec![EventAction::RunMouseBinding(evt)],
         XEvent::RandrNotify => vec![EventAction::DetectScreens],
         XEvent::ScreenChange => vec![EventAction::SetScreenFromPoint(None)],
-        XEvent::UnmapNotify(id) => vec![EventAction::Unmap(id)],
 
         // Require processing based on current WindowManager state
         XEvent::ClientMessage(msg) => process_client_message(state, conn, msg),
         XEvent::ConfigureNotify(evt) => process_configure_notify(evt),
         XEvent::ConfigureRequest(evt) => process_configure_request(evt),
         XEvent::Enter(p) => process_enter_notify(state, p),
+        XEvent::Leave(p) => process_leave_notify(state, p),
         XEvent::MapRequest(id, override_redirect) => {
             process_map_request(state, id, override_redirect)
         }
         XEvent::PropertyNotify(evt) => process_property_notify(evt),
+        XEvent::UnmapNotify(id, event) => process_unmap_notify(conn, id, event),
+    }
+}
+
+// Clients select StructureNotify and the root window selects SubstructureNotify, so each unmap
+// is reported twice: once to the client and once to root. Only the copy reported to root is
+// acted on so that each unmap is counted exactly once.
+fn process_unmap_notify<X>(conn: &X, id: Xid, event: Xid) -> Vec<EventAction>
+where
+    X: XState,
+{
+    if event == conn.root() {
+        vec![EventAction::Unmap(id)]
+    } else {
+        vec![]
     }
 }
 
-fn process_client_message<X>(_state: &WmState, conn: &X, msg: ClientMessage) -> Vec<EventAction>
+fn process_client_message<X>(state: &WmState, conn: &X, msg: ClientMessage) -> Vec<EventAction>
 where
     X: XAtomQuerier,
 {
     let data = msg.data();
     trace!(id = msg.id, dtype = ?msg.dtype, ?data, "got client message");
 
-    let is_fullscreen = |data: &[u32]| {
+    let has_state = |data: &[u32], atom: Atom| {
         data.iter()
             .map(|&a| conn.atom_name(a))
             .flatten()
-            .any(|s| s == Atom::NetWmStateFullscreen.as_ref())
+            .any(|s| s == atom.as_ref())
     };
+    let is_fullscreen = |data: &[u32]| has_state(data, Atom::NetWmStateFullscreen);
+    let demands_attention = |data: &[u32]| has_state(data, Atom::NetWmStateDemandsAttention);
 
     match Atom::from_str(&msg.dtype) {
         Ok(Atom::NetActiveWindow) => vec![EventAction::SetActiveClient(msg.id)],
@@ -124,6 +139,15 @@ where
                 should_fullscreen,
             )]
         }
+        Ok(Atom::NetWmState) if demands_attention(&data.as_u32()[1..3]) => {
+            // _NET_WM_STATE_ADD == 1, _NET_WM_STATE_TOGGLE == 2
+            let urgent = match data.as_usize()[0] {
+                1 => true,
+                2 => !state.clients.get(msg.id).is_some_and(|c| c.is_urgent()),
+                _ => false,
+            };
+            vec![EventAction::SetClientUrgent(msg.id, urgent)]
+        }
 
         _ => vec![],
     }
@@ -146,6 +170,10 @@ fn process_configure_request(evt: ConfigureEvent) -> Vec<EventAction> {
 }
 
 fn process_enter_notify(state: &WmState, p: PointerChange) -> Vec<EventAction> {
+    if !state.config.focus_follow_mouse || state.ignoring_enter_events() {
+        return vec![EventAction::SetScreenFromPoint(Some(p.abs))];
+    }
+
     let mut actions = vec![
         EventAction::ClientFocusGained(p.id),
         EventAction::SetScreenFromPoint(Some(p.abs)),
@@ -160,6 +188,17 @@ fn process_enter_notify(state: &WmState, p: PointerChange) -> Vec<EventAction> {
     actions
 }
 
+fn process_leave_notify(state: &WmState, p: PointerChange) -> Vec<EventAction> {
+    if state.config.focus_follow_mouse {
+        vec![
+            EventAction::ClientFocusLost(p.id),
+            EventAction::SetScreenFromPoint(Some(p.abs)),
+        ]
+    } else {
+        vec![EventAction::SetScreenFromPoint(Some(p.abs))]
+    }
+}
+
 // Processing around map_request is currently copied from dwm:
 //   - if override_redirect is set we completely ignore the window
 //   - if the client is in the client_map (i.e. we are already managing this client) then ignore
@@ -2,12 +2,18 @@ use crate::{
     core::{
         config::Config,
         hooks::HookName,
-        manager::{event::EventAction, state::WmState, util::pad_region},
+        manager::{
+            event::EventAction,
+            state::WmState,
+            util::{pad_region, tiling_region},
+        },
         xconnection::{XClientConfig, XClientHandler},
     },
     Result,
 };
 
+use std::time::Instant;
+
 #[tracing::instrument(level = "trace", err, skip(conn))]
 pub(super) fn layout_visible<X>(state: &mut WmState, conn: &X) -> Result<Vec<EventAction>>
 where
@@ -39,12 +45,13 @@ where
         show_bar,
         border_px,
         gap_px,
+        outer_gap_px,
         ..
     } = state.config;
 
     let (lc, aa) = state.workspaces.get_arrange_actions(
         wix,
-        s.region(show_bar),
+        tiling_region(&s.region(show_bar), outer_gap_px),
         &state
             .clients
             .clients_for_ids(&state.workspaces[wix].client_ids()),
@@ -54,6 +61,10 @@ where
         trace!(id, ?region, "positioning client");
         if let Some(region) = region {
             let reg = pad_region(&region, lc.gapless, gap_px, border_px);
+            let reg = state
+                .clients
+                .get(id)
+                .map_or(reg, |c| c.constrained_region(reg));
             conn.position_client(id, reg, border_px, false)?;
             state.clients.map_if_needed(id, conn)?;
         } else {
@@ -66,5 +77,7 @@ where
         conn.raise_client(id)?;
     }
 
+    state.last_layout = Some(Instant::now());
+
     Ok(Some(EventAction::RunHook(HookName::LayoutApplied(wix, i))))
 }
@@ -1,7 +1,7 @@
 //! The main user API and control logic for Penrose.
 use crate::{
     core::{
-        bindings::{KeyBindings, KeyCode, MouseBindings, MouseEvent},
+        bindings::{KeyBindings, KeyChain, KeyCode, MouseBindings, MouseEvent, MouseEventKind},
         client::Client,
         config::Config,
         data_types::{Change, Point, Region},
@@ -9,12 +9,16 @@ use crate::{
         ring::{Direction, InsertPoint, Selector},
         screen::Screen,
         workspace::Workspace,
-        xconnection::{Atom, ClientMessageKind, WindowState, XConn, Xid},
+        xconnection::{Atom, ClientMessageKind, WindowState, XConn, XEvent, Xid},
     },
     ErrorHandler, PenroseError, Result,
 };
 use nix::sys::signal::{signal, SigHandler, Signal};
-use std::{cell::Cell, fmt};
+use std::{
+    cell::Cell,
+    fmt,
+    time::{Duration, Instant},
+};
 use tracing::Level;
 
 #[cfg(feature = "serde")]
@@ -23,6 +27,16 @@ use crate::core::{helpers::logging_error_handler, layout::LayoutFunc};
 #[cfg(feature = "serde")]
 use std::collections::HashMap;
 
+#[cfg(feature = "ipc")]
+use crate::core::ipc::IpcServer;
+#[cfg(feature = "ipc")]
+use std::{os::unix::io::AsRawFd, path::Path};
+
+// How often to check for X events while waiting on IPC commands when the XConn is unable to
+// provide a file descriptor to wait on
+#[cfg(feature = "ipc")]
+const IPC_POLL_INTERVAL_MS: i32 = 20;
+
 mod clients;
 mod event;
 mod layout;
@@ -37,6 +51,7 @@ use event::EventAction;
 use layout::{apply_layout, layout_visible};
 use screens::Screens;
 use state::WmState;
+use util::dragged_region;
 use workspaces::Workspaces;
 
 #[cfg(feature = "serde")]
@@ -44,6 +59,20 @@ fn default_hooks<X: XConn>() -> Cell<Hooks<X>> {
     Cell::new(Vec::new())
 }
 
+#[cfg(feature = "serde")]
+fn default_key_chain<X: XConn>() -> Option<(KeyChain<X>, Option<Instant>)> {
+    None
+}
+
+// A mouse drag of a floating client: the pointer position and client geometry are recorded when
+// the button is pressed so that motion events can be applied relative to them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) struct MouseDrag {
+    id: Xid,
+    origin: Point,
+    region: Region,
+}
+
 /// WindowManager is the primary struct / owner of the event loop for penrose.
 ///
 /// It handles most (if not all) of the communication with the underlying [XConn], responding to
@@ -74,6 +103,17 @@ pub struct WindowManager<X: XConn> {
     pub(super) error_handler: ErrorHandler,
     #[cfg_attr(feature = "serde", serde(skip))]
     pub(super) hydrated: bool,
+    // (gap_px, outer_gap_px) to restore when gaps are toggled back on
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) stashed_gaps: Option<(u32, u32)>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) drag: Option<MouseDrag>,
+    // The key chain waiting for its next key press and when it should be cancelled
+    #[cfg_attr(feature = "serde", serde(skip, default = "default_key_chain"))]
+    pub(super) key_chain: Option<(KeyChain<X>, Option<Instant>)>,
+    #[cfg(feature = "ipc")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) ipc: Option<IpcServer>,
 }
 
 impl<X: XConn> fmt::Debug for WindowManager<X> {
@@ -112,6 +152,7 @@ impl<X: XConn> WindowManager<X> {
             clients,
             screens,
             workspaces,
+            last_layout: None,
         };
 
         Self {
@@ -122,6 +163,11 @@ impl<X: XConn> WindowManager<X> {
             running: false,
             hydrated: true,
             error_handler,
+            stashed_gaps: None,
+            drag: None,
+            key_chain: None,
+            #[cfg(feature = "ipc")]
+            ipc: None,
         }
     }
 
@@ -255,6 +301,7 @@ impl<X: XConn> WindowManager<X> {
         match hook_name {
             Startup => run_hooks!(startup, self,),
             NewClient(id) => run_hooks!(new_client, self, id),
+            ClientClosed(id) => run_hooks!(client_closed, self, id),
             RemoveClient(id) => run_hooks!(remove_client, self, id),
             ClientAddedToWorkspace(id, wix) => run_hooks!(client_added_to_workspace, self, id, wix),
             ClientNameUpdated(id, name, is_root) => {
@@ -325,15 +372,26 @@ impl<X: XConn> WindowManager<X> {
             MoveClientIfFloating(id, r) => self.handle_move_if_floating(id, r)?,
             RunHook(hook_name) => self.run_hook(hook_name),
             RunKeyBinding(e) => match key_bindings {
-                Some(kb) => self.run_key_binding(e, kb),
+                Some(kb) => match self.key_chain.take() {
+                    Some((chain, deadline)) => self.run_key_chain(chain, deadline, e)?,
+                    None => self.run_key_binding(e, kb),
+                },
                 None => return Err(perror!("keybindings can only be triggered from X events")),
             },
             RunMouseBinding(e) => match mouse_bindings {
-                Some(mb) => self.run_mouse_binding(e, mb),
+                Some(mb) => {
+                    self.start_drag_if_needed(&e)?;
+                    let kind = e.kind;
+                    self.run_mouse_binding(e, mb);
+                    if kind == MouseEventKind::Release {
+                        self.drag = None;
+                    }
+                }
                 None => return Err(perror!("mousebindings can only be triggered from X events")),
             },
             SetActiveClient(id) => self.set_active_client(id)?,
             SetActiveWorkspace(wix) => self.focus_workspace(&Selector::Index(wix))?,
+            SetClientUrgent(id, urgent) => self.set_urgent(id, urgent),
             SetScreenFromPoint(p) => self.set_screen_from_point(p)?,
             ToggleClientFullScreen(id, should_fullscreen) => {
                 self.set_fullscreen(id, should_fullscreen)?;
@@ -378,6 +436,16 @@ impl<X: XConn> WindowManager<X> {
             panic!("unable to set signal handler: {}", e);
         }
 
+        for (_, state) in mouse_bindings
+            .keys()
+            .filter(|(_, s)| s.modifiers.is_empty())
+        {
+            warn!(
+                ?state.button,
+                "mouse binding without modifiers: all clicks with this button will be intercepted"
+            );
+        }
+
         trace!("grabbing key and mouse bindings");
         self.conn.grab_keys(&key_bindings, &mouse_bindings)?;
 
@@ -389,12 +457,16 @@ impl<X: XConn> WindowManager<X> {
 
         trace!("entering main event loop");
         while self.running {
-            match self.conn.wait_for_event() {
+            match self.next_event() {
                 Ok(event) => {
                     let span = span!(target: "penrose", Level::DEBUG, "XEvent", %event);
                     let _enter = span.enter();
                     trace!(details = ?event, "event details");
 
+                    if let Err(e) = self.cancel_key_chain_if_expired() {
+                        (self.error_handler)(e);
+                    }
+
                     let actions = process_next_event(event, &self.state, &self.conn);
                     for action in actions {
                         if let Err(e) = self.handle_event_action(
@@ -410,13 +482,77 @@ impl<X: XConn> WindowManager<X> {
                     self.conn.flush();
                 }
 
-                Err(e) => (self.error_handler)(PenroseError::X(e)),
+                Err(e) => (self.error_handler)(e),
             }
         }
 
         Ok(())
     }
 
+    /// Listen for [Command][1]s sent to a Unix domain socket at `path`, running them from the main
+    /// event loop in between handling X events. See the [ipc][2] module for details.
+    ///
+    /// # Errors
+    /// An error is returned if the socket can not be created or if another process is already
+    /// listening on `path`.
+    ///
+    /// [1]: crate::core::ipc::Command
+    /// [2]: crate::core::ipc
+    #[cfg(feature = "ipc")]
+    pub fn listen_for_commands(&mut self, path: impl AsRef<Path>) -> Result<()> {
+        self.ipc = Some(IpcServer::bind(path)?);
+        Ok(())
+    }
+
+    fn next_event(&mut self) -> Result<XEvent> {
+        #[cfg(feature = "ipc")]
+        if let Some(server) = self.ipc.take() {
+            let event = self.next_event_or_commands(&server);
+            self.ipc = Some(server);
+            return event;
+        }
+
+        Ok(self.conn.wait_for_event()?)
+    }
+
+    // Wait on both the X connection and the IPC socket, running any commands that arrive until
+    // there is an X event to return.
+    #[cfg(feature = "ipc")]
+    fn next_event_or_commands(&mut self, server: &IpcServer) -> Result<XEvent> {
+        use nix::{
+            errno::Errno,
+            poll::{poll, PollFd, PollFlags},
+        };
+
+        loop {
+            // Events may already have been read from the X connection in the process of
+            // handling previous requests, so these need clearing before waiting on its fd.
+            if let Some(event) = self.conn.poll_for_event()? {
+                return Ok(event);
+            }
+
+            let mut fds = vec![PollFd::new(server.as_raw_fd(), PollFlags::POLLIN)];
+            let timeout = match self.conn.connection_fd() {
+                Some(fd) => {
+                    fds.push(PollFd::new(fd, PollFlags::POLLIN));
+                    -1
+                }
+                None => IPC_POLL_INTERVAL_MS,
+            };
+
+            match poll(&mut fds, timeout) {
+                Ok(_) | Err(Errno::EINTR) => (),
+                Err(e) => return Err(std::io::Error::from(e).into()),
+            }
+
+            if matches!(fds[0].revents(), Some(r) if r.contains(PollFlags::POLLIN)) {
+                server.handle_pending(self)?;
+                self.run_hook(HookName::EventHandled);
+                self.conn.flush();
+            }
+        }
+    }
+
     /*
      * Top Level EventAction handlers
      */
@@ -438,16 +574,7 @@ impl<X: XConn> WindowManager<X> {
                 // The requested id wasn't something we know about and we don't have any clients on the
                 // active workspace so all we can do is drop our focused state and revert focus back to
                 // the root window.
-                None => {
-                    let root = self.conn.root();
-                    if let Err(e) = self.conn.focus_client(root) {
-                        warn!("unable to focus root window: {}", e);
-                    }
-                    let active_window = Atom::NetActiveWindow.as_ref();
-                    self.conn.delete_prop(root, active_window)?;
-                    self.run_hook(HookName::FocusChange(root));
-                    return Ok(());
-                }
+                None => return self.clear_focus(),
             }
         };
 
@@ -462,6 +589,7 @@ impl<X: XConn> WindowManager<X> {
         self.focus_screen(&Selector::Condition(&|s| s.wix == wix));
         self.clients
             .set_x_focus(target, accepts_focus, &self.conn)?;
+        self.clear_urgency(target);
 
         if let Some(ws) = self.workspaces.get_mut(wix) {
             ws.focus_client(target);
@@ -477,9 +605,30 @@ impl<X: XConn> WindowManager<X> {
         Ok(())
     }
 
+    // Drop our focused client state and revert focus back to the root window. There is no
+    // active client after this so _NET_ACTIVE_WINDOW is removed rather than left pointing at
+    // a window that does not have focus.
+    fn clear_focus(&mut self) -> Result<()> {
+        self.clients.clear_focused();
+        let root = self.conn.root();
+        if let Err(e) = self.conn.focus_client(root) {
+            warn!("unable to focus root window: {}", e);
+        }
+        self.conn
+            .delete_prop(root, Atom::NetActiveWindow.as_ref())?;
+        self.run_hook(HookName::FocusChange(root));
+
+        Ok(())
+    }
+
     // The given window ID has been destroyed so remove our internal state referencing it.
     #[tracing::instrument(level = "trace", err, skip(self))]
     fn remove_client(&mut self, id: Xid) -> Result<()> {
+        if self.clients.is_known(id) {
+            self.run_hook(HookName::ClientClosed(id));
+        }
+
+        let was_focused = self.clients.focused_client_id() == Some(id);
         if let Some(client) = self.clients.remove(id) {
             let wix = client.workspace();
             self.workspaces.remove_client(wix, id);
@@ -488,6 +637,10 @@ impl<X: XConn> WindowManager<X> {
                 self.apply_layout(wix)?;
             }
 
+            if was_focused && self.active_workspace().focused_client().is_none() {
+                self.clear_focus()?;
+            }
+
             self.update_known_x_clients()?;
             self.run_hook(HookName::RemoveClient(id));
         } else {
@@ -524,13 +677,29 @@ impl<X: XConn> WindowManager<X> {
 
     /// Query the [XConn] for the current connected [Screen] list and reposition displayed
     /// [Workspace] instances if needed.
+    ///
+    /// This is run automatically whenever RandR reports a change to the connected outputs.
+    /// Newly connected screens are given the first workspaces that are not currently visible and
+    /// the clients of any workspace left without a screen are unmapped. If the focused screen was
+    /// disconnected then its workspace is moved to the first screen.
     #[tracing::instrument(level = "trace", err, skip(self))]
     pub fn detect_screens(&mut self) -> Result<()> {
+        let previously_visible = self.screens.visible_workspaces();
         let actions = self
             .state
             .screens
             .update_known_screens(&self.conn, self.workspaces.len())?;
 
+        let visible = self.screens.visible_workspaces();
+        for wix in previously_visible
+            .into_iter()
+            .filter(|w| !visible.contains(w))
+        {
+            for id in self.workspaces[wix].client_ids() {
+                self.state.clients.unmap_if_needed(id, &self.conn)?;
+            }
+        }
+
         self.handle_event_actions(actions)
     }
 
@@ -539,7 +708,8 @@ impl<X: XConn> WindowManager<X> {
     fn handle_map_request(&mut self, id: Xid) -> Result<()> {
         trace!(id, "handling map request");
         let classes = str_slice!(self.config.floating_classes);
-        let client = Client::new(&self.conn, id, self.screens.active_ws_index(), classes);
+        let mut client = Client::new(&self.conn, id, self.screens.active_ws_index(), classes);
+        self.apply_client_rules(&mut client);
         let is_managed_type = self.conn.is_managed_client(&client);
         trace!(id, ?client.wm_name, ?client.wm_class, ?client.wm_type, "client details");
 
@@ -551,6 +721,7 @@ impl<X: XConn> WindowManager<X> {
             .clients
             .get(id)
             .map(|c| (c.workspace(), c.wm_hints.clone(), c.wm_managed, c.floating));
+        let normal_hints = self.clients.get(id).and_then(|c| c.wm_normal_hints.clone());
 
         if details.is_none() {
             debug!(id, "Client was removed from the client map by a hook");
@@ -581,28 +752,54 @@ impl<X: XConn> WindowManager<X> {
                     id,
                     s.region(self.config.show_bar),
                     self.config.border_px,
+                    normal_hints.as_ref(),
                 )?
             }
         }
 
         self.conn.mark_new_client(id)?;
-        self.update_focus(id)?;
+        let active = wix == self.screens.active_ws_index();
+        if active {
+            self.update_focus(id)?;
+        }
         self.update_known_x_clients()?;
 
-        if wix == self.screens.active_ws_index() {
+        if self.screens.visible_workspaces().contains(&wix) {
             self.apply_layout(wix)?;
             self.state.clients.map_if_needed(id, &self.conn)?;
-            let s = self.screens.focused();
-            self.conn.warp_cursor(Some(id), s)?;
+            if active {
+                let s = self.screens.focused();
+                self.conn.warp_cursor(Some(id), s)?;
+            }
         }
 
         Ok(())
     }
 
+    // Apply the first matching ClientRule (if any) to a newly mapped client
+    fn apply_client_rules(&self, client: &mut Client) {
+        let rule = match self.config.client_rules.iter().find(|r| r.matches(client)) {
+            Some(rule) => rule,
+            None => return,
+        };
+
+        debug!(id = client.id(), ?rule, "applying client rule");
+        match rule.workspace {
+            Some(wix) if wix < self.workspaces.len() => client.set_workspace(wix),
+            Some(wix) => warn!(wix, "client rule workspace index out of range"),
+            None => (),
+        }
+
+        if rule.floating {
+            client.set_floating(true);
+        }
+    }
+
     fn handle_move_if_floating(&mut self, id: Xid, r: Region) -> Result<()> {
         if let Some(client) = self.clients.get(id) {
             if client.floating {
                 debug!(id, region = ?r, "repositioning floating window");
+                let r = client.constrained_region(r);
                 let bpx = self.config.border_px;
                 self.conn.position_client(id, r, bpx, true)?;
             }
@@ -611,11 +808,53 @@ impl<X: XConn> WindowManager<X> {
     }
 
     fn handle_prop_change(&mut self, id: Xid, atom: String, is_root: bool) -> Result<()> {
-        trace!(id, is_root, ?atom, "dropping prop change (unimplemented)");
+        let urgency_prop = atom == Atom::WmHints.as_ref() || atom == Atom::NetWmState.as_ref();
+        if is_root || !urgency_prop || !self.clients.is_known(id) {
+            trace!(id, is_root, ?atom, "dropping prop change (unimplemented)");
+            return Ok(());
+        }
+
+        let urgent = self.conn.client_is_urgent(id)?;
+        self.set_urgent(id, urgent);
+
         Ok(())
     }
 
+    // The focused client already has the user's attention so it is never marked as urgent
+    fn set_urgent(&mut self, id: Xid, urgent: bool) {
+        let urgent = urgent && self.clients.focused_client_id() != Some(id);
+        self.state.clients.modify(id, |c| c.urgent = urgent);
+    }
+
+    // Focusing an urgent client acknowledges it: as well as dropping our own flag we clear
+    // _NET_WM_STATE_DEMANDS_ATTENTION so that pagers and other tools stop flagging it.
+    fn clear_urgency(&mut self, id: Xid) {
+        if !self.clients.get(id).is_some_and(|c| c.is_urgent()) {
+            return;
+        }
+
+        self.state.clients.modify(id, |c| c.urgent = false);
+        if let Err(e) = self.conn.clear_demands_attention(id) {
+            warn!(id, "unable to clear _NET_WM_STATE_DEMANDS_ATTENTION: {}", e);
+        }
+    }
+
+    // Unmaps that we requested ourselves leave the client under management: any other unmap of
+    // a known client means that the owning program has withdrawn its window.
     fn handle_unmap_notify(&mut self, id: Xid) -> Result<()> {
+        let withdrawn = match self.clients.get_mut(id) {
+            Some(c) if c.pending_unmaps > 0 => {
+                c.pending_unmaps -= 1;
+                false
+            }
+            Some(_) => true,
+            None => false,
+        };
+
+        if withdrawn {
+            self.remove_client(id)?;
+        }
+
         Ok(self.conn.set_client_state(id, WindowState::Withdrawn)?)
     }
 
@@ -632,6 +871,65 @@ impl<X: XConn> WindowManager<X> {
         }
     }
 
+    // Called from the handler returned by key_chain
+    pub(crate) fn start_key_chain(&mut self, chain: KeyChain<X>) -> Result<()> {
+        self.conn.grab_keyboard()?;
+        let timeout = self.config.key_chain_timeout_ms;
+        let deadline = if timeout > 0 {
+            Some(Instant::now() + Duration::from_millis(timeout))
+        } else {
+            None
+        };
+        trace!(?chain, ?deadline, "starting key chain");
+        self.key_chain = Some((chain, deadline));
+
+        Ok(())
+    }
+
+    // The key press following the start of a key chain is run from the chain's own bindings.
+    // NOTE: As with run_key_binding, this defers control to user defined actions.
+    fn run_key_chain(
+        &mut self,
+        chain: KeyChain<X>,
+        deadline: Option<Instant>,
+        k: KeyCode,
+    ) -> Result<()> {
+        if chain.is_modifier(&k) {
+            self.key_chain = Some((chain, deadline));
+            return Ok(());
+        }
+
+        self.conn.ungrab_keyboard()?;
+        if chain.is_escape(&k) {
+            debug!("key chain cancelled");
+            return Ok(());
+        }
+
+        let mut bindings = chain.bindings().borrow_mut();
+        match bindings.get_mut(&k) {
+            Some(action) => {
+                if let Err(e) = action(self) {
+                    (self.error_handler)(e);
+                }
+            }
+            None => debug!(?k, "key is not bound in the active key chain: cancelling"),
+        }
+
+        Ok(())
+    }
+
+    fn cancel_key_chain_if_expired(&mut self) -> Result<()> {
+        if let Some((_, Some(deadline))) = self.key_chain {
+            if Instant::now() >= deadline {
+                debug!("key chain timed out");
+                self.key_chain = None;
+                self.conn.ungrab_keyboard()?;
+            }
+        }
+
+        Ok(())
+    }
+
     // NOTE: This defers control of the [WindowManager] to the user's mouse-binding action
     //       which can lead to arbitrary calls to public methods on the [WindowManager]
     //       including mutable methods.
@@ -645,7 +943,18 @@ impl<X: XConn> WindowManager<X> {
         }
     }
 
+    // Handle a _NET_ACTIVE_WINDOW request from an external program (a pager, wmctrl etc),
+    // bringing the workspace of the requested client into view if it is currently hidden.
     fn set_active_client(&mut self, id: Xid) -> Result<()> {
+        let wix = self
+            .clients
+            .workspace_index_for_client(id)
+            .ok_or(PenroseError::UnknownClient(id))?;
+
+        if !self.screens.visible_workspaces().contains(&wix) {
+            self.focus_workspace(&Selector::Index(wix))?;
+        }
+
         self.focus_client(&Selector::WinId(id))
             .map_err(|_| PenroseError::UnknownClient(id))
             .map(|_| ())
@@ -821,6 +1130,52 @@ impl<X: XConn> WindowManager<X> {
         self.focus_workspace(&Selector::Index(wix)) // focus_workspace will pull it to the new screen
     }
 
+    /// Move the focused [Workspace] to the [Screen] at index `screen_ix`, focusing that screen.
+    ///
+    /// Any workspace already shown on the target screen is swapped onto the screen that was
+    /// previously focused. Layouts are re-applied to fit the new screen sizes and the cursor is
+    /// warped to follow the moved workspace. This is a no-op if there is only one screen.
+    pub fn move_workspace_to_screen(&mut self, screen_ix: usize) -> Result<()> {
+        if !self.check_screen_index(screen_ix)? || screen_ix == self.screens.focused_index() {
+            return Ok(());
+        }
+
+        let wix = self.screens.active_ws_index();
+        self.focus_screen(&Selector::Index(screen_ix));
+        self.focus_workspace(&Selector::Index(wix))?;
+
+        let focused = self.workspaces.get_workspace(wix)?.focused_client();
+        let screen = self.screens.focused();
+        Ok(self.conn.warp_cursor(focused, screen)?)
+    }
+
+    /// Move the focused [Client] to the active [Workspace] of the [Screen] at index `screen_ix`.
+    ///
+    /// Focus remains on the current screen. This is a no-op if there is only one screen.
+    pub fn move_client_to_screen(&mut self, screen_ix: usize) -> Result<()> {
+        if !self.check_screen_index(screen_ix)? {
+            return Ok(());
+        }
+
+        self.client_to_screen(&Selector::Index(screen_ix))
+    }
+
+    // Whether or not there is another screen that could be moved to, erroring if 'screen_ix'
+    // is out of range
+    fn check_screen_index(&self, screen_ix: usize) -> Result<bool> {
+        let n_screens = self.screens.n_screens();
+        if n_screens == 1 {
+            info!("only one screen is connected: ignoring request to move to another screen");
+            return Ok(false);
+        }
+
+        if screen_ix >= n_screens {
+            return Err(perror!("unknown screen index: {}", screen_ix));
+        }
+
+        Ok(true)
+    }
+
     /// Cycle focus between [clients][1] for the active [Workspace]
     ///
     /// [1]: Client
@@ -837,6 +1192,120 @@ impl<X: XConn> WindowManager<X> {
         Ok(())
     }
 
+    /// Toggle whether or not the [Client] with the given id is floating.
+    ///
+    /// Floating clients are excluded from the layout of their workspace and stacked above tiled
+    /// clients. A client that was previously floating is restored to its last known floating
+    /// position, otherwise it is centered on its screen at half the screen size. Toggling a
+    /// floating client returns it to its original place in the layout.
+    pub fn toggle_floating(&mut self, id: Xid) -> Result<()> {
+        let (wix, floating, floating_region) = match self.clients.get(id) {
+            Some(c) => (c.workspace(), c.floating, c.floating_region),
+            None => return Err(PenroseError::UnknownClient(id)),
+        };
+
+        if floating {
+            let r = self.conn.client_geometry(id)?;
+            self.clients.modify(id, |c| {
+                c.floating = false;
+                c.floating_region = Some(r);
+            });
+        } else {
+            self.clients.modify(id, |c| c.floating = true);
+            if let Some((_, s)) = self.screens.indexed_screen_for_workspace(wix) {
+                let r = match floating_region {
+                    Some(r) => r,
+                    None => {
+                        let screen = s.region(self.config.show_bar);
+                        screen.scale_w(0.5).scale_h(0.5).centered_in(&screen)?
+                    }
+                };
+                let r = self.clients.get(id).map_or(r, |c| c.constrained_region(r));
+                self.conn
+                    .position_client(id, r, self.config.border_px, true)?;
+            }
+        }
+
+        if self.screens.visible_workspaces().contains(&wix) {
+            self.apply_layout(wix)?;
+        }
+
+        Ok(())
+    }
+
+    // Record the focused client's geometry on a button press so that it can be dragged
+    fn start_drag_if_needed(&mut self, e: &MouseEvent) -> Result<()> {
+        if e.kind != MouseEventKind::Press {
+            return Ok(());
+        }
+
+        self.drag = match self.clients.focused_client() {
+            Some(c) if c.floating => Some(MouseDrag {
+                id: c.id(),
+                origin: e.rpt,
+                region: self.conn.client_geometry(c.id())?,
+            }),
+            _ => None,
+        };
+
+        Ok(())
+    }
+
+    /// The change in pointer position since the start of the current mouse drag.
+    ///
+    /// A drag starts when a bound mouse button is pressed while a floating [Client] has focus and
+    /// ends when that button is released: this is `None` at all other times.
+    pub fn drag_delta(&self, e: &MouseEvent) -> Option<(i32, i32)> {
+        self.drag.map(|d| {
+            let dx = e.rpt.x as i32 - d.origin.x as i32;
+            let dy = e.rpt.y as i32 - d.origin.y as i32;
+            (dx, dy)
+        })
+    }
+
+    /// Move the floating [Client] being dragged so that it follows the pointer.
+    ///
+    /// This is intended to be bound to the `Motion` event for a mouse button and modifier
+    /// combination. Tiled clients are left in place.
+    ///
+    /// ```no_run
+    /// # use penrose::{core::bindings::MouseEvent, gen_mousebindings, WindowManager};
+    /// # fn example<X: penrose::core::xconnection::XConn>() {
+    /// let mouse_bindings = gen_mousebindings! {
+    ///     Motion Left + [Meta] => |wm: &mut WindowManager<X>, e: &MouseEvent| wm.drag_move_client(e),
+    ///     Motion Right + [Meta] => |wm: &mut WindowManager<X>, e: &MouseEvent| wm.drag_resize_client(e)
+    /// };
+    /// # }
+    /// ```
+    pub fn drag_move_client(&mut self, e: &MouseEvent) -> Result<()> {
+        self.mouse_drag_client(e, false)
+    }
+
+    /// Resize the floating [Client] being dragged, keeping its top left corner in place.
+    ///
+    /// See [drag_move_client][WindowManager::drag_move_client] for an example of binding this
+    /// to a mouse button. Any size hints set by the client are respected.
+    pub fn drag_resize_client(&mut self, e: &MouseEvent) -> Result<()> {
+        self.mouse_drag_client(e, true)
+    }
+
+    fn mouse_drag_client(&mut self, e: &MouseEvent, resize: bool) -> Result<()> {
+        let (drag, delta) = match (self.drag, self.drag_delta(e)) {
+            (Some(drag), Some(delta)) => (drag, delta),
+            _ => return Ok(()),
+        };
+
+        let r = dragged_region(&drag.region, delta, resize);
+        let r = match self.clients.get(drag.id) {
+            Some(c) if c.floating => c.constrained_region(r),
+            _ => return Ok(()),
+        };
+
+        Ok(self
+            .conn
+            .position_client(drag.id, r, self.config.border_px, true)?)
+    }
+
     /// Focus the [Client] matching the given [Selector]
     pub fn focus_client(&mut self, selector: &Selector<'_, Client>) -> Result<Xid> {
         let id = match self.client(selector) {
@@ -915,6 +1384,60 @@ impl<X: XConn> WindowManager<X> {
         self.apply_layout(wix)
     }
 
+    /// Increase the current [layout][1] main_ratio by `step`, clamping at
+    /// [MAX_MAIN_RATIO][2].
+    ///
+    /// The change is applied to the active [layout][1] on the [Workspace] that currently holds
+    /// focus and is retained when switching away from and back to that [Workspace].
+    ///
+    /// [1]: crate::core::layout::Layout
+    /// [2]: crate::core::layout::MAX_MAIN_RATIO
+    pub fn increase_main_ratio(&mut self, step: f32) -> Result<()> {
+        let wix = self.screens.active_ws_index();
+        self.workspaces
+            .update_main_ratio_by(wix, Change::More, step);
+        self.apply_layout(wix)
+    }
+
+    /// Decrease the current [layout][1] main_ratio by `step`, clamping at
+    /// [MIN_MAIN_RATIO][2].
+    ///
+    /// The change is applied to the active [layout][1] on the [Workspace] that currently holds
+    /// focus and is retained when switching away from and back to that [Workspace].
+    ///
+    /// [1]: crate::core::layout::Layout
+    /// [2]: crate::core::layout::MIN_MAIN_RATIO
+    pub fn decrease_main_ratio(&mut self, step: f32) -> Result<()> {
+        let wix = self.screens.active_ws_index();
+        self.workspaces
+            .update_main_ratio_by(wix, Change::Less, step);
+        self.apply_layout(wix)
+    }
+
+    /// Set the gap left around each tiled client and the additional gap left between tiled
+    /// clients and the edges of the screen, in pixels, re-applying the layout of each visible
+    /// workspace. Setting both to zero tiles clients edge to edge.
+    pub fn set_gaps(&mut self, gap_px: u32, outer_gap_px: u32) -> Result<()> {
+        self.state.config.gap_px = gap_px;
+        self.state.config.outer_gap_px = outer_gap_px;
+        self.stashed_gaps = None;
+        self.layout_visible()
+    }
+
+    /// Toggle gaps between tiled clients on and off, restoring the previous gaps when they are
+    /// toggled back on. This can be bound to a key using `run_internal!(toggle_gaps)`.
+    pub fn toggle_gaps(&mut self) -> Result<()> {
+        let current = (self.config.gap_px, self.config.outer_gap_px);
+        let (gap_px, outer_gap_px) = self.stashed_gaps.take().unwrap_or((0, 0));
+        self.state.config.gap_px = gap_px;
+        self.state.config.outer_gap_px = outer_gap_px;
+        if current != (0, 0) {
+            self.stashed_gaps = Some(current);
+        }
+
+        self.layout_visible()
+    }
+
     /// Shut down the WindowManager, running any required cleanup and exiting penrose
     ///
     /// **NOTE**: any registered hooks on the `WindowManager` will still run following calling this
@@ -981,9 +1504,9 @@ impl<X: XConn> WindowManager<X> {
                     // update xproperty _NET_CURRENT_DESKTOP
                     self.conn.set_current_workspace(index)?;
 
-                    let ws = self.workspaces.get_workspace(index)?;
-                    if let Some(id) = ws.focused_client() {
-                        self.update_focus(id)?;
+                    match self.workspaces.get_workspace(index)?.focused_client() {
+                        Some(id) => self.update_focus(id)?,
+                        None => self.clear_focus()?,
                     };
 
                     self.workspaces.focus(&Selector::Index(index));
@@ -1008,9 +1531,9 @@ impl<X: XConn> WindowManager<X> {
             self.apply_layout(index)?;
             self.conn.set_current_workspace(index)?;
 
-            let ws = self.workspaces.get_workspace(index)?;
-            if let Some(id) = ws.focused_client() {
-                self.update_focus(id)?;
+            match self.workspaces.get_workspace(index)?.focused_client() {
+                Some(id) => self.update_focus(id)?,
+                None => self.clear_focus()?,
             };
 
             self.workspaces.focus(&Selector::Index(index));
@@ -1096,14 +1619,38 @@ impl<X: XConn> WindowManager<X> {
             .expect("no active workspace")
     }
 
+    /// The IDs of all clients that are currently requesting the user's attention, either through
+    /// the urgency flag in their `WM_HINTS` or `_NET_WM_STATE_DEMANDS_ATTENTION`.
+    pub fn urgent_windows(&self) -> Vec<Xid> {
+        self.urgent_clients().iter().map(|c| c.id()).collect()
+    }
+
+    /// The [Client]s that are currently requesting the user's attention. Clients stop being
+    /// urgent once they are focused.
+    pub fn urgent_clients(&self) -> Vec<&Client> {
+        self.clients
+            .all_known_ids()
+            .into_iter()
+            .flat_map(|id| self.clients.get(id))
+            .filter(|c| c.is_urgent())
+            .collect()
+    }
+
     /// The currently focused workspace indices being shown on each screen
     pub fn focused_workspaces(&self) -> Vec<usize> {
         self.screens.visible_workspaces()
     }
 
     /// Add a new workspace at `index`, shifting all workspaces with indices greater to the right.
+    ///
+    /// Clients and screens on the shifted workspaces are updated to track their new index.
     pub fn add_workspace(&mut self, index: usize, ws: Workspace) -> Result<()> {
+        if index > self.workspaces.len() {
+            return Err(PenroseError::UnknownWorkspace(index));
+        }
+
         self.workspaces.add_workspace(index, ws);
+        self.shift_workspace_indices(index, true)?;
         self.update_x_workspace_details()
     }
 
@@ -1113,8 +1660,13 @@ impl<X: XConn> WindowManager<X> {
         self.update_x_workspace_details()
     }
 
-    /// Remove a Workspace from the WindowManager. All clients that were present on the removed
-    /// workspace will be destroyed. Xid selectors will be ignored.
+    /// Remove a Workspace from the WindowManager. Xid selectors will be ignored.
+    ///
+    /// All clients that were present on the removed workspace are moved to an adjacent
+    /// workspace (the one before it, or the one after it if it was the first workspace). If the
+    /// removed workspace was being shown on a screen then the adjacent workspace is shown in its
+    /// place, or the nearest workspace that is not already visible on another screen. The
+    /// removed workspace is returned with no clients.
     pub fn remove_workspace(
         &mut self,
         selector: &Selector<'_, Workspace>,
@@ -1123,18 +1675,102 @@ impl<X: XConn> WindowManager<X> {
             return Err(perror!("must have at least one workspace per screen"));
         }
 
-        let ws = self.workspaces.remove_workspace(&selector)?;
-        ws.iter().try_for_each(|c| self.remove_client(*c))?;
+        let wix = self
+            .workspaces
+            .index(selector)
+            .ok_or_else(|| perror!("unknown workspace"))?;
+        let visible = self.screens.visible_workspaces();
+        let target = match visible.iter().position(|&ix| ix == wix) {
+            Some(i) => {
+                let r = replacement_workspace(wix, self.workspaces.len(), &visible);
+                self.show_workspace_on_screen(i, r)?;
+                r
+            }
+            None if wix > 0 => wix - 1,
+            None => wix + 1,
+        };
+
+        // Migrated clients are placed after any existing clients, keeping their relative order
+        let ids = self.workspaces.get_workspace(wix)?.client_ids();
+        for id in ids.iter() {
+            self.workspaces.remove_client(wix, *id);
+            if let Some(ws) = self.workspaces.get_mut(target) {
+                ws.add_client(*id, &InsertPoint::Last)?;
+            }
+            self.clients.set_client_workspace(*id, target);
+            self.conn.set_client_workspace(*id, target)?;
+            self.run_hook(HookName::ClientAddedToWorkspace(*id, target));
+        }
+
+        if self.screens.visible_workspaces().contains(&target) {
+            for id in ids.iter() {
+                self.state.clients.map_if_needed(*id, &self.conn)?;
+            }
+            self.apply_layout(target)?;
+        }
 
-        // Focus the workspace before the one we just removed. There is always at least one
-        // workspace before this one due to the guard above.
-        let ix = self.screens.focused().wix.saturating_sub(1);
-        self.focus_workspace(&Selector::Index(ix))?;
+        let ws = self.workspaces.remove_workspace(&Selector::Index(wix))?;
+        self.shift_workspace_indices(wix, false)?;
         self.update_x_workspace_details()?;
 
         Ok(Some(ws))
     }
 
+    // Replace the workspace shown on screen 'i' with the workspace at 'wix', which must not
+    // currently be visible on any screen
+    fn show_workspace_on_screen(&mut self, i: usize, wix: usize) -> Result<()> {
+        if i == self.screens.focused_index() {
+            return self.focus_workspace(&Selector::Index(wix));
+        }
+
+        let prev = self.screens.inner[i].wix;
+        for id in self.workspaces.get_workspace(prev)?.client_ids().iter() {
+            self.state.clients.unmap_if_needed(*id, &self.conn)?;
+        }
+
+        self.screens.inner[i].wix = wix;
+        for id in self.workspaces.get_workspace(wix)?.client_ids().iter() {
+            self.state.clients.map_if_needed(*id, &self.conn)?;
+        }
+
+        self.apply_layout(wix)
+    }
+
+    // Update the workspace indices held by clients and screens after a workspace has been
+    // inserted at (or removed from) 'wix' so that they continue to refer to the same workspace
+    fn shift_workspace_indices(&mut self, wix: usize, inserted: bool) -> Result<()> {
+        let shift = |ix: usize| match inserted {
+            true if ix >= wix => ix + 1,
+            false if ix > wix => ix - 1,
+            _ => ix,
+        };
+
+        for id in self.clients.all_known_ids() {
+            let current = match self.clients.get(id) {
+                Some(c) => c.workspace(),
+                None => continue,
+            };
+
+            let new = shift(current);
+            if new != current {
+                self.clients.set_client_workspace(id, new);
+                self.conn.set_client_workspace(id, new)?;
+            }
+        }
+
+        self.screens
+            .inner
+            .iter_mut()
+            .for_each(|s| s.wix = shift(s.wix));
+        self.previous_workspace = shift(self.previous_workspace).min(self.workspaces.len() - 1);
+
+        let active = self.screens.active_ws_index();
+        self.workspaces.focus(&Selector::Index(active));
+        self.conn.set_current_workspace(active)?;
+
+        Ok(())
+    }
+
     /// Get a reference to the first Workspace satisfying 'selector'. Xid selectors will return
     /// the workspace containing that Client if the client is known.
     pub fn workspace(&self, selector: &Selector<'_, Workspace>) -> Option<&Workspace> {
@@ -1170,6 +1806,18 @@ impl<X: XConn> WindowManager<X> {
         self.update_x_workspace_details()
     }
 
+    /// Rename the [Workspace] at `index`, leaving its clients, layout and focus unchanged.
+    ///
+    /// Hooks (such as the status bar workspace widget) are notified of the new name, returning
+    /// an error if `index` is out of range.
+    pub fn rename_workspace(&mut self, index: usize, name: impl Into<String>) -> Result<()> {
+        if index >= self.workspaces.len() {
+            return Err(PenroseError::UnknownWorkspace(index));
+        }
+
+        self.set_workspace_name(name, &Selector::Index(index))
+    }
+
     /// Take a reference to the first Client found matching 'selector'
     pub fn client(&self, selector: &Selector<'_, Client>) -> Option<&Client> {
         match selector {
@@ -1288,26 +1936,44 @@ impl<X: XConn> WindowManager<X> {
     }
 }
 
+// The workspace to show in place of 'wix' when it is removed: the closest workspace to it that
+// is not already visible, preferring the one before it
+fn replacement_workspace(wix: usize, n_workspaces: usize, visible: &[usize]) -> usize {
+    (1..n_workspaces)
+        .flat_map(|d| [wix.checked_sub(d), Some(wix + d)])
+        .flatten()
+        .find(|ix| *ix < n_workspaces && !visible.contains(ix))
+        .unwrap_or(wix)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{
         __test_helpers::{
             n_clients, test_key_bindings, test_mouse_bindings, test_windowmanager, RecordedCall,
-            RecordingXConn,
+            RecordingXConn, TestWM, TestXConn,
         },
         core::{
+            bindings::{KeyEventHandler, ModifierKey, MouseButton, MouseEventHandler, MouseState},
+            client::ClientRule,
             data_types::*,
             helpers::logging_error_handler,
+            hooks::Hook,
             layout::*,
             ring::Direction::*,
             screen::*,
-            xconnection::{MockXConn, Prop, XEvent},
+            xconnection::{MockXConn, PointerChange, Prop, XEvent, XState},
         },
         draw::Color,
     };
 
-    use std::{cell::Cell, collections::HashMap, convert::TryFrom};
+    use std::{
+        cell::{Cell, RefCell},
+        collections::HashMap,
+        convert::TryFrom,
+        rc::Rc,
+    };
 
     fn wm_with_mock_conn(events: Vec<XEvent>, unmanaged_ids: Vec<Xid>) -> WindowManager<MockXConn> {
         let conn = MockXConn::new(test_screens(), events, unmanaged_ids);
@@ -1662,6 +2328,8 @@ mod tests {
     layout_trigger_test!(cycle_layout; true; Forward);
     layout_trigger_test!(update_max_main; true; Change::More);
     layout_trigger_test!(update_main_ratio; true; Change::More);
+    layout_trigger_test!(increase_main_ratio; true; 0.1);
+    layout_trigger_test!(decrease_main_ratio; true; 0.1);
     layout_trigger_test!(exit; false;);
     layout_trigger_test!(set_root_window_name; false; "test");
     layout_trigger_test!(set_client_insert_point; false; InsertPoint::First);
@@ -1719,7 +2387,163 @@ mod tests {
         assert!(wm.conn.was_called("position_client"));
     }
 
-    /*
+    fn recording_wm() -> WindowManager<RecordingXConn> {
+        let conn = RecordingXConn::init();
+        let mut wm = WindowManager::new(Config::default(), conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+
+        wm
+    }
+
+    fn _delete_active_window(wm: &WindowManager<RecordingXConn>) -> RecordedCall {
+        let root = wm.conn.root();
+        (
+            "delete_prop".into(),
+            strings!(root, Atom::NetActiveWindow.as_ref()),
+        )
+    }
+
+    #[test]
+    fn active_window_requests_show_hidden_workspaces() {
+        let mut wm = recording_wm();
+        add_n_clients(&mut wm, 1, 0);
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+        add_n_clients(&mut wm, 2, 1);
+        wm.focus_workspace(&Selector::Index(0)).unwrap();
+
+        let action = EventAction::SetActiveClient(20);
+        wm.handle_event_action(action, None, None).unwrap();
+
+        assert_eq!(wm.screens.active_ws_index(), 2);
+        assert_eq!(wm.focused_client_id(), Some(20));
+        assert_eq!(wm.workspaces[2].focused_client(), Some(20));
+    }
+
+    #[test]
+    fn active_window_requests_for_unknown_clients_are_an_error() {
+        let mut wm = recording_wm();
+        let action = EventAction::SetActiveClient(42);
+
+        assert!(matches!(
+            wm.handle_event_action(action, None, None),
+            Err(PenroseError::UnknownClient(42))
+        ));
+    }
+
+    #[test]
+    fn removing_the_last_focused_client_clears_active_window() {
+        let mut wm = recording_wm();
+        add_n_clients(&mut wm, 1, 0);
+        wm.conn.clear();
+
+        wm.remove_client(10).unwrap();
+
+        let expected = _delete_active_window(&wm);
+        assert!(wm.conn.calls().contains(&expected));
+        assert_eq!(wm.focused_client_id(), None);
+    }
+
+    #[test]
+    fn focusing_an_empty_workspace_clears_active_window() {
+        let mut wm = recording_wm();
+        add_n_clients(&mut wm, 1, 0);
+        wm.conn.clear();
+
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+
+        let expected = _delete_active_window(&wm);
+        assert!(wm.conn.calls().contains(&expected));
+        assert_eq!(wm.focused_client_id(), None);
+    }
+
+    #[test]
+    fn focused_clients_are_never_urgent() {
+        let mut wm = recording_wm();
+        add_n_clients(&mut wm, 2, 0);
+
+        for id in [10, 20] {
+            let action = EventAction::SetClientUrgent(id, true);
+            wm.handle_event_action(action, None, None).unwrap();
+        }
+
+        assert_eq!(wm.urgent_windows(), vec![10]);
+    }
+
+    #[test]
+    fn focusing_urgent_clients_clears_urgency() {
+        let mut wm = recording_wm();
+        add_n_clients(&mut wm, 2, 0);
+        let action = EventAction::SetClientUrgent(10, true);
+        wm.handle_event_action(action, None, None).unwrap();
+        wm.conn.clear();
+
+        wm.focus_client(&Selector::WinId(10)).unwrap();
+
+        // RecordingXConn can not report the current _NET_WM_STATE so it must be left untouched
+        let overwrites_state =
+            |(m, args): &RecordedCall| m == "change_prop" && args[1] == Atom::NetWmState.as_ref();
+        assert!(wm.urgent_clients().is_empty());
+        assert!(!wm.conn.calls().iter().any(overwrites_state));
+    }
+
+    fn pointer_change(id: Xid) -> PointerChange {
+        PointerChange {
+            id,
+            abs: Point::new(5, 5),
+            relative: Point::new(0, 0),
+        }
+    }
+
+    test_cases! {
+        focus_follow_mouse;
+        args: (follow: bool, recent_layout: bool, expected: bool);
+
+        case: enabled => (true, false, true);
+        case: enabled_after_layout => (true, true, false);
+        case: disabled => (false, false, false);
+        case: disabled_after_layout => (false, true, false);
+
+        body: {
+            let mut wm = wm_with_mock_conn(vec![], vec![]);
+            add_n_clients(&mut wm, 2, 0);
+            wm.config.focus_follow_mouse = follow;
+            wm.state.last_layout = if recent_layout { Some(Instant::now()) } else { None };
+
+            let enter = XEvent::Enter(pointer_change(10));
+            let actions = process_next_event(enter, &wm.state, &wm.conn);
+            assert_eq!(actions.contains(&EventAction::ClientFocusGained(10)), expected);
+            assert_eq!(actions.contains(&EventAction::ClientFocusLost(20)), expected);
+
+            let leave = XEvent::Leave(pointer_change(20));
+            let actions = process_next_event(leave, &wm.state, &wm.conn);
+            assert_eq!(actions.contains(&EventAction::ClientFocusLost(20)), follow);
+        }
+    }
+
+    #[test]
+    fn toggling_gaps_restores_the_previous_gaps() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        wm.set_gaps(4, 8).unwrap();
+
+        wm.toggle_gaps().unwrap();
+        assert_eq!((wm.config.gap_px, wm.config.outer_gap_px), (0, 0));
+
+        wm.toggle_gaps().unwrap();
+        assert_eq!((wm.config.gap_px, wm.config.outer_gap_px), (4, 8));
+    }
+
+    #[test]
+    fn setting_gaps_clears_toggled_gaps() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        wm.toggle_gaps().unwrap();
+        wm.set_gaps(4, 8).unwrap();
+        wm.toggle_gaps().unwrap();
+        wm.toggle_gaps().unwrap();
+
+        assert_eq!((wm.config.gap_px, wm.config.outer_gap_px), (4, 8));
+    }
+
+    /*
      * Helpers for specifying expected events with RecordingXConn
      */
 
@@ -2007,6 +2831,503 @@ mod tests {
         assert_eq!(wm.active_workspace().client_ids(), vec![2, 1, 3, 0]);
     }
 
+    test_cases! {
+        replacement_workspace;
+        args: (wix: usize, visible: &[usize], expected: usize);
+
+        case: previous => (3, &[3], 2);
+        case: next_when_first => (0, &[0], 1);
+        case: skips_visible => (3, &[3, 2], 4);
+        case: further_away => (1, &[1, 0, 2], 3);
+
+        body: {
+            assert_eq!(replacement_workspace(wix, 5, visible), expected);
+        }
+    }
+
+    #[test]
+    fn remove_workspace_moves_clients_to_an_adjacent_workspace() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0);
+        wm.focus_workspace(&Selector::Index(3)).unwrap();
+        add_n_clients(&mut wm, 1, 2);
+        wm.focus_workspace(&Selector::Index(0)).unwrap();
+
+        let removed = wm.remove_workspace(&Selector::Index(3)).unwrap().unwrap();
+
+        assert_eq!(removed.name(), "4");
+        assert!(removed.is_empty());
+        assert_eq!(wm.workspaces[2].client_ids(), vec![30]);
+        assert_eq!(wm.clients.get(30).map(|c| c.workspace()), Some(2));
+        assert_eq!(wm.focused_workspaces(), vec![0, 1]);
+    }
+
+    #[test]
+    fn remove_focused_workspace_shows_the_previous_workspace() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        wm.focus_workspace(&Selector::Index(4)).unwrap();
+        add_n_clients(&mut wm, 2, 0);
+
+        wm.remove_workspace(&Selector::Index(4)).unwrap();
+
+        assert_eq!(wm.focused_workspaces(), vec![3, 1]);
+        assert_eq!(wm.active_workspace().client_ids(), vec![20, 10]);
+        assert!(wm.clients.get(10).is_some_and(|c| c.mapped));
+    }
+
+    #[test]
+    fn removing_a_workspace_shifts_later_indices() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        wm.focus_workspace(&Selector::Index(5)).unwrap();
+        add_n_clients(&mut wm, 1, 0);
+
+        wm.remove_workspace(&Selector::Index(2)).unwrap();
+
+        assert_eq!(wm.active_workspace().name(), "6");
+        assert_eq!(wm.focused_workspaces(), vec![4, 1]);
+        assert_eq!(wm.clients.get(10).map(|c| c.workspace()), Some(4));
+    }
+
+    #[test]
+    fn adding_a_workspace_shifts_later_indices() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        wm.focus_workspace(&Selector::Index(5)).unwrap();
+        add_n_clients(&mut wm, 1, 0);
+
+        wm.add_workspace(2, Workspace::new("new", focus_test_layouts(false)))
+            .unwrap();
+
+        assert_eq!(wm.workspaces[2].name(), "new");
+        assert_eq!(wm.active_workspace().name(), "6");
+        assert_eq!(wm.focused_workspaces(), vec![6, 1]);
+        assert_eq!(wm.clients.get(10).map(|c| c.workspace()), Some(6));
+    }
+
+    #[test]
+    fn add_workspace_out_of_range_is_an_error() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        let n = wm.workspaces.len();
+        let ws = Workspace::new("new", focus_test_layouts(false));
+
+        assert!(wm.add_workspace(n + 1, ws).is_err());
+    }
+
+    fn wm_with_client_rules(rules: Vec<ClientRule>) -> WindowManager<MockXConn> {
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            client_rules: rules,
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+
+        wm
+    }
+
+    // MockXConn clients have no WM_CLASS set so rules are matched against their WM_NAME
+    #[test]
+    fn client_rules_place_matching_clients() {
+        let mut wm = wm_with_client_rules(vec![
+            ClientRule::new("other", None, true),
+            ClientRule::new("mock name", Some(3), true),
+            ClientRule::new("mock name", Some(4), false),
+        ]);
+        add_n_clients(&mut wm, 1, 0);
+
+        assert!(wm.workspaces[0].is_empty());
+        assert_eq!(wm.workspaces[3].client_ids(), vec![10]);
+        assert!(wm.clients.get(10).is_some_and(|c| c.floating));
+        assert!(wm.clients.get(10).is_some_and(|c| !c.mapped));
+    }
+
+    #[test]
+    fn client_rules_with_an_unknown_workspace_use_the_active_workspace() {
+        let mut wm = wm_with_client_rules(vec![ClientRule::new("mock name", Some(42), false)]);
+        add_n_clients(&mut wm, 1, 0);
+
+        assert_eq!(wm.workspaces[0].client_ids(), vec![10]);
+        assert_eq!(wm.focused_client_id(), Some(10));
+    }
+
+    #[test]
+    fn clients_placed_on_a_visible_workspace_are_mapped() {
+        let mut wm = wm_with_client_rules(vec![ClientRule::new("mock name", Some(1), false)]);
+        add_n_clients(&mut wm, 1, 0);
+
+        assert_eq!(wm.workspaces[1].client_ids(), vec![10]);
+        assert!(wm.clients.get(10).is_some_and(|c| c.mapped));
+    }
+
+    #[test]
+    fn toggle_floating_centers_clients_the_first_time() {
+        let mut wm = test_windowmanager(1, vec![]);
+        add_n_clients(&mut wm, 1, 0);
+
+        wm.toggle_floating(10).unwrap();
+
+        assert!(wm.clients.get(10).is_some_and(|c| c.floating));
+        assert_eq!(
+            wm.conn.client_geometry(10).unwrap(),
+            Region::new(200, 163, 400, 291)
+        );
+    }
+
+    #[test]
+    fn toggle_floating_restores_the_last_floating_position() {
+        let mut wm = test_windowmanager(1, vec![]);
+        add_n_clients(&mut wm, 1, 0);
+        let tiled = wm.conn.client_geometry(10).unwrap();
+        let moved = Region::new(10, 20, 300, 200);
+
+        wm.toggle_floating(10).unwrap();
+        wm.handle_move_if_floating(10, moved).unwrap();
+        wm.toggle_floating(10).unwrap();
+
+        assert!(wm.clients.get(10).is_some_and(|c| !c.floating));
+        assert_eq!(wm.conn.client_geometry(10).unwrap(), tiled);
+
+        wm.toggle_floating(10).unwrap();
+        assert_eq!(wm.conn.client_geometry(10).unwrap(), moved);
+    }
+
+    fn mouse_event(x: u32, y: u32, button: MouseButton, kind: MouseEventKind) -> EventAction {
+        let state = MouseState::new(button, vec![ModifierKey::Meta]);
+        EventAction::RunMouseBinding(MouseEvent::new(0, x as i16, y as i16, 0, 0, state, kind))
+    }
+
+    fn mouse_drag_bindings() -> MouseBindings<TestXConn> {
+        let drag_move: MouseEventHandler<TestXConn> = Box::new(|wm, e| wm.drag_move_client(e));
+        let drag_resize: MouseEventHandler<TestXConn> = Box::new(|wm, e| wm.drag_resize_client(e));
+        let mut bindings: MouseBindings<TestXConn> = HashMap::new();
+        bindings.insert(
+            (
+                MouseEventKind::Motion,
+                MouseState::new(MouseButton::Left, vec![ModifierKey::Meta]),
+            ),
+            drag_move,
+        );
+        bindings.insert(
+            (
+                MouseEventKind::Motion,
+                MouseState::new(MouseButton::Right, vec![ModifierKey::Meta]),
+            ),
+            drag_resize,
+        );
+
+        bindings
+    }
+
+    test_cases! {
+        mouse_drag_floating_client;
+        args: (button: MouseButton, expected: Region);
+
+        case: left_moves => (MouseButton::Left, Region::new(250, 193, 400, 291));
+        case: right_resizes => (MouseButton::Right, Region::new(200, 163, 450, 321));
+
+        body: {
+            let mut wm = test_windowmanager(1, vec![]);
+            let mut bindings = mouse_drag_bindings();
+            add_n_clients(&mut wm, 1, 0);
+            wm.toggle_floating(10).unwrap();
+
+            for (x, y, kind) in &[
+                (100, 100, MouseEventKind::Press),
+                (120, 110, MouseEventKind::Motion),
+                (150, 130, MouseEventKind::Motion),
+            ] {
+                let e = mouse_event(*x, *y, button, *kind);
+                wm.handle_event_action(e, None, Some(&mut bindings)).unwrap();
+            }
+
+            assert_eq!(wm.conn.client_geometry(10).unwrap(), expected);
+
+            let e = mouse_event(150, 130, button, MouseEventKind::Release);
+            wm.handle_event_action(e, None, Some(&mut bindings)).unwrap();
+            assert_eq!(wm.drag, None);
+        }
+    }
+
+    #[test]
+    fn mouse_drag_leaves_tiled_clients_in_place() {
+        let mut wm = test_windowmanager(1, vec![]);
+        let mut bindings = mouse_drag_bindings();
+        add_n_clients(&mut wm, 1, 0);
+        let tiled = wm.conn.client_geometry(10).unwrap();
+
+        for (x, y, kind) in &[
+            (100, 100, MouseEventKind::Press),
+            (150, 130, MouseEventKind::Motion),
+        ] {
+            let e = mouse_event(*x, *y, MouseButton::Left, *kind);
+            wm.handle_event_action(e, None, Some(&mut bindings))
+                .unwrap();
+        }
+
+        assert_eq!(wm.drag, None);
+        assert_eq!(wm.conn.client_geometry(10).unwrap(), tiled);
+    }
+
+    fn key(code: u8) -> KeyCode {
+        KeyCode { mask: 0, code }
+    }
+
+    // A WindowManager with an active key chain that binds key 10 to counting how many times it
+    // has run, along with top level key bindings that bind key 20 in the same way.
+    fn wm_with_key_chain() -> (
+        WindowManager<RecordingXConn>,
+        KeyBindings<RecordingXConn>,
+        Rc<Cell<usize>>,
+    ) {
+        let runs = Rc::new(Cell::new(0));
+        let counter = |runs: &Rc<Cell<usize>>| -> KeyEventHandler<RecordingXConn> {
+            let runs = Rc::clone(runs);
+            Box::new(move |_| {
+                runs.set(runs.get() + 1);
+                Ok(())
+            })
+        };
+
+        let mut chain_bindings: KeyBindings<RecordingXConn> = HashMap::new();
+        chain_bindings.insert(key(10), counter(&runs));
+        let mut key_bindings: KeyBindings<RecordingXConn> = HashMap::new();
+        key_bindings.insert(key(20), counter(&runs));
+
+        let codes = vec![("Escape".to_string(), 9), ("Shift_L".to_string(), 50)]
+            .into_iter()
+            .collect();
+        let mut wm = WindowManager::new(
+            Config::default(),
+            RecordingXConn::init(),
+            vec![],
+            logging_error_handler(),
+        );
+        wm.start_key_chain(KeyChain::new(chain_bindings, &codes))
+            .unwrap();
+
+        (wm, key_bindings, runs)
+    }
+
+    #[test]
+    fn key_chains_grab_the_keyboard_until_the_next_key_press() {
+        let (mut wm, mut key_bindings, runs) = wm_with_key_chain();
+        assert!(wm.conn.was_called("grab_keyboard"));
+
+        let action = EventAction::RunKeyBinding(key(10));
+        wm.handle_event_action(action, Some(&mut key_bindings), None)
+            .unwrap();
+
+        assert_eq!(runs.get(), 1);
+        assert!(wm.key_chain.is_none());
+        assert!(wm.conn.was_called("ungrab_keyboard"));
+
+        // Key 10 is not a top level binding
+        let action = EventAction::RunKeyBinding(key(10));
+        wm.handle_event_action(action, Some(&mut key_bindings), None)
+            .unwrap();
+        assert_eq!(runs.get(), 1);
+    }
+
+    test_cases! {
+        key_chain_cancelled_by;
+        args: (code: u8);
+
+        case: escape => (9);
+        case: unbound_key => (20);
+
+        body: {
+            let (mut wm, mut key_bindings, runs) = wm_with_key_chain();
+            wm.conn.clear();
+
+            let action = EventAction::RunKeyBinding(key(code));
+            wm.handle_event_action(action, Some(&mut key_bindings), None)
+                .unwrap();
+
+            assert_eq!(runs.get(), 0);
+            assert!(wm.key_chain.is_none());
+            assert!(wm.conn.was_called("ungrab_keyboard"));
+        }
+    }
+
+    #[test]
+    fn key_chains_ignore_modifier_keys() {
+        let (mut wm, mut key_bindings, runs) = wm_with_key_chain();
+        wm.conn.clear();
+
+        let action = EventAction::RunKeyBinding(key(50));
+        wm.handle_event_action(action, Some(&mut key_bindings), None)
+            .unwrap();
+
+        assert!(wm.key_chain.is_some());
+        assert!(wm.conn.was_not_called("ungrab_keyboard"));
+
+        let action = EventAction::RunKeyBinding(key(10));
+        wm.handle_event_action(action, Some(&mut key_bindings), None)
+            .unwrap();
+        assert_eq!(runs.get(), 1);
+    }
+
+    #[test]
+    fn key_chains_are_cancelled_after_the_timeout() {
+        let (mut wm, _, _) = wm_with_key_chain();
+        wm.cancel_key_chain_if_expired().unwrap();
+        assert!(wm.key_chain.is_some());
+
+        wm.conn.clear();
+        if let Some((_, deadline)) = wm.key_chain.as_mut() {
+            *deadline = Some(Instant::now() - Duration::from_millis(1));
+        }
+        wm.cancel_key_chain_if_expired().unwrap();
+
+        assert!(wm.key_chain.is_none());
+        assert!(wm.conn.was_called("ungrab_keyboard"));
+    }
+
+    #[test]
+    fn toggle_floating_unknown_client_is_an_error() {
+        let mut wm = test_windowmanager(1, vec![]);
+
+        assert!(matches!(
+            wm.toggle_floating(42),
+            Err(PenroseError::UnknownClient(42))
+        ));
+    }
+
+    #[test]
+    fn move_workspace_to_screen_swaps_workspaces() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 1, 0);
+
+        wm.move_workspace_to_screen(1).unwrap();
+
+        assert_eq!(wm.focused_workspaces(), vec![1, 0]);
+        assert_eq!(wm.active_screen_index(), 1);
+        assert_eq!(wm.focused_client_id(), Some(10));
+    }
+
+    #[test]
+    fn move_workspace_to_the_focused_screen_is_a_no_op() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        wm.move_workspace_to_screen(0).unwrap();
+
+        assert_eq!(wm.focused_workspaces(), vec![0, 1]);
+        assert_eq!(wm.active_screen_index(), 0);
+    }
+
+    #[test]
+    fn move_client_to_screen_keeps_focus_on_the_current_screen() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0);
+
+        wm.move_client_to_screen(1).unwrap();
+
+        assert_eq!(wm.workspaces[0].client_ids(), vec![10]);
+        assert_eq!(wm.workspaces[1].client_ids(), vec![20]);
+        assert_eq!(wm.active_screen_index(), 0);
+    }
+
+    #[test]
+    fn moving_to_an_unknown_screen_is_an_error() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+
+        assert!(wm.move_workspace_to_screen(2).is_err());
+        assert!(wm.move_client_to_screen(2).is_err());
+    }
+
+    #[test]
+    fn moving_between_screens_with_one_screen_is_a_no_op() {
+        let mut wm = test_windowmanager(1, vec![]);
+        add_n_clients(&mut wm, 1, 0);
+
+        wm.move_workspace_to_screen(1).unwrap();
+        wm.move_client_to_screen(1).unwrap();
+
+        assert_eq!(wm.focused_workspaces(), vec![0]);
+        assert_eq!(wm.workspaces[0].client_ids(), vec![10]);
+    }
+
+    #[test]
+    fn rename_workspace_only_changes_the_name() {
+        let mut wm = test_windowmanager(1, vec![]);
+        add_n_clients(&mut wm, 2, 0);
+        wm.cycle_layout(Forward).unwrap();
+        let (ids, focused) = (wm.workspaces[0].client_ids(), wm.focused_client_id());
+
+        wm.rename_workspace(0, "mail").unwrap();
+
+        assert_eq!(wm.workspaces[0].name(), "mail");
+        assert_eq!(wm.workspaces[0].client_ids(), ids);
+        assert_eq!(wm.focused_client_id(), focused);
+        assert_eq!(wm.current_layout_symbol(), "second");
+    }
+
+    #[test]
+    fn rename_workspace_out_of_range_is_an_error() {
+        let mut wm = test_windowmanager(1, vec![]);
+        let n = wm.workspaces.len();
+
+        assert!(matches!(
+            wm.rename_workspace(n, "nope"),
+            Err(PenroseError::UnknownWorkspace(i)) if i == n
+        ));
+    }
+
+    #[test]
+    fn main_ratio_changes_are_kept_per_workspace() {
+        let mut wm = test_windowmanager(1, vec![]);
+        let ratio = |wm: &TestWM, wix: usize| wm.workspaces[wix].main_ratio();
+
+        wm.increase_main_ratio(0.2).unwrap();
+        assert!((ratio(&wm, 0) - 0.8).abs() < f32::EPSILON);
+
+        wm.focus_workspace(&Selector::Index(1)).unwrap();
+        wm.decrease_main_ratio(1.0).unwrap();
+        assert!((ratio(&wm, 1) - MIN_MAIN_RATIO).abs() < f32::EPSILON);
+
+        wm.focus_workspace(&Selector::Index(0)).unwrap();
+        assert!((ratio(&wm, 0) - 0.8).abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn monocle_maps_only_the_focused_client() {
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let conf = Config {
+            layouts: vec![
+                Layout::monocle("[M]"),
+                Layout::new("[side]", LayoutConf::default(), side_stack, 1, 0.6),
+            ],
+            ..Default::default()
+        };
+        let mut wm = WindowManager::new(conf, conn, vec![], logging_error_handler());
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 3, 0);
+
+        let mapped = |wm: &WindowManager<MockXConn>| -> Vec<Xid> {
+            let mut ids: Vec<Xid> = wm.clients.all_known_ids();
+            ids.sort_unstable();
+            ids.retain(|id| wm.clients.get(*id).is_some_and(|c| c.mapped));
+            ids
+        };
+
+        assert_eq!(wm.current_layout_symbol(), "[M]");
+        assert_eq!(mapped(&wm), vec![30]);
+
+        // Focus cycling still visits every client, mapping each one in turn
+        let mut seen = vec![];
+        for _ in 0..3 {
+            wm.cycle_client(Forward).unwrap();
+            let focused = wm.focused_client_id().unwrap();
+            assert_eq!(mapped(&wm), vec![focused]);
+            seen.push(focused);
+        }
+        seen.sort_unstable();
+        assert_eq!(seen, vec![10, 20, 30]);
+
+        // Moving to a layout that shows all clients re-maps the hidden ones
+        wm.cycle_layout(Forward).unwrap();
+        assert_eq!(mapped(&wm), vec![10, 20, 30]);
+    }
+
     #[test]
     fn cycle_layout() {
         let mut wm = test_windowmanager(1, vec![]);
@@ -2137,4 +3458,130 @@ mod tests {
             Some(5)
         );
     }
+
+    #[test]
+    fn disconnecting_a_screen_unmaps_the_clients_left_without_one() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 2, 0);
+        wm.cycle_screen(Forward).unwrap();
+        add_n_clients(&mut wm, 2, 2);
+
+        wm.conn = MockXConn::new(vec![test_screens()[0]], vec![], vec![]);
+        wm.detect_screens().unwrap();
+
+        let is_mapped = |id| wm.clients.get(id).map(|c| c.mapped);
+        assert_eq!(wm.screens.visible_workspaces(), vec![1]);
+        assert_eq!(wm.screens.active_ws_index(), 1);
+        assert_eq!(is_mapped(10), Some(false));
+        assert_eq!(is_mapped(20), Some(false));
+        assert_eq!(is_mapped(30), Some(true));
+        assert_eq!(is_mapped(40), Some(true));
+    }
+
+    // The ids passed to client_closed along with whether or not the client was still known
+    type Closed = Rc<RefCell<Vec<(Xid, bool)>>>;
+
+    struct ClosedClients(Closed);
+
+    impl<X: XConn> Hook<X> for ClosedClients {
+        fn client_closed(&mut self, wm: &mut WindowManager<X>, id: Xid) -> Result<()> {
+            self.0.borrow_mut().push((id, wm.clients.is_known(id)));
+            Ok(())
+        }
+    }
+
+    fn wm_recording_closed_clients() -> (WindowManager<MockXConn>, Closed) {
+        let closed = Rc::new(RefCell::new(vec![]));
+        let conn = MockXConn::new(test_screens(), vec![], vec![]);
+        let conf = Config {
+            layouts: focus_test_layouts(false),
+            ..Default::default()
+        };
+        let hooks: Hooks<MockXConn> = vec![Box::new(ClosedClients(Rc::clone(&closed)))];
+        let mut wm = WindowManager::new(conf, conn, hooks, logging_error_handler());
+        wm.init().unwrap();
+        add_n_clients(&mut wm, 2, 0);
+
+        (wm, closed)
+    }
+
+    #[test]
+    fn unmapping_by_the_client_removes_it() {
+        let (mut wm, closed) = wm_recording_closed_clients();
+
+        wm.handle_event_action(EventAction::Unmap(10), None, None)
+            .unwrap();
+
+        assert_eq!(*closed.borrow(), vec![(10, true)]);
+        assert!(!wm.clients.is_known(10));
+        assert_eq!(wm.workspaces[0].client_ids(), vec![20]);
+    }
+
+    #[test]
+    fn unmaps_requested_by_penrose_do_not_remove_the_client() {
+        let (mut wm, closed) = wm_recording_closed_clients();
+
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+        wm.handle_event_action(EventAction::Unmap(10), None, None)
+            .unwrap();
+
+        assert!(closed.borrow().is_empty());
+        assert!(wm.clients.is_known(10));
+
+        // A further unmap that we did not request is from the client itself
+        wm.handle_event_action(EventAction::Unmap(10), None, None)
+            .unwrap();
+
+        assert_eq!(*closed.borrow(), vec![(10, true)]);
+        assert!(!wm.clients.is_known(10));
+    }
+
+    #[test]
+    fn hiding_a_workspace_does_not_remove_its_clients() {
+        let (mut wm, closed) = wm_recording_closed_clients();
+        let root = wm.conn.root();
+
+        wm.focus_workspace(&Selector::Index(2)).unwrap();
+        for id in [10, 20] {
+            // X reports the unmap to both the client and its parent
+            for event in [id, root] {
+                let unmap = XEvent::UnmapNotify(id, event);
+                for action in process_next_event(unmap, &wm.state, &wm.conn) {
+                    wm.handle_event_action(action, None, None).unwrap();
+                }
+            }
+        }
+
+        assert!(closed.borrow().is_empty());
+        assert!(wm.clients.is_known(10));
+        assert!(wm.clients.is_known(20));
+    }
+
+    #[test]
+    fn closing_the_focused_client_focuses_the_next_client_in_the_stack() {
+        let mut wm = wm_with_mock_conn(vec![], vec![]);
+        add_n_clients(&mut wm, 3, 0);
+        assert_eq!(wm.active_workspace().client_ids(), vec![30, 20, 10]);
+
+        wm.focus_client(&Selector::WinId(20)).unwrap();
+        wm.handle_event_action(EventAction::DestroyClient(20), None, None)
+            .unwrap();
+
+        assert_eq!(wm.active_workspace().focused_client(), Some(10));
+    }
+
+    #[test]
+    fn client_closed_runs_once_when_unmapped_then_destroyed() {
+        let (mut wm, closed) = wm_recording_closed_clients();
+
+        wm.handle_event_action(EventAction::Unmap(20), None, None)
+            .unwrap();
+        wm.handle_event_action(EventAction::DestroyClient(20), None, None)
+            .unwrap();
+        wm.handle_event_action(EventAction::DestroyClient(10), None, None)
+            .unwrap();
+
+        assert_eq!(*closed.borrow(), vec![(20, true), (10, true)]);
+        assert!(wm.clients.all_known_ids().is_empty());
+    }
 }
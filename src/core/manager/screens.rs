@@ -84,7 +84,21 @@ impl Screens {
     where
         S: XState,
     {
+        let mut detected: Vec<Screen> = state.current_screens()?;
+
+        // sort the screens to be in the correct order
+        detected.sort_by_key(|s| s.true_region.x);
+
+        // Screens keep their current workspace where possible with any new screens being given
+        // the first unused workspaces. If the focused screen has been removed then its workspace
+        // falls back to the first screen so that the active workspace remains visible.
+        let mut focused = self.inner.focused_index();
         let mut workspace_ordering = self.visible_workspaces();
+        if focused >= detected.len() && focused < workspace_ordering.len() {
+            let wix = workspace_ordering.remove(focused);
+            workspace_ordering.insert(0, wix);
+            focused = 0;
+        }
         workspace_ordering.append(
             &mut (0..n_workspaces)
                 .filter(|w| !workspace_ordering.contains(w))
@@ -93,11 +107,6 @@ impl Screens {
 
         debug!(?workspace_ordering, "current workspace ordering");
 
-        let mut detected: Vec<Screen> = state.current_screens()?;
-
-        // sort the screens to be in the correct order
-        detected.sort_by_key(|s| s.true_region.x);
-
         let detected = detected
             .into_iter()
             .zip(workspace_ordering)
@@ -115,6 +124,7 @@ impl Screens {
 
         Ok(if self.inner.as_vec() != detected {
             self.inner = Ring::new(detected);
+            self.inner.focus(&Selector::Index(focused));
             vec![
                 EventAction::LayoutVisible,
                 EventAction::RunHook(HookName::ScreenUpdated),
@@ -190,6 +200,58 @@ mod tests {
         assert!(events.is_empty());
     }
 
+    fn single_screen() -> Vec<Screen> {
+        vec![Screen::new(Region::new(0, 0, 1366, 768), 0)]
+    }
+
+    fn three_screens() -> Vec<Screen> {
+        let mut screens = raw_screens();
+        screens.push(Screen::new(Region::new(2732, 0, 1366, 768), 2));
+        screens
+    }
+
+    #[test]
+    fn removing_the_focused_screen_moves_its_workspace_to_the_first_screen() {
+        let mut s = Screens::new(10, true);
+        s.update_known_screens(&MockXConn::new(raw_screens(), vec![], vec![]), 10)
+            .unwrap();
+        s.focus_screen(&Selector::Index(1));
+
+        let conn = MockXConn::new(single_screen(), vec![], vec![]);
+        s.update_known_screens(&conn, 10).unwrap();
+
+        assert_eq!(s.visible_workspaces(), vec![1]);
+        assert_eq!(s.active_ws_index(), 1);
+    }
+
+    #[test]
+    fn removing_an_unfocused_screen_leaves_focus_unchanged() {
+        let mut s = Screens::new(10, true);
+        s.update_known_screens(&MockXConn::new(three_screens(), vec![], vec![]), 10)
+            .unwrap();
+        s.focus_screen(&Selector::Index(1));
+
+        let conn = MockXConn::new(raw_screens(), vec![], vec![]);
+        s.update_known_screens(&conn, 10).unwrap();
+
+        assert_eq!(s.visible_workspaces(), vec![0, 1]);
+        assert_eq!(s.inner.focused_index(), 1);
+    }
+
+    #[test]
+    fn new_screens_are_given_unused_workspaces() {
+        let mut s = Screens::new(10, true);
+        let conn = MockXConn::new(single_screen(), vec![], vec![]);
+        s.update_known_screens(&conn, 10).unwrap();
+        s.inner.focused_mut_unchecked().wix = 3;
+
+        let conn = MockXConn::new(three_screens(), vec![], vec![]);
+        s.update_known_screens(&conn, 10).unwrap();
+
+        assert_eq!(s.visible_workspaces(), vec![3, 0, 1]);
+        assert_eq!(s.active_ws_index(), 3);
+    }
+
     #[test]
     fn changing_focus_generates_event_actions() {
         let mut s = Screens::new(10, true);
@@ -3,7 +3,13 @@ use crate::core::{
     manager::{clients::Clients, screens::Screens, workspaces::Workspaces, WindowManager},
     xconnection::XConn,
 };
-use std::ops::{Deref, DerefMut};
+use std::{
+    ops::{Deref, DerefMut},
+    time::{Duration, Instant},
+};
+
+// How long after clients have been positioned to ignore pointer enter events for
+const ENTER_GRACE_PERIOD: Duration = Duration::from_millis(50);
 
 #[derive(Debug)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -12,6 +18,18 @@ pub struct WmState {
     pub(super) clients: Clients,
     pub(super) screens: Screens,
     pub(super) workspaces: Workspaces,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub(super) last_layout: Option<Instant>,
+}
+
+impl WmState {
+    // Applying a layout moves and unmaps windows out from under the pointer (as does warping
+    // the pointer to the newly focused client) and the X server reports the window that ends up
+    // beneath the pointer as having been entered. Those events are not the user moving the
+    // mouse so they must not steal focus.
+    pub(super) fn ignoring_enter_events(&self) -> bool {
+        matches!(self.last_layout, Some(t) if t.elapsed() < ENTER_GRACE_PERIOD)
+    }
 }
 
 impl<X> Deref for WindowManager<X>
@@ -1,7 +1,7 @@
 use crate::{
     core::{
         data_types::Region,
-        xconnection::{XClientConfig, XState, Xid},
+        xconnection::{WmNormalHints, XClientConfig, XState, Xid},
     },
     Result,
 };
@@ -27,11 +27,39 @@ pub(super) fn pad_region(region: &Region, gapless: bool, gap_px: u32, border_px:
     Region::new(x + gpx, y + gpx, w - padding, h - padding)
 }
 
+// The area available for tiling on a screen once the outer gap has been removed from each edge
+pub(super) fn tiling_region(region: &Region, outer_gap_px: u32) -> Region {
+    let (x, y, w, h) = region.values();
+    let padding = 2 * outer_gap_px;
+
+    if w <= padding || h <= padding {
+        warn!("not applying outer gap to avoid integer underflow");
+        return *region;
+    }
+
+    Region::new(x + outer_gap_px, y + outer_gap_px, w - padding, h - padding)
+}
+
+// The region of a client after being dragged by 'delta' with the mouse from 'region'. Resizing
+// keeps the top left corner in place and never shrinks the client below 1x1.
+pub(super) fn dragged_region(region: &Region, delta: (i32, i32), resize: bool) -> Region {
+    let shift = |v: u32, d: i32| (v as i64 + d as i64).max(0) as u32;
+    let (x, y, w, h) = region.values();
+    let (dx, dy) = delta;
+
+    if resize {
+        Region::new(x, y, shift(w, dx).max(1), shift(h, dy).max(1))
+    } else {
+        Region::new(shift(x, dx), shift(y, dy), w, h)
+    }
+}
+
 pub(super) fn position_floating_client<X>(
     conn: &X,
     id: Xid,
     screen_region: Region,
     border_px: u32,
+    hints: Option<&WmNormalHints>,
 ) -> Result<()>
 where
     X: XClientConfig + XState,
@@ -55,6 +83,7 @@ where
         warn!("floating client too small {}", id);
         Region::new(x, y, w, h)
     };
+    let reg = hints.map_or(reg, |h| h.apply_to(&reg));
 
     Ok(conn.position_client(id, reg, border_px, false)?)
 }
@@ -105,7 +134,7 @@ where
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::__test_helpers::*;
+    use crate::{__test_helpers::*, core::xconnection::WmNormalHintsFlags};
 
     #[test]
     fn pad_region_centered() {
@@ -125,13 +154,43 @@ mod tests {
         assert_eq!(pad_region(&r, true, g, b), r);
     }
 
+    #[test]
+    fn tiling_region_removes_the_outer_gap() {
+        let r = Region::new(10, 20, 200, 100);
+        assert_eq!(tiling_region(&r, 5), Region::new(15, 25, 190, 90));
+        assert_eq!(tiling_region(&r, 0), r);
+    }
+
+    #[test]
+    fn tiling_region_tiny() {
+        let r = Region::new(0, 0, 10, 10);
+        assert_eq!(tiling_region(&r, 5), r);
+    }
+
+    test_cases! {
+        dragged_region;
+        args: (delta: (i32, i32), resize: bool, expected: Region);
+
+        case: move_by_positive_delta => ((10, 20), false, Region::new(110, 120, 300, 200));
+        case: move_by_negative_delta => ((-10, -20), false, Region::new(90, 80, 300, 200));
+        case: move_clamps_at_origin => ((-150, -150), false, Region::new(0, 0, 300, 200));
+        case: resize_larger => ((10, 20), true, Region::new(100, 100, 310, 220));
+        case: resize_smaller => ((-10, -20), true, Region::new(100, 100, 290, 180));
+        case: resize_to_minimum => ((-500, -500), true, Region::new(100, 100, 1, 1));
+
+        body: {
+            let r = Region::new(100, 100, 300, 200);
+            assert_eq!(dragged_region(&r, delta, resize), expected);
+        }
+    }
+
     #[test]
     fn position_floating() {
         let conn = TestXConn::new(1, vec![], vec![]);
         conn.position_client(0, Region::new(0, 0, 400, 300), 2, false)
             .unwrap();
 
-        position_floating_client(&conn, 0, Region::default(), 2).unwrap();
+        position_floating_client(&conn, 0, Region::default(), 2, None).unwrap();
 
         assert_eq!(
             conn.client_geometry(0).unwrap(),
@@ -145,8 +204,30 @@ mod tests {
         conn.position_client(0, Region::new(0, 0, 4, 3), 2, false)
             .unwrap();
 
-        position_floating_client(&conn, 0, Region::default(), 2).unwrap();
+        position_floating_client(&conn, 0, Region::default(), 2, None).unwrap();
 
         assert_eq!(conn.client_geometry(0).unwrap(), Region::new(0, 0, 4, 3));
     }
+
+    #[test]
+    fn position_floating_respects_size_hints() {
+        let conn = TestXConn::new(1, vec![], vec![]);
+        conn.position_client(0, Region::new(0, 0, 400, 300), 2, false)
+            .unwrap();
+        let fixed = Region::new(0, 0, 200, 100);
+        let hints = WmNormalHints::new(
+            WmNormalHintsFlags::P_MIN_SIZE | WmNormalHintsFlags::P_MAX_SIZE,
+            None,
+            Some(fixed),
+            Some(fixed),
+            None,
+        );
+
+        position_floating_client(&conn, 0, Region::default(), 2, Some(&hints)).unwrap();
+
+        assert_eq!(
+            conn.client_geometry(0).unwrap(),
+            Region::new(2, 2, 200, 100)
+        );
+    }
 }
@@ -225,7 +225,10 @@ impl Workspaces {
     }
 
     pub fn update_main_ratio(&mut self, wix: usize, change: Change) {
-        let step = self.main_ratio_step;
+        self.update_main_ratio_by(wix, change, self.main_ratio_step);
+    }
+
+    pub fn update_main_ratio_by(&mut self, wix: usize, change: Change, step: f32) {
         self.inner.apply_to(&Selector::Index(wix), |ws| {
             ws.update_main_ratio(change, step);
         });
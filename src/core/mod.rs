@@ -41,6 +41,8 @@ pub mod config;
 pub mod data_types;
 pub mod helpers;
 pub mod hooks;
+#[cfg(feature = "ipc")]
+pub mod ipc;
 pub mod layout;
 pub mod manager;
 pub mod ring;
@@ -124,6 +124,9 @@ impl<T> Ring<T> {
     }
 
     pub fn would_wrap(&self, dir: Direction) -> bool {
+        if self.elements.is_empty() {
+            return false;
+        }
         let wrap_back = self.focused == 0 && dir == Direction::Backward;
         let wrap_forward = self.focused == self.elements.len() - 1 && dir == Direction::Forward;
 
@@ -150,49 +153,95 @@ impl<T> Ring<T> {
         &mut self.elements[self.focused]
     }
 
-    pub fn rotate(&mut self, direction: Direction) {
-        if self.elements.is_empty() {
-            return;
+    /// Move every element one place forward (wrapping the last element to the front) while
+    /// leaving the focus point where it is.
+    pub fn rotate_forward(&mut self) {
+        if !self.elements.is_empty() {
+            self.elements.rotate_right(1);
         }
+    }
+
+    /// Move every element one place backward (wrapping the first element to the back) while
+    /// leaving the focus point where it is.
+    pub fn rotate_backward(&mut self) {
+        if !self.elements.is_empty() {
+            self.elements.rotate_left(1);
+        }
+    }
+
+    pub fn rotate(&mut self, direction: Direction) {
         match direction {
-            Direction::Forward => self.elements.rotate_right(1),
-            Direction::Backward => self.elements.rotate_left(1),
+            Direction::Forward => self.rotate_forward(),
+            Direction::Backward => self.rotate_backward(),
         }
     }
 
+    // The index one step from the focus point in the given direction, wrapping at either end.
+    // Empty rings always give 0.
     fn next_index(&self, direction: Direction) -> usize {
-        let max = self.elements.len() - 1;
+        let len = self.elements.len();
+        if len == 0 {
+            return 0;
+        }
         match direction {
-            Direction::Forward => {
-                if self.focused == max {
-                    0
-                } else {
-                    self.focused + 1
-                }
-            }
-            Direction::Backward => {
-                if self.focused == 0 {
-                    max
-                } else {
-                    self.focused - 1
-                }
-            }
+            Direction::Forward => (self.focused + 1) % len,
+            Direction::Backward => (self.focused + len - 1) % len,
         }
     }
 
+    /// Move focus to the next element, wrapping from the last element to the first.
+    pub fn focus_next(&mut self) -> Option<&T> {
+        self.focused = self.next_index(Direction::Forward);
+        self.focused()
+    }
+
+    /// Move focus to the previous element, wrapping from the first element to the last.
+    pub fn focus_prev(&mut self) -> Option<&T> {
+        self.focused = self.next_index(Direction::Backward);
+        self.focused()
+    }
+
     pub fn cycle_focus(&mut self, direction: Direction) -> Option<&T> {
-        self.focused = self.next_index(direction);
+        match direction {
+            Direction::Forward => self.focus_next(),
+            Direction::Backward => self.focus_prev(),
+        }
+    }
+
+    /// Swap the focused element with the one after it (wrapping at the end) with focus
+    /// following the originally focused element.
+    pub fn swap_focused_with_next(&mut self) -> Option<&T> {
+        self.swap_focused(Direction::Forward)
+    }
+
+    /// Swap the focused element with the one before it (wrapping at the start) with focus
+    /// following the originally focused element.
+    pub fn swap_focused_with_prev(&mut self) -> Option<&T> {
+        self.swap_focused(Direction::Backward)
+    }
+
+    fn swap_focused(&mut self, direction: Direction) -> Option<&T> {
+        let other = self.next_index(direction);
+        if other != self.focused {
+            self.elements.swap(self.focused, other);
+            self.focused = other;
+        }
         self.focused()
     }
 
+    /// Like [Ring::swap_focused_with_next] and [Ring::swap_focused_with_prev] other than at the
+    /// ends of the ring, where the whole ring is rotated instead so that the focused element
+    /// moves to the other end without changing the order of the remaining elements.
     pub fn drag_focused(&mut self, direction: Direction) -> Option<&T> {
-        match (self.focused, self.next_index(direction), direction) {
-            (0, _, Direction::Backward) => self.rotate(direction),
-            (_, 0, Direction::Forward) => self.rotate(direction),
-            (focused, other, _) => self.elements.swap(focused, other),
+        if self.would_wrap(direction) {
+            self.rotate(direction);
+            return self.cycle_focus(direction);
         }
 
-        self.cycle_focus(direction)
+        match direction {
+            Direction::Forward => self.swap_focused_with_next(),
+            Direction::Backward => self.swap_focused_with_prev(),
+        }
     }
 
     pub fn len(&self) -> usize {
@@ -251,8 +300,8 @@ impl<T> Ring<T> {
     }
 
     fn clamp_focus(&mut self) {
-        if self.focused > 0 && self.focused >= self.elements.len() - 1 {
-            self.focused -= 1;
+        if self.focused > 0 && self.focused >= self.elements.len() {
+            self.focused = self.elements.len().saturating_sub(1);
         }
     }
 
@@ -512,6 +561,25 @@ mod tests {
         assert_eq!(r.remove(&Selector::Focused), None);
     }
 
+    test_cases! {
+        remove_keeps_the_focus_position;
+        args: (focused: usize, removed: usize, expected_index: usize, expected: Option<u32>);
+
+        case: after_focus => (1, 2, 1, Some(2));
+        case: focused_middle => (1, 1, 1, Some(3));
+        case: focused_first => (0, 0, 0, Some(2));
+        case: focused_last => (2, 2, 1, Some(2));
+
+        body: {
+            let mut r = Ring::new(vec![1, 2, 3]);
+            r.focused = focused;
+            r.remove(&Selector::Index(removed));
+
+            assert_eq!(r.focused_index(), expected_index);
+            assert_eq!(r.focused(), expected.as_ref());
+        }
+    }
+
     #[test]
     fn indices_are_in_bounds() {
         let r = Ring::new(vec![1, 2, 3, 4, 5, 6]);
@@ -552,6 +620,143 @@ mod tests {
         assert_eq!(r.as_vec(), vec![1, 2, 3]);
     }
 
+    #[test]
+    fn focus_next_and_prev_wrap() {
+        let mut r = Ring::new(vec![1, 2, 3]);
+        assert_eq!(r.focus_prev(), Some(&3));
+        assert_eq!(r.focus_prev(), Some(&2));
+        assert_eq!(r.focus_next(), Some(&3));
+        assert_eq!(r.focus_next(), Some(&1));
+        assert_eq!(r.as_vec(), vec![1, 2, 3]);
+    }
+
+    test_cases! {
+        focus_next_matches_cycling_forward;
+        args: (focused: usize, expected: u32);
+
+        case: from_start => (0, 2);
+        case: from_middle => (1, 3);
+        case: from_end => (2, 1);
+
+        body: {
+            let mut r = Ring::new(vec![1, 2, 3]);
+            let mut cycled = r.clone();
+            r.focused = focused;
+            cycled.focused = focused;
+
+            assert_eq!(r.focus_next(), Some(&expected));
+            assert_eq!(cycled.cycle_focus(Direction::Forward), Some(&expected));
+            assert_eq!(r.focused_index(), cycled.focused_index());
+            assert_eq!(r.as_vec(), vec![1, 2, 3]);
+        }
+    }
+
+    #[test]
+    fn focus_movement_with_a_single_element() {
+        let mut r = Ring::new(vec![1]);
+        assert_eq!(r.focus_next(), Some(&1));
+        assert_eq!(r.focus_prev(), Some(&1));
+        assert_eq!(r.focused_index(), 0);
+    }
+
+    #[test]
+    fn operations_on_an_empty_ring_are_no_ops() {
+        let mut r: Ring<u32> = Ring::default();
+        for dir in &[Direction::Forward, Direction::Backward] {
+            assert!(!r.would_wrap(*dir));
+            assert_eq!(r.cycle_focus(*dir), None);
+            assert_eq!(r.drag_focused(*dir), None);
+            r.rotate(*dir);
+        }
+        assert_eq!(r.focus_next(), None);
+        assert_eq!(r.focus_prev(), None);
+        assert_eq!(r.swap_focused_with_next(), None);
+        assert_eq!(r.swap_focused_with_prev(), None);
+        r.rotate_forward();
+        r.rotate_backward();
+
+        assert_eq!(r.focused_index(), 0);
+        assert!(r.as_vec().is_empty());
+    }
+
+    #[test]
+    fn operations_on_a_single_element_leave_it_focused() {
+        let mut r = Ring::new(vec![1]);
+        for dir in &[Direction::Forward, Direction::Backward] {
+            assert!(r.would_wrap(*dir));
+            assert_eq!(r.drag_focused(*dir), Some(&1));
+            r.rotate(*dir);
+        }
+        assert_eq!(r.swap_focused_with_next(), Some(&1));
+        assert_eq!(r.swap_focused_with_prev(), Some(&1));
+        r.rotate_forward();
+        r.rotate_backward();
+
+        assert_eq!(r.focused_index(), 0);
+        assert_eq!(r.as_vec(), vec![1]);
+    }
+
+    test_cases! {
+        rotating_keeps_the_focus_point;
+        args: (forward: bool, focused: usize, expected: Vec<u32>, expected_focus: u32);
+
+        case: forward_from_start => (true, 0, vec![4, 1, 2, 3], 4);
+        case: forward_from_end => (true, 3, vec![4, 1, 2, 3], 3);
+        case: backward_from_start => (false, 0, vec![2, 3, 4, 1], 2);
+        case: backward_from_end => (false, 3, vec![2, 3, 4, 1], 1);
+
+        body: {
+            let mut r = Ring::new(vec![1, 2, 3, 4]);
+            r.focused = focused;
+            if forward {
+                r.rotate_forward();
+            } else {
+                r.rotate_backward();
+            }
+            assert_eq!(r.as_vec(), expected);
+            assert_eq!(r.focused_index(), focused);
+            assert_eq!(r.focused(), Some(&expected_focus));
+        }
+    }
+
+    test_cases! {
+        swapping_the_focused_element;
+        args: (forward: bool, focused: usize, expected: Vec<u32>, expected_index: usize);
+
+        case: next_from_start => (true, 0, vec![2, 1, 3, 4], 1);
+        case: next_from_middle => (true, 1, vec![1, 3, 2, 4], 2);
+        case: next_wraps_at_end => (true, 3, vec![4, 2, 3, 1], 0);
+        case: prev_from_end => (false, 3, vec![1, 2, 4, 3], 2);
+        case: prev_from_middle => (false, 2, vec![1, 3, 2, 4], 1);
+        case: prev_wraps_at_start => (false, 0, vec![4, 2, 3, 1], 3);
+
+        body: {
+            let mut r = Ring::new(vec![1, 2, 3, 4]);
+            r.focused = focused;
+            let original = r[focused];
+            let res = if forward {
+                r.swap_focused_with_next().copied()
+            } else {
+                r.swap_focused_with_prev().copied()
+            };
+            assert_eq!(res, Some(original));
+            assert_eq!(r.as_vec(), expected);
+            assert_eq!(r.focused_index(), expected_index);
+        }
+    }
+
+    #[test]
+    fn swapping_with_two_elements() {
+        let mut r = Ring::new(vec![1, 2]);
+        assert_eq!(r.swap_focused_with_next(), Some(&1));
+        assert_eq!(r.as_vec(), vec![2, 1]);
+        assert_eq!(r.swap_focused_with_next(), Some(&1));
+        assert_eq!(r.as_vec(), vec![1, 2]);
+        assert_eq!(r.swap_focused_with_prev(), Some(&1));
+        assert_eq!(r.as_vec(), vec![2, 1]);
+        assert_eq!(r.focused_index(), 1);
+    }
+
     #[test]
     fn element() {
         let mut r = Ring::new(vec![1, 2, 3, 4]);
@@ -359,6 +359,11 @@ impl Workspace {
         self.layouts.focused_unchecked().conf
     }
 
+    /// The main area ratio of the currently active Layout.
+    pub fn main_ratio(&self) -> f32 {
+        self.layouts.focused_unchecked().ratio()
+    }
+
     /// Cycle focus through the clients on this workspace, returning the previous and new focused
     /// client ids.
     ///
@@ -504,6 +509,16 @@ mod tests {
         assert_eq!(removed, 42);
     }
 
+    #[test]
+    fn removing_the_focused_client_focuses_the_next_client() {
+        let mut ws = Workspace::new("test", test_layouts());
+        ws.clients = Ring::new(vec![13, 42, 69]);
+        ws.focus_client(42);
+
+        assert_eq!(ws.remove_client(42), Some(42));
+        assert_eq!(ws.focused_client(), Some(69));
+    }
+
     #[test]
     fn removing_a_client_when_not_present() {
         let mut ws = Workspace::new("test", test_layouts());
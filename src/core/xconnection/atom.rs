@@ -9,7 +9,9 @@ use strum::*;
 ///
 /// [1]: crate::core::xconnection::XConn
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
-#[derive(AsRefStr, EnumString, EnumIter, Debug, Clone, Copy, Hash, PartialEq, Eq)]
+#[derive(
+    AsRefStr, IntoStaticStr, EnumString, EnumIter, Debug, Clone, Copy, Hash, PartialEq, Eq,
+)]
 pub enum Atom {
     /// ATOM
     #[strum(serialize = "ATOM")]
@@ -98,9 +100,18 @@ pub enum Atom {
     /// _NET_WM_STATE
     #[strum(serialize = "_NET_WM_STATE")]
     NetWmState,
+    /// _NET_WM_STATE_DEMANDS_ATTENTION
+    #[strum(serialize = "_NET_WM_STATE_DEMANDS_ATTENTION")]
+    NetWmStateDemandsAttention,
     /// _NET_WM_STATE_FULLSCREEN
     #[strum(serialize = "_NET_WM_STATE_FULLSCREEN")]
     NetWmStateFullscreen,
+    /// _NET_WM_STRUT
+    #[strum(serialize = "_NET_WM_STRUT")]
+    NetWmStrut,
+    /// _NET_WM_STRUT_PARTIAL
+    #[strum(serialize = "_NET_WM_STRUT_PARTIAL")]
+    NetWmStrutPartial,
     /// _NET_WM_WINDOW_TYPE
     #[strum(serialize = "_NET_WM_WINDOW_TYPE")]
     NetWmWindowType,
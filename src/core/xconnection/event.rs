@@ -43,8 +43,9 @@ pub enum XEvent {
     RandrNotify,
     /// Focus has moved to a different screen
     ScreenChange,
-    /// A client is being unmapped
-    UnmapNotify(Xid),
+    /// A client is being unmapped. The second id is the window that the event was reported to:
+    /// either the client itself or its parent.
+    UnmapNotify(Xid, Xid),
 }
 
 impl std::fmt::Display for XEvent {
@@ -64,7 +65,7 @@ impl std::fmt::Display for XEvent {
             XEvent::PropertyNotify(_) => write!(f, "PropertyNotify"),
             XEvent::RandrNotify => write!(f, "RandrNotify"),
             XEvent::ScreenChange => write!(f, "ScreenChange"),
-            XEvent::UnmapNotify(_) => write!(f, "UnmapNotify"),
+            XEvent::UnmapNotify(_, _) => write!(f, "UnmapNotify"),
         }
     }
 }
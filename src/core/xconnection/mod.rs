@@ -18,6 +18,7 @@ use crate::{
 };
 
 use penrose_proc::stubbed_companion_trait;
+use std::os::unix::io::RawFd;
 
 pub mod atom;
 pub mod event;
@@ -31,8 +32,8 @@ pub use event::{
     ExposeEvent, PointerChange, PropertyEvent, XEvent,
 };
 pub use property::{
-    MapState, Prop, WindowAttributes, WindowClass, WindowState, WmHints, WmNormalHints,
-    WmNormalHintsFlags,
+    MapState, Prop, WindowAttributes, WindowClass, WindowState, WmHints, WmHintsFlags,
+    WmNormalHints, WmNormalHintsFlags,
 };
 
 /// An X resource ID
@@ -105,6 +106,8 @@ pub enum ClientConfig {
     Position(Region),
     /// Mark this window as stacking on top of its peers
     StackAbove,
+    /// Mark this window as stacking below its peers
+    StackBelow,
 }
 
 /// Attributes for an X11 client window (not all are curently implemented)
@@ -184,6 +187,23 @@ pub trait XEventHandler {
     #[stub(Err(XError::Raw("mocked".into())))]
     fn wait_for_event(&self) -> Result<XEvent>;
 
+    /// Return the next event from the X server if there is one, without blocking.
+    ///
+    /// `None` must only be returned once all events already read from the X server have been
+    /// handed out, so that it is safe to wait on the [connection_fd][1] afterwards.
+    ///
+    /// [1]: XEventHandler::connection_fd
+    #[stub(Ok(None))]
+    fn poll_for_event(&self) -> Result<Option<XEvent>>;
+
+    /// The file descriptor of the underlying connection to the X server, if there is one.
+    ///
+    /// This allows for waiting on X events alongside other sources of input. Implementations
+    /// that are unable to provide a file descriptor should return `None`.
+    fn connection_fd(&self) -> Option<RawFd> {
+        None
+    }
+
     /// Send an X event to the target client
     ///
     /// The `msg` being sent can be composed by hand or, for known common message types, generated
@@ -237,6 +257,7 @@ pub trait XClientHandler {
         if let Some(c) = win {
             if c.mapped {
                 c.mapped = false;
+                c.pending_unmaps += 1;
                 self.unmap_client(c.id())?;
             }
         }
@@ -301,6 +322,45 @@ pub trait XClientProperties {
         }
     }
 
+    /// Check to see if a given client is requesting the user's attention, either by setting the
+    /// urgency flag in its `WM_HINTS` or by having `_NET_WM_STATE_DEMANDS_ATTENTION` in its
+    /// `_NET_WM_STATE`. Clients without either property set are not urgent.
+    fn client_is_urgent(&self, id: Xid) -> Result<bool> {
+        match self.get_prop(id, Atom::WmHints.as_ref()) {
+            Ok(Prop::WmHints(hints)) if hints.flags.contains(WmHintsFlags::URGENCY_HINT) => {
+                return Ok(true)
+            }
+            Ok(_) | Err(XError::MissingProperty(_, _)) => (),
+            Err(e) => return Err(e),
+        }
+
+        match self.get_prop(id, Atom::NetWmState.as_ref()) {
+            Ok(Prop::Atom(states)) => Ok(states
+                .iter()
+                .any(|s| s == Atom::NetWmStateDemandsAttention.as_ref())),
+            Ok(_) | Err(XError::MissingProperty(_, _)) => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Remove `_NET_WM_STATE_DEMANDS_ATTENTION` from the `_NET_WM_STATE` of the given client,
+    /// leaving any other states that are set in place.
+    fn clear_demands_attention(&self, id: Xid) -> Result<()> {
+        let states = match self.get_prop(id, Atom::NetWmState.as_ref()) {
+            Ok(Prop::Atom(states)) => states,
+            Ok(_) | Err(XError::MissingProperty(_, _)) => return Ok(()),
+            Err(e) => return Err(e),
+        };
+
+        let attention = Atom::NetWmStateDemandsAttention.as_ref();
+        if !states.iter().any(|s| s == attention) {
+            return Ok(());
+        }
+
+        let remaining = states.into_iter().filter(|s| s != attention).collect();
+        self.change_prop(id, Atom::NetWmState.as_ref(), Prop::Atom(remaining))
+    }
+
     /// Toggle the fullscreen state of the given client ID with the X server
     fn toggle_client_fullscreen(&self, id: Xid, client_is_fullscreen: bool) -> Result<()> {
         let data = if client_is_fullscreen {
@@ -467,6 +527,19 @@ pub trait XConn:
         mouse_bindings: &MouseBindings<Self>,
     ) -> Result<()>;
 
+    /// Grab all keyboard input so that every key press is reported to the WindowManager, not just
+    /// those for the bindings passed to [grab_keys][XConn::grab_keys].
+    ///
+    /// This is used while waiting for the next key of a [key chain][1].
+    ///
+    /// [1]: crate::core::bindings::key_chain
+    #[stub(Ok(()))]
+    fn grab_keyboard(&self) -> Result<()>;
+
+    /// Release a grab taken using [grab_keyboard][XConn::grab_keyboard]
+    #[stub(Ok(()))]
+    fn ungrab_keyboard(&self) -> Result<()>;
+
     /*
      *  The following default implementations should used if possible.
      *
@@ -712,7 +785,7 @@ mod mock_conn {
 mod tests {
     use super::*;
 
-    use std::str::FromStr;
+    use std::{cell::RefCell, str::FromStr};
 
     struct WmNameXConn {
         wm_name: bool,
@@ -754,4 +827,101 @@ mod tests {
             assert_eq!(&conn.client_name(42).unwrap(), expected);
         }
     }
+
+    struct UrgencyXConn {
+        hints: Option<WmHintsFlags>,
+        states: Option<Vec<Atom>>,
+    }
+
+    impl StubXClientProperties for UrgencyXConn {
+        fn mock_get_prop(&self, id: Xid, name: &str) -> Result<Prop> {
+            match (Atom::from_str(name)?, &self.hints, &self.states) {
+                (Atom::WmHints, Some(flags), _) => Ok(Prop::WmHints(WmHints::new(
+                    *flags,
+                    true,
+                    WindowState::Normal,
+                    0,
+                    0,
+                    Point::new(0, 0),
+                    0,
+                    0,
+                ))),
+                (Atom::NetWmState, _, Some(states)) => Ok(Prop::Atom(
+                    states.iter().map(|a| a.as_ref().to_string()).collect(),
+                )),
+                _ => Err(XError::MissingProperty(name.into(), id)),
+            }
+        }
+    }
+
+    test_cases! {
+        client_is_urgent;
+        args: (hints: Option<WmHintsFlags>, states: Option<Vec<Atom>>, expected: bool);
+
+        case: no_properties => (None, None, false);
+        case: hints_without_urgency => (Some(WmHintsFlags::INPUT_HINT), None, false);
+        case: urgency_hint => (Some(WmHintsFlags::URGENCY_HINT), None, true);
+        case: demands_attention => (None, Some(vec![Atom::NetWmStateDemandsAttention]), true);
+        case: other_states => (None, Some(vec![Atom::NetWmStateFullscreen]), false);
+
+        body: {
+            let conn = UrgencyXConn { hints, states };
+            assert_eq!(conn.client_is_urgent(42).unwrap(), expected);
+        }
+    }
+
+    struct WmStateXConn {
+        states: Option<Vec<&'static str>>,
+        written: RefCell<Option<Vec<String>>>,
+    }
+
+    impl StubXClientProperties for WmStateXConn {
+        fn mock_get_prop(&self, id: Xid, name: &str) -> Result<Prop> {
+            match &self.states {
+                Some(states) if name == Atom::NetWmState.as_ref() => {
+                    Ok(Prop::Atom(states.iter().map(|s| s.to_string()).collect()))
+                }
+                _ => Err(XError::MissingProperty(name.into(), id)),
+            }
+        }
+
+        fn mock_change_prop(&self, _id: Xid, name: &str, val: Prop) -> Result<()> {
+            match val {
+                Prop::Atom(states) if name == Atom::NetWmState.as_ref() => {
+                    self.written.replace(Some(states));
+                    Ok(())
+                }
+                _ => Err(XError::Raw(format!("unexpected prop change: {}", name))),
+            }
+        }
+    }
+
+    test_cases! {
+        clear_demands_attention;
+        args: (states: Option<Vec<&'static str>>, expected: Option<Vec<&'static str>>);
+
+        case: no_state => (None, None);
+        case: not_demanding_attention => (Some(vec!["_NET_WM_STATE_ABOVE"]), None);
+        case: only_demands_attention => (
+            Some(vec!["_NET_WM_STATE_DEMANDS_ATTENTION"]),
+            Some(vec![])
+        );
+        case: other_states_are_kept => (
+            Some(vec![
+                "_NET_WM_STATE_ABOVE",
+                "_NET_WM_STATE_DEMANDS_ATTENTION",
+                "_NET_WM_STATE_STICKY",
+                "_NET_WM_STATE_SKIP_TASKBAR",
+            ]),
+            Some(vec!["_NET_WM_STATE_ABOVE", "_NET_WM_STATE_STICKY", "_NET_WM_STATE_SKIP_TASKBAR"])
+        );
+
+        body: {
+            let conn = WmStateXConn { states, written: RefCell::new(None) };
+            conn.clear_demands_attention(42).unwrap();
+
+            let expected = expected.map(|v| v.iter().map(|s| s.to_string()).collect());
+            assert_eq!(conn.written.into_inner(), expected);
+        }
+    }
 }
@@ -18,6 +18,8 @@ pub enum Prop {
     Bytes(Vec<u32>),
     /// A cardinal number
     Cardinal(u32),
+    /// A list of cardinal numbers
+    Cardinals(Vec<u32>),
     /// UTF-8 encoded string data
     UTF8String(Vec<String>),
     /// An X window IDs
@@ -219,10 +221,11 @@ impl WmHints {
 /// Client requested hints about window geometry.
 ///
 /// See the ICCCM [spec][1] for further details or the [Xlib manual][2] for more details of the
-/// data fromat but note that Penrose does not honour the following hints:
-///   - gravity
-///   - increment
-///   - aspect ratio
+/// data fromat but note that Penrose does not honour the window gravity hint.
+///
+/// Only the hints whose corresponding [WmNormalHintsFlags] bit is set are used, following the
+/// ICCCM. Hints are applied to the size of floating clients using [apply_to][Self::apply_to]:
+/// tiled clients ignore them unless they have a fixed size (equal minimum and maximum sizes).
 ///
 /// [1]: https://www.x.org/releases/X11R7.6/doc/xorg-docs/specs/ICCCM/icccm.html#wm_normal_hints_property
 /// [2]: https://tronche.com/gui/x/xlib/ICC/client-to-window-manager/wm-normal-hints.html
@@ -234,10 +237,13 @@ pub struct WmNormalHints {
     pub(crate) min: Option<Region>,
     pub(crate) max: Option<Region>,
     pub(crate) user_specified: Option<Region>,
+    pub(crate) resize_inc: Option<(u32, u32)>,
+    pub(crate) min_aspect: Option<(u32, u32)>,
+    pub(crate) max_aspect: Option<(u32, u32)>,
 }
 
 impl WmNormalHints {
-    /// Create a new instance from component parts
+    /// Create a new instance from component parts with no resize increment or aspect ratio hints
     pub fn new(
         flags: WmNormalHintsFlags,
         base: Option<Region>,
@@ -251,7 +257,94 @@ impl WmNormalHints {
             min,
             max,
             user_specified,
+            resize_inc: None,
+            min_aspect: None,
+            max_aspect: None,
+        }
+    }
+
+    /// Set the width and height increments that this window should be resized in
+    pub fn with_resize_inc(mut self, w: u32, h: u32) -> Self {
+        self.resize_inc = Some((w, h));
+        self
+    }
+
+    /// Set the minimum and maximum aspect ratios of this window as (numerator, denominator)
+    /// pairs of width to height
+    pub fn with_aspect(mut self, min: (u32, u32), max: (u32, u32)) -> Self {
+        self.min_aspect = Some(min);
+        self.max_aspect = Some(max);
+        self
+    }
+
+    /// Whether or not these hints specify a fixed size for the window, having a minimum size
+    /// equal to its maximum size.
+    pub fn is_fixed_size(&self) -> bool {
+        match (self.min, self.max) {
+            (Some(min), Some(max)) => {
+                let ((_, _, min_w, min_h), (_, _, max_w, max_h)) = (min.values(), max.values());
+                min_w == max_w && min_h == max_h
+            }
+            _ => false,
+        }
+    }
+
+    /// Adjust the size of `r` to satisfy these hints, leaving its position unchanged.
+    ///
+    /// The size is reduced to meet the aspect ratio and resize increments of the window before
+    /// being clamped between its minimum and maximum size, following the procedure described
+    /// in section 4.1.2.3 of the ICCCM.
+    pub fn apply_to(&self, r: &Region) -> Region {
+        let (x, y, mut w, mut h) = r.values();
+        let dims = |r: Option<Region>| r.map(|r| (r.values().2, r.values().3));
+
+        // The base size and minimum size are used as fallbacks for one another
+        let (base_w, base_h) = dims(self.base.or(self.min)).unwrap_or((0, 0));
+        let (min_w, min_h) = dims(self.min.or(self.base)).unwrap_or((0, 0));
+
+        // Aspect ratios are checked against the size minus the base size only when the base
+        // size has been explicitly provided
+        let base_is_min = (base_w, base_h) == (min_w, min_h);
+        if !base_is_min {
+            w = w.saturating_sub(base_w);
+            h = h.saturating_sub(base_h);
+        }
+
+        if let (Some((min_x, min_y)), Some((max_x, max_y))) = (self.min_aspect, self.max_aspect) {
+            let (wf, hf) = (w as f64, h as f64);
+            if min_x > 0 && min_y > 0 && max_x > 0 && max_y > 0 && w > 0 && h > 0 {
+                let (min_a, max_a) = (min_x as f64 / min_y as f64, max_x as f64 / max_y as f64);
+                if wf / hf > max_a {
+                    w = (hf * max_a).round() as u32;
+                } else if wf / hf < min_a {
+                    h = (wf / min_a).round() as u32;
+                }
+            }
         }
+
+        if base_is_min {
+            w = w.saturating_sub(base_w);
+            h = h.saturating_sub(base_h);
+        }
+
+        if let Some((inc_w, inc_h)) = self.resize_inc {
+            if inc_w > 0 {
+                w -= w % inc_w;
+            }
+            if inc_h > 0 {
+                h -= h % inc_h;
+            }
+        }
+
+        w = (w + base_w).max(min_w);
+        h = (h + base_h).max(min_h);
+
+        if let Some((max_w, max_h)) = dims(self.max) {
+            w = w.min(max_w);
+            h = h.min(max_h);
+        }
+
+        Region::new(x, y, w, h)
     }
 
     /// Try to construct a [WmNormalHints] instance from raw bytes.
@@ -283,7 +376,8 @@ impl WmNormalHints {
             )));
         }
 
-        let flags = WmNormalHintsFlags::from_bits(raw[0]).unwrap();
+        // Unknown bits are dropped rather than rejecting the hints entirely
+        let flags = WmNormalHintsFlags::from_bits_truncate(raw[0]);
 
         // These properties are marked as obsolete but some clients still set them
         // so it they are useful as fallbacks
@@ -292,25 +386,38 @@ impl WmNormalHints {
 
         let (min_w, min_h) = (raw[5], raw[6]);
         let (max_w, max_h) = (raw[7], raw[8]);
+        let (inc_w, inc_h) = (raw[9], raw[10]);
+        let (min_aspect, max_aspect) = ((raw[11], raw[12]), (raw[13], raw[14]));
         let (base_w, base_h) = (raw[15], raw[16]);
 
-        // ignoring increment, aspect ratio, gravity as they are not used in
-        // the main WindowManager logic
+        // ignoring gravity as it is not used in the main WindowManager logic
 
-        let if_set = |x, y, w, h| {
-            if w > 0 && h > 0 {
+        let if_set = |flag, x, y, w, h| {
+            if flags.intersects(flag) && w > 0 && h > 0 {
                 Some(Region::new(x, y, w, h))
             } else {
                 None
             }
         };
+        let pair_if_set = |flag, pair: (u32, u32)| {
+            if flags.contains(flag) && pair.0 > 0 && pair.1 > 0 {
+                Some(pair)
+            } else {
+                None
+            }
+        };
+
+        use WmNormalHintsFlags as F;
 
         Ok(Self {
             flags,
-            base: if_set(x, y, base_w, base_h),
-            min: if_set(x, y, min_w, min_h),
-            max: if_set(x, y, max_w, max_h),
-            user_specified: if_set(x, y, user_w, user_h),
+            base: if_set(F::P_BASE_SIZE, x, y, base_w, base_h),
+            min: if_set(F::P_MIN_SIZE, x, y, min_w, min_h),
+            max: if_set(F::P_MAX_SIZE, x, y, max_w, max_h),
+            user_specified: if_set(F::U_SIZE | F::P_SIZE, x, y, user_w, user_h),
+            resize_inc: pair_if_set(F::P_RESIZE_INC, (inc_w, inc_h)),
+            min_aspect: pair_if_set(F::P_ASPECT, min_aspect),
+            max_aspect: pair_if_set(F::P_ASPECT, max_aspect),
         })
     }
 }
@@ -361,3 +468,100 @@ impl WindowAttributes {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use WmNormalHintsFlags as F;
+
+    fn raw_normal_hints(flags: WmNormalHintsFlags) -> Vec<u32> {
+        #[rustfmt::skip]
+        let raw = vec![
+            flags.bits(),
+            10, 20,     // x, y
+            300, 200,   // width, height
+            100, 50,    // min
+            800, 600,   // max
+            10, 20,     // increments
+            1, 1, 2, 1, // min aspect, max aspect
+            4, 6,       // base
+            0,          // gravity
+        ];
+
+        raw
+    }
+
+    #[test]
+    fn normal_hints_only_use_fields_with_their_flag_set() {
+        let hints = WmNormalHints::try_from_bytes(&raw_normal_hints(F::P_MIN_SIZE)).unwrap();
+
+        assert_eq!(hints.min, Some(Region::new(10, 20, 100, 50)));
+        assert_eq!(hints.max, None);
+        assert_eq!(hints.base, None);
+        assert_eq!(hints.resize_inc, None);
+        assert_eq!(hints.min_aspect, None);
+    }
+
+    #[test]
+    fn normal_hints_parse_all_fields() {
+        let hints = WmNormalHints::try_from_bytes(&raw_normal_hints(F::all())).unwrap();
+
+        assert_eq!(hints.user_specified, Some(Region::new(10, 20, 300, 200)));
+        assert_eq!(hints.max, Some(Region::new(10, 20, 800, 600)));
+        assert_eq!(hints.base, Some(Region::new(10, 20, 4, 6)));
+        assert_eq!(hints.resize_inc, Some((10, 20)));
+        assert_eq!(hints.min_aspect, Some((1, 1)));
+        assert_eq!(hints.max_aspect, Some((2, 1)));
+    }
+
+    #[test]
+    fn normal_hints_ignore_unknown_flag_bits() {
+        let mut raw = raw_normal_hints(F::P_MIN_SIZE);
+        raw[0] |= 1 << 20;
+
+        let hints = WmNormalHints::try_from_bytes(&raw).unwrap();
+        assert_eq!(hints.flags, F::P_MIN_SIZE);
+    }
+
+    #[test]
+    fn normal_hints_must_be_18_u32s() {
+        assert!(WmNormalHints::try_from_bytes(&[0; 15]).is_err());
+    }
+
+    fn hints(min: Option<(u32, u32)>, max: Option<(u32, u32)>) -> WmNormalHints {
+        let r = |(w, h)| Region::new(0, 0, w, h);
+        WmNormalHints::new(F::empty(), None, min.map(r), max.map(r), None)
+    }
+
+    test_cases! {
+        apply_size_hints;
+        args: (hints: WmNormalHints, w: u32, h: u32, expected: (u32, u32));
+
+        case: no_hints => (hints(None, None), 300, 200, (300, 200));
+        case: grown_to_min => (hints(Some((400, 300)), None), 300, 200, (400, 300));
+        case: shrunk_to_max => (hints(None, Some((100, 50))), 300, 200, (100, 50));
+        case: fixed_size => (hints(Some((80, 60)), Some((80, 60))), 300, 20, (80, 60));
+        case: increments => (hints(None, None).with_resize_inc(7, 9), 300, 200, (294, 198));
+        case: increments_from_base => (
+            WmNormalHints::new(F::empty(), Some(Region::new(0, 0, 4, 6)), None, None, None)
+                .with_resize_inc(10, 10),
+            300, 200, (294, 196)
+        );
+        case: too_wide_for_aspect => (hints(None, None).with_aspect((1, 1), (2, 1)), 500, 100, (200, 100));
+        case: too_tall_for_aspect => (hints(None, None).with_aspect((1, 1), (2, 1)), 100, 500, (100, 100));
+        case: within_aspect => (hints(None, None).with_aspect((1, 1), (2, 1)), 300, 200, (300, 200));
+
+        body: {
+            let r = hints.apply_to(&Region::new(5, 10, w, h));
+            assert_eq!(r, Region::new(5, 10, expected.0, expected.1));
+        }
+    }
+
+    #[test]
+    fn is_fixed_size() {
+        assert!(hints(Some((80, 60)), Some((80, 60))).is_fixed_size());
+        assert!(!hints(Some((80, 60)), Some((80, 61))).is_fixed_size());
+        assert!(!hints(Some((80, 60)), None).is_fixed_size());
+    }
+}
@@ -48,19 +48,56 @@
 //! ```
 use crate::{
     core::{
-        data_types::{Region, WinType},
+        bindings::{MouseButton, MouseEvent, MouseEventKind},
+        data_types::{Point, Region},
         hooks::Hook,
         manager::WindowManager,
         xconnection::{Atom, Prop, XConn, Xid},
     },
-    draw::{Color, Draw, DrawContext, HookableWidget, Result, TextStyle},
+    draw::{
+        ClickTarget, Color, DrawContext, DrawError, FontMetrics, HookableWidget, RenderedText,
+        Result, TextStyle, Widget, XDraw,
+    },
 };
 
-use std::fmt;
+use std::{
+    collections::HashMap,
+    fmt,
+    path::Path,
+    time::{Duration, Instant},
+};
 
 use crate::draw::widget::{ActiveWindowName, CurrentLayout, RootWindowName, Workspaces};
 
 const MAX_ACTIVE_WINDOW_CHARS: usize = 80;
+const DIM_FACTOR: f64 = 0.6;
+
+/// Convenience re-exports of everything you need to build a [StatusBar].
+///
+/// ```no_run
+/// use penrose::draw::bar::prelude::*;
+/// ```
+pub mod prelude {
+    #[doc(no_inline)]
+    pub use crate::draw::{
+        bar::{
+            dwm_bar, Animator, Border, Easing, Edge, Position, StatusBar, StatusBarBuilder, Zone,
+        },
+        widget::{
+            ActiveWindowName, ClickTarget, CurrentLayout, FlexSpacer, HookableWidget,
+            IndicatorStyle, RootWindowName, Separator, Text, Widget, Workspaces,
+        },
+        Color, TextStyle,
+    };
+
+    #[cfg(feature = "draw_stats")]
+    #[doc(no_inline)]
+    pub use crate::draw::bar::DrawStats;
+
+    #[cfg(feature = "chrono")]
+    #[doc(no_inline)]
+    pub use crate::draw::widget::ClockWidget;
+}
 
 /// Create a default dwm style status bar that displays content pulled from the
 /// WM_NAME property of the root window.
@@ -74,7 +111,7 @@ pub fn dwm_bar<C, D, X>(
 ) -> Result<StatusBar<C, D, X>>
 where
     C: DrawContext + 'static,
-    D: Draw<Ctx = C>,
+    D: XDraw<Ctx = C>,
     X: XConn,
 {
     let highlight = highlight.into();
@@ -120,28 +157,390 @@ pub enum Position {
     Bottom,
 }
 
+// The region occupied by a bar 'h' pixels tall at the given position on 'screen'
+pub(crate) fn bar_region(position: Position, screen: Region, h: u32) -> Region {
+    let (sx, sy, sw, sh) = screen.values();
+    let h = h.min(sh);
+    let y = match position {
+        Position::Top => sy,
+        Position::Bottom => sy + sh - h,
+    };
+
+    Region::new(sx, y, sw, h)
+}
+
+// The _NET_WM_STRUT_PARTIAL values for a bar occupying 'r' at the given position on a root window
+// that is 'root_h' pixels tall. The first four values are the _NET_WM_STRUT values.
+pub(crate) fn strut_partial(position: Position, r: Region, root_h: u32) -> [u32; 12] {
+    let (x, y, w, h) = r.values();
+    let (start_x, end_x) = (x, (x + w).saturating_sub(1));
+
+    // left, right, top, bottom, then the start and end of each along its edge
+    let mut strut = [0; 12];
+    match position {
+        Position::Top => {
+            strut[2] = y + h;
+            strut[8] = start_x;
+            strut[9] = end_x;
+        }
+        Position::Bottom => {
+            strut[3] = root_h.saturating_sub(y);
+            strut[10] = start_x;
+            strut[11] = end_x;
+        }
+    }
+
+    strut
+}
+
+/// An edge of a status bar along which a border line can be drawn
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Edge {
+    /// The top edge of the bar
+    Top,
+    /// The bottom edge of the bar
+    Bottom,
+}
+
+/// The edge, thickness in pixels and color of a [StatusBar] border
+pub type Border = (Edge, f64, Color);
+
+/// A section of a [StatusBar] that can be given its own background color.
+///
+/// Zones are determined by the greedy widgets in the bar: the center zone runs from the first
+/// visible greedy widget to the last, with the left and right zones containing the widgets
+/// either side of it. If there are no visible greedy widgets then all widgets are in the left
+/// zone.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Zone {
+    /// Widgets before the first greedy widget
+    Left,
+    /// Greedy widgets and any widgets between them
+    Center,
+    /// Widgets after the last greedy widget
+    Right,
+}
+
+/// A builder for constructing a [StatusBar] one field at a time.
+///
+/// The bar height must be set before calling [StatusBarBuilder::build]. If not otherwise
+/// specified, the bar will be placed at the top of the screen with a black background.
+///
+/// ```no_run
+/// # use penrose::__test_helpers::*;
+/// use penrose::draw::bar::prelude::*;
+/// use penrose::xcb::XcbDraw;
+///
+/// # fn example(style: &TextStyle) -> penrose::draw::Result<()> {
+/// let bar: StatusBar<_, _, TestXConn> = StatusBarBuilder::new()
+///     .height(18)
+///     .position(Position::Bottom)
+///     .background(0x282828)
+///     .font("mono")
+///     .widget(Box::new(CurrentLayout::new(style)))
+///     .build(XcbDraw::new()?)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct StatusBarBuilder<X>
+where
+    X: XConn,
+{
+    position: Position,
+    height: Option<usize>,
+    bg: Color,
+    fonts: Vec<String>,
+    border: Option<Border>,
+    zone_backgrounds: HashMap<Zone, Color>,
+    widgets: Vec<Box<dyn HookableWidget<X>>>,
+    right_widgets: Vec<Box<dyn HookableWidget<X>>>,
+}
+
+impl<X> fmt::Debug for StatusBarBuilder<X>
+where
+    X: XConn,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StatusBarBuilder")
+            .field("position", &self.position)
+            .field("height", &self.height)
+            .field("bg", &self.bg)
+            .field("fonts", &self.fonts)
+            .field("border", &self.border)
+            .field("zone_backgrounds", &self.zone_backgrounds)
+            .field("widgets", &stringify!(self.widgets))
+            .field("right_widgets", &stringify!(self.right_widgets))
+            .finish()
+    }
+}
+
+impl<X> Default for StatusBarBuilder<X>
+where
+    X: XConn,
+{
+    fn default() -> Self {
+        Self {
+            position: Position::Top,
+            height: None,
+            bg: 0x000000.into(),
+            fonts: vec![],
+            border: None,
+            zone_backgrounds: HashMap::new(),
+            widgets: vec![],
+            right_widgets: vec![],
+        }
+    }
+}
+
+impl<X> StatusBarBuilder<X>
+where
+    X: XConn,
+{
+    /// Create a new builder with default values
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set whether the bar is placed at the top or the bottom of the screen
+    pub fn position(&mut self, position: Position) -> &mut Self {
+        self.position = position;
+        self
+    }
+
+    /// Set the height of the bar in pixels
+    pub fn height(&mut self, px: usize) -> &mut Self {
+        self.height = Some(px);
+        self
+    }
+
+    /// Set the background color of the bar
+    pub fn background(&mut self, bg: impl Into<Color>) -> &mut Self {
+        self.bg = bg.into();
+        self
+    }
+
+    /// Register a font to be used by widgets in the bar
+    pub fn font(&mut self, font: impl Into<String>) -> &mut Self {
+        self.fonts.push(font.into());
+        self
+    }
+
+    /// Draw a border line of the given thickness and color along one edge of the bar
+    pub fn border(&mut self, edge: Edge, thickness: f64, color: impl Into<Color>) -> &mut Self {
+        self.border = Some((edge, thickness, color.into()));
+        self
+    }
+
+    /// Set the background color of one [Zone] of the bar
+    pub fn zone_background(&mut self, zone: Zone, color: impl Into<Color>) -> &mut Self {
+        self.zone_backgrounds.insert(zone, color.into());
+        self
+    }
+
+    /// Append a widget to the right of any widgets added so far
+    pub fn widget(&mut self, widget: Box<dyn HookableWidget<X>>) -> &mut Self {
+        self.widgets.push(widget);
+        self
+    }
+
+    /// Append multiple widgets to the right of any widgets added so far
+    pub fn widgets(&mut self, widgets: Vec<Box<dyn HookableWidget<X>>>) -> &mut Self {
+        self.widgets.extend(widgets);
+        self
+    }
+
+    /// Append a widget to the section of the bar that sits flush against its right edge.
+    ///
+    /// See [StatusBar::set_right_section] for how the two sections are laid out.
+    pub fn right_widget(&mut self, widget: Box<dyn HookableWidget<X>>) -> &mut Self {
+        self.right_widgets.push(widget);
+        self
+    }
+
+    /// Append multiple widgets to the section of the bar that sits flush against its right edge
+    pub fn right_widgets(&mut self, widgets: Vec<Box<dyn HookableWidget<X>>>) -> &mut Self {
+        self.right_widgets.extend(widgets);
+        self
+    }
+
+    /// Validate the current settings and create the [StatusBar], moving all added widgets into
+    /// it. Fails if a required field is missing or the bar windows can not be created.
+    pub fn build<C, D>(&mut self, drw: D) -> Result<StatusBar<C, D, X>>
+    where
+        C: DrawContext,
+        D: XDraw<Ctx = C>,
+    {
+        let h = match self.height {
+            Some(h) if h > 0 => h,
+            _ => return Err(DrawError::MissingField("height")),
+        };
+        let fonts: Vec<&str> = self.fonts.iter().map(|f| f.as_str()).collect();
+
+        let mut widgets = std::mem::take(&mut self.widgets);
+        let right_start = (!self.right_widgets.is_empty()).then_some(widgets.len());
+        widgets.append(&mut self.right_widgets);
+
+        let mut bar = StatusBar::try_new(drw, self.position, h, self.bg, &fonts, widgets)?;
+        bar.set_right_section(right_start);
+        bar.set_border(self.border);
+        for (&zone, &color) in self.zone_backgrounds.iter() {
+            bar.set_zone_background(zone, Some(color));
+        }
+
+        Ok(bar)
+    }
+}
+
+/// An easing curve used by an [Animator] to map linear progress onto animated progress
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum Easing {
+    /// Progress at a constant rate
+    Linear,
+    /// Start quickly and slow down towards the end
+    EaseOut,
+}
+
+impl Easing {
+    /// Apply this easing curve to linear progress `t`, clamped to the 0.0-1.0 range
+    pub fn apply(&self, t: f64) -> f64 {
+        let t = t.clamp(0.0, 1.0);
+        match self {
+            Self::Linear => t,
+            Self::EaseOut => 1.0 - (1.0 - t).powi(2),
+        }
+    }
+}
+
+/// Interpolation between two values over a fixed duration, for animating widgets.
+///
+/// An Animator holds no timing state of its own: widgets track when the animation started and
+/// ask for the value at the time elapsed since then. Colors can be animated using
+/// [Animator::progress_at] together with [Color::lerp].
+///
+/// ```
+/// # use penrose::draw::bar::{Animator, Easing};
+/// # use std::time::Duration;
+/// let anim = Animator::new(0.0, 100.0, Duration::from_millis(200), Easing::Linear);
+///
+/// assert_eq!(anim.value_at(Duration::from_millis(0)), 0.0);
+/// assert_eq!(anim.value_at(Duration::from_millis(100)), 50.0);
+/// assert_eq!(anim.value_at(Duration::from_millis(500)), 100.0);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Animator {
+    start: f64,
+    end: f64,
+    duration: Duration,
+    easing: Easing,
+}
+
+impl Animator {
+    /// Create a new [Animator] moving from `start` to `end` over `duration`
+    pub fn new(start: f64, end: f64, duration: Duration, easing: Easing) -> Self {
+        Self {
+            start,
+            end,
+            duration,
+            easing,
+        }
+    }
+
+    /// The eased progress of the animation after `elapsed`, in the range 0.0-1.0
+    pub fn progress_at(&self, elapsed: Duration) -> f64 {
+        if self.duration.is_zero() {
+            return 1.0;
+        }
+
+        self.easing
+            .apply(elapsed.as_secs_f64() / self.duration.as_secs_f64())
+    }
+
+    /// The interpolated value after `elapsed`
+    pub fn value_at(&self, elapsed: Duration) -> f64 {
+        self.start + (self.end - self.start) * self.progress_at(elapsed)
+    }
+
+    /// Whether or not the animation has reached its end value after `elapsed`
+    pub fn is_finished(&self, elapsed: Duration) -> bool {
+        elapsed >= self.duration
+    }
+}
+
+/// Accumulated render timings for a [StatusBar]
+///
+/// Only available when the `draw_stats` feature is enabled. Timings are summed across all calls
+/// to [StatusBar::redraw] until [DrawStats::reset] is called.
+#[cfg(feature = "draw_stats")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DrawStats {
+    /// The number of redraws that have been timed
+    pub redraws: u32,
+    /// Total time spent inside of [StatusBar::redraw]
+    pub redraw: Duration,
+    /// Total time spent drawing each widget, indexed by widget position in the bar
+    pub widgets: Vec<Duration>,
+    /// Total time spent flushing rendered content through to the X server
+    pub flush: Duration,
+}
+
+#[cfg(feature = "draw_stats")]
+impl DrawStats {
+    /// The mean time taken for a single call to [StatusBar::redraw]
+    pub fn mean_redraw(&self) -> Duration {
+        if self.redraws == 0 {
+            Duration::default()
+        } else {
+            self.redraw / self.redraws
+        }
+    }
+
+    /// Clear all accumulated timings
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    fn record_widgets(&mut self, timings: &[Duration]) {
+        if self.widgets.len() < timings.len() {
+            self.widgets.resize(timings.len(), Duration::default());
+        }
+        for (total, d) in self.widgets.iter_mut().zip(timings) {
+            *total += *d;
+        }
+    }
+}
+
 /// A simple status bar that works via hooks
 pub struct StatusBar<C, D, X>
 where
     C: DrawContext,
-    D: Draw<Ctx = C>,
+    D: XDraw<Ctx = C>,
     X: XConn,
 {
     drw: D,
     position: Position,
     /// The widgets contained within this status bar
     pub widgets: Vec<Box<dyn HookableWidget<X>>>,
-    screens: Vec<(Xid, f64)>, // window and width
+    right_start: Option<usize>,
+    screens: Vec<(Xid, f64)>,        // window and width
+    positions: Vec<Vec<(f64, f64)>>, // x offset and width of each widget per screen
     hpx: usize,
     h: f64,
     bg: Color,
+    fonts: Vec<String>,
+    border: Option<Border>,
+    zone_backgrounds: HashMap<Zone, Color>,
     active_screen: usize,
+    dimmed: bool,
+    drag: Option<(usize, usize, MouseButton)>, // screen, widget and button of an active drag
+    last_updated: Vec<Option<Instant>>,        // when each widget was last refreshed
+    #[cfg(feature = "draw_stats")]
+    stats: DrawStats,
 }
 
 impl<C, D, X> fmt::Debug for StatusBar<C, D, X>
 where
     C: DrawContext,
-    D: Draw<Ctx = C>,
+    D: XDraw<Ctx = C>,
     X: XConn,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -149,10 +548,14 @@ where
             .field("drw", &stringify!(self.drw))
             .field("position", &self.position)
             .field("widgets", &stringify!(self.widgets))
+            .field("right_start", &self.right_start)
             .field("screens", &self.screens)
             .field("hpx", &self.hpx)
             .field("bg", &self.bg)
+            .field("border", &self.border)
+            .field("zone_backgrounds", &self.zone_backgrounds)
             .field("active_screen", &self.active_screen)
+            .field("dimmed", &self.dimmed)
             .finish()
     }
 }
@@ -160,7 +563,7 @@ where
 impl<C, D, X> StatusBar<C, D, X>
 where
     C: DrawContext,
-    D: Draw<Ctx = C>,
+    D: XDraw<Ctx = C>,
     X: XConn,
 {
     /// Try to initialise a new empty status bar. Can fail if we are unable to create our window
@@ -176,14 +579,26 @@ where
             drw,
             position,
             widgets,
+            right_start: None,
             screens: vec![],
+            positions: vec![],
             hpx: h,
             h: h as f64,
             bg: bg.into(),
+            fonts: fonts.iter().map(|f| f.to_string()).collect(),
+            border: None,
+            zone_backgrounds: HashMap::new(),
             active_screen: 0,
+            dimmed: false,
+            drag: None,
+            last_updated: vec![],
+            #[cfg(feature = "draw_stats")]
+            stats: DrawStats::default(),
         };
         bar.init_for_screens()?;
-        fonts.iter().for_each(|f| bar.drw.register_font(f));
+        for f in fonts {
+            bar.drw.register_font(f)?;
+        }
 
         Ok(bar)
     }
@@ -192,17 +607,9 @@ where
         let screen_sizes = self.drw.screen_sizes()?;
         self.screens = screen_sizes
             .iter()
-            .map(|r| {
-                let (sx, sy, sw, sh) = r.values();
-                let y = match self.position {
-                    Position::Top => sy as usize,
-                    Position::Bottom => sh as usize - self.hpx,
-                };
-                let id = self.drw.new_window(
-                    WinType::InputOutput(Atom::NetWindowTypeDock),
-                    Region::new(sx, y as u32, sw, self.hpx as u32),
-                    false,
-                )?;
+            .enumerate()
+            .map(|(ix, r)| {
+                let id = self.drw.new_dock(self.position, self.hpx as u32, ix)?;
 
                 let p = Prop::UTF8String(vec!["penrose-statusbar".to_string()]);
                 for atom in &[Atom::NetWmName, Atom::WmName, Atom::WmClass] {
@@ -210,77 +617,777 @@ where
                 }
 
                 self.drw.flush(id)?;
-                Ok((id, sw as f64))
+                Ok((id, r.w as f64))
             })
             .collect::<Result<Vec<(u32, f64)>>>()?;
 
         Ok(())
     }
 
-    /// Re-render all widgets in this status bar
+    /// Render timings accumulated so far for this status bar
+    #[cfg(feature = "draw_stats")]
+    pub fn draw_stats(&self) -> &DrawStats {
+        &self.stats
+    }
+
+    /// Mutable access to the render timings for this status bar (e.g. to reset them)
+    #[cfg(feature = "draw_stats")]
+    pub fn draw_stats_mut(&mut self) -> &mut DrawStats {
+        &mut self.stats
+    }
+
+    /// Dim the bars on screens that do not currently have focus.
+    ///
+    /// While enabled, every color rendered to an unfocused screen (including the bar background)
+    /// has its brightness scaled down, giving a visual cue as to which screen is active.
+    pub fn set_dimmed(&mut self, dimmed: bool) {
+        self.dimmed = dimmed;
+    }
+
+    /// Split the widgets of this bar into a left and right section, with the right section
+    /// made up of the widgets from index `start` onward. Passing `None` places all widgets in
+    /// the left section.
+    ///
+    /// Widgets in the right section are laid out flush against the right edge of the bar while
+    /// the left section grows rightward from the left edge. If the left section would overlap
+    /// the right, the widget crossing into it is truncated to fit (and any following it are
+    /// given no space) so that the right section is always fully visible.
+    pub fn set_right_section(&mut self, start: Option<usize>) {
+        self.right_start = start;
+    }
+
+    /// Set or remove a border line drawn along one edge of the bar.
+    ///
+    /// Widgets are rendered in the space left over once the border thickness has been taken from
+    /// the bar height so that they never overlap the border.
+    pub fn set_border(&mut self, border: Option<Border>) {
+        self.border = border;
+    }
+
+    /// Set or remove the background color for one [Zone] of the bar.
+    ///
+    /// Zone backgrounds are drawn over the bar background before any widgets are rendered and
+    /// span from the start of the first widget in the zone to the end of the last (including
+    /// margins). The left and right zones are extended to the edges of the bar.
+    pub fn set_zone_background(&mut self, zone: Zone, color: Option<Color>) {
+        match color {
+            Some(color) => self.zone_backgrounds.insert(zone, color),
+            None => self.zone_backgrounds.remove(&zone),
+        };
+    }
+
+    // Re-establish a lost connection to the X server and recreate our windows. The ids of the
+    // new windows will differ from the old ones but as they are only tracked in self.screens
+    // they are updated in a single place.
+    fn reconnect(&mut self) -> Result<()> {
+        warn!("status bar lost its connection to the X server: attempting to reconnect");
+        self.drw
+            .reconnect()
+            .map_err(|e| DrawError::ReconnectFailed(e.to_string()))?;
+
+        self.init_for_screens()
+            .map_err(|e| DrawError::ReconnectFailed(e.to_string()))?;
+        // fonts were checked when the bar was first created
+        for f in self.fonts.iter() {
+            self.drw.register_font_unchecked(f);
+        }
+
+        Ok(())
+    }
+
+    /// Re-render all widgets in this status bar.
+    ///
+    /// If the connection to the X server has been lost then an attempt will be made to
+    /// reconnect and recreate the status bar windows before rendering.
     pub fn redraw(&mut self) -> Result<()> {
+        if !self.drw.connection_alive() {
+            self.reconnect()?;
+        }
+
+        #[cfg(feature = "draw_stats")]
+        let start = Instant::now();
+
+        self.positions.clear();
         for (i, &(id, w)) in self.screens.clone().iter().enumerate() {
             let screen_has_focus = self.active_screen == i;
             let mut ctx = self.drw.context_for(id)?;
+            let mut dimmed_ctx;
+            let ctx: &mut dyn DrawContext = if self.dimmed && !screen_has_focus {
+                dimmed_ctx = DimmedContext {
+                    inner: &mut ctx,
+                    factor: DIM_FACTOR,
+                };
+                &mut dimmed_ctx
+            } else {
+                &mut ctx
+            };
 
             ctx.clear()?;
 
             ctx.color(&self.bg);
             ctx.rectangle(0.0, 0.0, w, self.h as f64)?;
 
-            let extents = self.layout(&mut ctx, w)?;
-            let mut x = 0.0;
-            for (wd, (w, _)) in self.widgets.iter_mut().zip(extents) {
-                wd.draw(&mut ctx, self.active_screen, screen_has_focus, w, self.h)?;
-                x += w;
-                ctx.flush();
+            let (content_y, content_h) = content_area(self.border, self.h);
+            ctx.set_y_offset(content_y);
+
+            let (extents, margins) = self.layout(ctx, w)?;
+
+            if !self.zone_backgrounds.is_empty() {
+                let zones = widget_zones(
+                    &self
+                        .widgets
+                        .iter()
+                        .map(|wd| wd.is_visible() && wd.is_greedy())
+                        .collect::<Vec<_>>(),
+                );
+                let spans = zone_spans(&zones, &extents, &margins, w);
+
+                for zone in [Zone::Left, Zone::Center, Zone::Right] {
+                    if let (Some(color), Some(&(x, zw))) =
+                        (self.zone_backgrounds.get(&zone), spans.get(&zone))
+                    {
+                        ctx.color(color);
+                        ctx.rectangle(x, 0.0, zw, content_h)?;
+                    }
+                }
+            }
+
+            let right_start = self.right_start.unwrap_or(self.widgets.len());
+            let positions = widget_positions(&extents, &margins, right_start, w);
+            #[cfg(feature = "draw_stats")]
+            let mut widget_timings = Vec::with_capacity(self.widgets.len());
+
+            for (wd, &(x, w)) in self.widgets.iter_mut().zip(positions.iter()) {
+                if !wd.is_visible() {
+                    #[cfg(feature = "draw_stats")]
+                    widget_timings.push(Duration::default());
+                    continue;
+                }
+
+                #[cfg(feature = "draw_stats")]
+                let t = Instant::now();
+
+                // Each widget starts from a clean context regardless of what the previous one did
+                ctx.save()?;
+                ctx.reset();
+                ctx.set_y_offset(content_y);
                 ctx.set_x_offset(x);
+                wd.draw(ctx, self.active_screen, screen_has_focus, w, content_h)?;
+                ctx.restore()?;
+                ctx.flush();
+
+                #[cfg(feature = "draw_stats")]
+                widget_timings.push(t.elapsed());
             }
 
+            self.positions.push(positions);
+
+            if let Some((edge, thickness, color)) = self.border {
+                let y = match edge {
+                    Edge::Top => 0.0,
+                    Edge::Bottom => content_y + content_h,
+                };
+                ctx.set_x_offset(0.0);
+                ctx.set_y_offset(0.0);
+                ctx.color(&color);
+                ctx.rectangle(0.0, y, w, thickness.min(self.h))?;
+            }
+
+            #[cfg(feature = "draw_stats")]
+            self.stats.record_widgets(&widget_timings);
+
+            #[cfg(feature = "draw_stats")]
+            let t = Instant::now();
+
             self.drw.flush(id)?;
+
+            #[cfg(feature = "draw_stats")]
+            {
+                self.stats.flush += t.elapsed();
+            }
+        }
+
+        #[cfg(feature = "draw_stats")]
+        {
+            self.stats.redraws += 1;
+            self.stats.redraw += start.elapsed();
         }
 
         Ok(())
     }
 
-    fn layout(&mut self, ctx: &mut C, w: f64) -> Result<Vec<(f64, f64)>> {
-        let mut extents = Vec::with_capacity(self.widgets.len());
-        let mut greedy_indices = vec![];
+    /// Update each widget whose [Widget::refresh_interval] has elapsed since it was last
+    /// updated and redraw any that have changed as a result.
+    ///
+    /// This should be called periodically by whatever is driving the bar, using
+    /// [StatusBar::next_refresh] to determine how long to wait between calls. Where possible only
+    /// the widgets that have changed are repainted: if their size has changed, or zone
+    /// backgrounds are in use, the whole bar is redrawn instead.
+    pub fn refresh(&mut self) -> Result<()> {
+        let now = Instant::now();
+        self.last_updated.resize(self.widgets.len(), None);
+
+        let mut dirty = vec![];
+        for (ix, wd) in self.widgets.iter_mut().enumerate() {
+            let due = wd
+                .refresh_interval()
+                .is_some_and(|i| time_until_refresh(i, self.last_updated[ix], now).is_zero());
 
-        for (i, w) in self.widgets.iter_mut().enumerate() {
-            extents.push(w.current_extent(ctx, self.h)?);
-            if w.is_greedy() {
-                greedy_indices.push(i)
+            if due {
+                wd.update();
+                self.last_updated[ix] = Some(now);
+                if wd.is_visible() && wd.require_draw() {
+                    dirty.push(ix);
+                }
             }
         }
 
-        let total = extents.iter().map(|(w, _)| w).sum::<f64>();
-        let n_greedy = greedy_indices.len();
+        if dirty.is_empty() {
+            return Ok(());
+        }
+
+        self.redraw_widgets(&dirty)
+    }
+
+    /// The time remaining until the next call to [StatusBar::refresh] will update a widget, or
+    /// `None` if none of the widgets in this bar need refreshing.
+    pub fn next_refresh(&self) -> Option<Duration> {
+        let now = Instant::now();
+
+        self.widgets
+            .iter()
+            .enumerate()
+            .filter_map(|(ix, wd)| {
+                let last = self.last_updated.get(ix).copied().flatten();
+                Some(time_until_refresh(wd.refresh_interval()?, last, now))
+            })
+            .min()
+    }
+
+    // Repaint only the widgets at the given indices over the bar background, falling back to a
+    // full redraw if doing so could leave the bar in an inconsistent state.
+    fn redraw_widgets(&mut self, ixs: &[usize]) -> Result<()> {
+        let can_redraw_in_place = self.drw.connection_alive()
+            && self.zone_backgrounds.is_empty()
+            && self.positions.len() == self.screens.len();
+
+        if !can_redraw_in_place {
+            return self.redraw_and_flush();
+        }
+
+        let (content_y, content_h) = content_area(self.border, self.h);
+        let right_start = self.right_start.unwrap_or(self.widgets.len());
 
-        if total < w && n_greedy > 0 {
-            let per_greedy = (w - total) / n_greedy as f64;
-            for i in greedy_indices.iter() {
-                let (w, h) = extents[*i];
-                extents[*i] = (w + per_greedy, h);
+        for (i, &(id, w)) in self.screens.clone().iter().enumerate() {
+            let screen_has_focus = self.active_screen == i;
+            let mut ctx = self.drw.context_for(id)?;
+            let mut dimmed_ctx;
+            let ctx: &mut dyn DrawContext = if self.dimmed && !screen_has_focus {
+                dimmed_ctx = DimmedContext {
+                    inner: &mut ctx,
+                    factor: DIM_FACTOR,
+                };
+                &mut dimmed_ctx
+            } else {
+                &mut ctx
+            };
+
+            ctx.set_y_offset(content_y);
+            let (extents, margins) = self.layout(ctx, w)?;
+            if widget_positions(&extents, &margins, right_start, w) != self.positions[i] {
+                return self.redraw_and_flush();
             }
+
+            for &ix in ixs {
+                let (x, ww) = self.positions[i][ix];
+                ctx.save()?;
+                ctx.reset();
+                ctx.set_y_offset(content_y);
+                ctx.set_x_offset(x);
+                ctx.color(&self.bg);
+                ctx.rectangle(0.0, 0.0, ww, content_h)?;
+                self.widgets[ix].draw(ctx, self.active_screen, screen_has_focus, ww, content_h)?;
+                ctx.restore()?;
+            }
+
+            ctx.flush();
+            self.drw.flush(id)?;
         }
 
-        // Allowing overflow to happen
-        Ok(extents)
+        Ok(())
     }
 
-    fn redraw_if_needed(&mut self) -> Result<()> {
-        if self.widgets.iter().any(|w| w.require_draw()) {
-            self.redraw()?;
-            for (id, _) in self.screens.iter() {
-                self.drw.flush(*id)?;
+    fn redraw_and_flush(&mut self) -> Result<()> {
+        self.redraw()?;
+        for (id, _) in self.screens.iter() {
+            self.drw.flush(*id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Find the [ClickTarget] (if any) under `p` for the bar on the given screen.
+    ///
+    /// `p` should be relative to the top left corner of the bar window and hit testing is
+    /// carried out against the positions of widgets as of the last call to [StatusBar::redraw].
+    pub fn click_target(&self, screen: usize, p: Point) -> Option<ClickTarget> {
+        let positions = self.positions.get(screen)?;
+        let ix = widget_at(positions, p.x as f64)?;
+        let local = Point::new(p.x - positions[ix].0 as u32, p.y);
+
+        self.widgets[ix]
+            .click_regions()
+            .into_iter()
+            .find(|(r, _)| r.contains_point(&local))
+            .map(|(_, target)| target)
+    }
+
+    /// Find the [ClickTarget] (if any) under the pointer for a button press on one of the bar
+    /// windows, such as the [Workspace][ClickTarget::Workspace] that was clicked on in a
+    /// [Workspaces] widget.
+    ///
+    /// Returns `None` for events other than button presses and for events on windows that are
+    /// not owned by this bar.
+    pub fn handle_click(&self, e: &MouseEvent) -> Option<ClickTarget> {
+        if e.kind != MouseEventKind::Press {
+            return None;
+        }
+
+        self.click_target(self.screen_for(e.id)?, e.wpt)
+    }
+
+    // The index of the screen that the given bar window is on
+    fn screen_for(&self, id: Xid) -> Option<usize> {
+        self.screens.iter().position(|(w, _)| *w == id)
+    }
+
+    /// Forward a [MouseEvent] received for one of the bar windows to the widget under the
+    /// pointer via [Widget::on_drag], redrawing if that widget now needs it.
+    ///
+    /// A button press over a widget starts a drag which is tracked until the button is released:
+    /// motion events in the meantime are sent to the widget that was pressed, wherever the
+    /// pointer is. Events for windows other than those owned by this bar are ignored.
+    pub fn handle_mouse_event(&mut self, e: &MouseEvent) -> Result<()> {
+        let screen = match self.screen_for(e.id) {
+            Some(screen) => screen,
+            None => return Ok(()),
+        };
+
+        let (screen, ix, button) = match (e.kind, self.drag) {
+            (MouseEventKind::Press, _) => {
+                let x = e.wpt.x as i32 as f64;
+                match self.positions.get(screen).and_then(|p| widget_at(p, x)) {
+                    Some(ix) => (screen, ix, e.state.button),
+                    None => {
+                        self.drag = None;
+                        return Ok(());
+                    }
+                }
             }
+            (MouseEventKind::Motion, Some(drag)) => drag,
+            (MouseEventKind::Release, _) => {
+                self.drag = None;
+                return Ok(());
+            }
+            _ => return Ok(()),
+        };
+
+        let (offset, w) = match self.positions.get(screen).and_then(|p| p.get(ix)) {
+            Some(&position) => position,
+            None => return Ok(()),
+        };
+
+        self.drag = Some((screen, ix, button));
+        self.widgets[ix].on_drag(drag_point(e.wpt, offset, w, self.h), button);
+        self.redraw_if_needed()
+    }
+
+    fn layout(&mut self, ctx: &mut dyn DrawContext, w: f64) -> Result<WidgetSpans> {
+        let (_, content_h) = content_area(self.border, self.h);
+        widget_extents(&mut self.widgets, ctx, content_h, w)
+    }
+
+    fn redraw_if_needed(&mut self) -> Result<()> {
+        if self
+            .widgets
+            .iter()
+            .any(|w| w.is_visible() && w.require_draw())
+        {
+            self.redraw_and_flush()?;
         }
 
         Ok(())
     }
 }
 
+// The y offset and height of the area of a bar of height 'h' that is available to widgets once
+// space has been reserved for the border
+fn content_area(border: Option<Border>, h: f64) -> (f64, f64) {
+    match border {
+        Some((edge, thickness, _)) => {
+            let thickness = thickness.clamp(0.0, h);
+            match edge {
+                Edge::Top => (thickness, h - thickness),
+                Edge::Bottom => (0.0, h - thickness),
+            }
+        }
+        None => (0.0, h),
+    }
+}
+
+// Wraps a DrawContext so that all colors set on it are dimmed by 'factor'
+struct DimmedContext<'a> {
+    inner: &'a mut dyn DrawContext,
+    factor: f64,
+}
+
+impl<'a> DrawContext for DimmedContext<'a> {
+    fn font(&mut self, font_name: &str, point_size: i32) -> Result<()> {
+        self.inner.font(font_name, point_size)
+    }
+
+    fn color(&mut self, color: &Color) {
+        self.inner.color(&color.scale_brightness(self.factor))
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.inner.clear()
+    }
+
+    fn reset(&self) {
+        self.inner.reset()
+    }
+
+    fn save(&self) -> Result<()> {
+        self.inner.save()
+    }
+
+    fn restore(&self) -> Result<()> {
+        self.inner.restore()
+    }
+
+    fn translate(&self, dx: f64, dy: f64) {
+        self.inner.translate(dx, dy)
+    }
+
+    fn set_x_offset(&self, x: f64) {
+        self.inner.set_x_offset(x)
+    }
+
+    fn set_y_offset(&self, y: f64) {
+        self.inner.set_y_offset(y)
+    }
+
+    fn device_align(&self, coord: f64) -> f64 {
+        self.inner.device_align(coord)
+    }
+
+    fn fill_dithered(&mut self, region: Region, color: Color, alpha: f64) -> Result<()> {
+        let color = color.scale_brightness(self.factor);
+        self.inner.fill_dithered(region, color, alpha)
+    }
+
+    fn set_line_width(&self, w: f64) {
+        self.inner.set_line_width(w)
+    }
+
+    fn set_dash(&self, dashes: &[f64], offset: f64) {
+        self.inner.set_dash(dashes, offset)
+    }
+
+    fn rectangle(&self, x: f64, y: f64, w: f64, h: f64) -> Result<()> {
+        self.inner.rectangle(x, y, w, h)
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start: f64, end: f64) -> Result<()> {
+        self.inner.arc(x, y, radius, start, end)
+    }
+
+    fn outline(&self, x: f64, y: f64, w: f64, h: f64) -> Result<()> {
+        self.inner.outline(x, y, w, h)
+    }
+
+    fn text(&self, s: &str, h_offset: f64, padding: (f64, f64)) -> Result<(f64, f64)> {
+        self.inner.text(s, h_offset, padding)
+    }
+
+    fn text_detailed(&self, s: &str, h_offset: f64, padding: (f64, f64)) -> Result<RenderedText> {
+        self.inner.text_detailed(s, h_offset, padding)
+    }
+
+    fn text_outlined(
+        &self,
+        s: &str,
+        h_offset: f64,
+        padding: (f64, f64),
+        outline: Color,
+        width: f64,
+        fill: Color,
+    ) -> Result<(f64, f64)> {
+        self.inner.text_outlined(
+            s,
+            h_offset,
+            padding,
+            outline.scale_brightness(self.factor),
+            width,
+            fill.scale_brightness(self.factor),
+        )
+    }
+
+    fn text_scrolled(
+        &self,
+        s: &str,
+        region_width: f64,
+        offset_px: f64,
+        padding: (f64, f64),
+    ) -> Result<(f64, f64)> {
+        self.inner
+            .text_scrolled(s, region_width, offset_px, padding)
+    }
+
+    fn text_clamped(
+        &self,
+        s: &str,
+        max_width: f64,
+        max_lines: usize,
+        padding: (f64, f64),
+    ) -> Result<RenderedText> {
+        self.inner.text_clamped(s, max_width, max_lines, padding)
+    }
+
+    fn text_wrapped(
+        &self,
+        s: &str,
+        width: f64,
+        padding: (f64, f64, f64, f64),
+    ) -> Result<(f64, f64)> {
+        self.inner.text_wrapped(s, width, padding)
+    }
+
+    fn xy_to_index(&self, s: &str, x: f64, y: f64) -> Result<usize> {
+        self.inner.xy_to_index(s, x, y)
+    }
+
+    fn text_extent(&self, s: &str) -> Result<(f64, f64)> {
+        self.inner.text_extent(s)
+    }
+
+    fn image_rgba(&self, data: &[u8], w: usize, h: usize, x: f64, y: f64) -> Result<()> {
+        self.inner.image_rgba(data, w, h, x, y)
+    }
+
+    fn fill_pattern(&self, region: Region, image: &Path) -> Result<()> {
+        self.inner.fill_pattern(region, image)
+    }
+
+    fn image(&self, image: &Path, x: f64, y: f64, w: Option<f64>, h: Option<f64>) -> Result<()> {
+        self.inner.image(image, x, y, w, h)
+    }
+
+    fn font_metrics(&self) -> Result<FontMetrics> {
+        self.inner.font_metrics()
+    }
+
+    fn font_has_char(&self, c: char) -> Result<bool> {
+        self.inner.font_has_char(c)
+    }
+
+    fn flush(&self) {
+        self.inner.flush()
+    }
+}
+
+// The extent and (left, right) margins of each widget in a bar
+type WidgetSpans = (Vec<(f64, f64)>, Vec<(f64, f64)>);
+
+// Compute the extent and (left, right) margins of each widget for a bar of width 'w' and
+// height 'h'. Hidden widgets are given a zero extent and never receive any of the remaining
+// space.
+fn widget_extents<W>(
+    widgets: &mut [Box<W>],
+    ctx: &mut dyn DrawContext,
+    h: f64,
+    w: f64,
+) -> Result<WidgetSpans>
+where
+    W: Widget + ?Sized,
+{
+    let mut extents = Vec::with_capacity(widgets.len());
+    let mut greedy = vec![];
+    let margins = widget_margins(
+        &widgets
+            .iter()
+            .map(|wd| wd.is_visible().then(|| wd.margin().max(0.0)))
+            .collect::<Vec<_>>(),
+    );
+
+    for (i, wd) in widgets.iter_mut().enumerate() {
+        if !wd.is_visible() {
+            extents.push((0.0, 0.0));
+            continue;
+        }
+
+        extents.push(wd.current_extent(ctx, h)?);
+        if wd.is_greedy() {
+            greedy.push((i, wd.greedy_weight()))
+        }
+    }
+
+    let total_margin = margins.iter().map(|(l, r)| l + r).sum::<f64>();
+    distribute_remaining_width(&mut extents, &greedy, w - total_margin);
+
+    // Allowing overflow to happen
+    Ok((extents, margins))
+}
+
+// The (x offset, width) of each widget in a bar of width 'w' given their extents and margins.
+// Widgets from 'right_start' onward are placed flush against the right edge of the bar. Widgets
+// before them grow rightward from the left edge and are truncated (or given no width at all) if
+// they would overlap the right hand widgets.
+fn widget_positions(
+    extents: &[(f64, f64)],
+    margins: &[(f64, f64)],
+    right_start: usize,
+    w: f64,
+) -> Vec<(f64, f64)> {
+    let right_start = right_start.min(extents.len());
+    let right_w: f64 = extents[right_start..]
+        .iter()
+        .zip(&margins[right_start..])
+        .map(|((ew, _), (l, r))| l + ew + r)
+        .sum();
+    let limit = if right_start < extents.len() {
+        (w - right_w).max(0.0)
+    } else {
+        f64::INFINITY
+    };
+
+    let mut x = 0.0;
+    let mut positions = Vec::with_capacity(extents.len());
+    for (i, (&(ew, _), &(l, r))) in extents.iter().zip(margins).enumerate() {
+        if i == right_start {
+            x = limit;
+        }
+
+        x += l;
+        let width = if i < right_start {
+            ew.min(limit - x).max(0.0)
+        } else {
+            ew
+        };
+        positions.push((x, width));
+        x += ew + r;
+    }
+
+    positions
+}
+
+// The zone that each widget belongs to, given whether or not each widget is visible and greedy
+fn widget_zones(greedy: &[bool]) -> Vec<Zone> {
+    let first = greedy.iter().position(|&g| g);
+    let last = greedy.iter().rposition(|&g| g);
+
+    (0..greedy.len())
+        .map(|i| match (first, last) {
+            (Some(f), _) if i < f => Zone::Left,
+            (_, Some(l)) if i > l => Zone::Right,
+            (Some(_), Some(_)) => Zone::Center,
+            _ => Zone::Left,
+        })
+        .collect()
+}
+
+// The (x offset, width) covered by each zone containing at least one widget in a bar of width
+// 'w'. Zones are contiguous so their spans never overlap.
+fn zone_spans(
+    zones: &[Zone],
+    extents: &[(f64, f64)],
+    margins: &[(f64, f64)],
+    w: f64,
+) -> HashMap<Zone, (f64, f64)> {
+    let mut bounds: HashMap<Zone, (f64, f64)> = HashMap::new();
+    let mut x = 0.0;
+
+    for ((zone, (ew, _)), (l, r)) in zones.iter().zip(extents).zip(margins) {
+        let end = x + l + ew + r;
+        bounds
+            .entry(*zone)
+            .and_modify(|(_, e)| *e = end)
+            .or_insert((x, end));
+        x = end;
+    }
+
+    if let Some((_, end)) = bounds.get_mut(&Zone::Right) {
+        *end = end.max(w);
+    }
+
+    bounds
+        .into_iter()
+        .map(|(zone, (start, end))| (zone, (start, end.min(w) - start)))
+        .filter(|(_, (_, zw))| *zw > 0.0)
+        .collect()
+}
+
+// Margins are applied either side of each visible widget (given as Some(margin)) other than at
+// the outer edges of the bar, where they collapse to zero.
+fn widget_margins(margins: &[Option<f64>]) -> Vec<(f64, f64)> {
+    let first = margins.iter().position(|m| m.is_some());
+    let last = margins.iter().rposition(|m| m.is_some());
+
+    margins
+        .iter()
+        .enumerate()
+        .map(|(i, m)| match m {
+            Some(m) => (
+                if Some(i) == first { 0.0 } else { *m },
+                if Some(i) == last { 0.0 } else { *m },
+            ),
+            None => (0.0, 0.0),
+        })
+        .collect()
+}
+
+// Split any space left over after laying out widgets at their requested extents between the
+// greedy widgets (given as (index, weight) pairs) in proportion to their weights.
+fn distribute_remaining_width(extents: &mut [(f64, f64)], greedy: &[(usize, f64)], w: f64) {
+    let total = extents.iter().map(|(w, _)| w).sum::<f64>();
+    let total_weight = greedy.iter().map(|(_, weight)| weight).sum::<f64>();
+
+    if total < w && total_weight > 0.0 {
+        let per_weight = (w - total) / total_weight;
+        for &(i, weight) in greedy.iter() {
+            let (w, h) = extents[i];
+            extents[i] = (w + per_weight * weight, h);
+        }
+    }
+}
+
+// The index of the widget (given as (x offset, width) pairs) containing the given x coordinate
+fn widget_at(positions: &[(f64, f64)], x: f64) -> Option<usize> {
+    positions
+        .iter()
+        .position(|&(offset, w)| offset <= x && x < offset + w)
+}
+
+// How long until a widget with the given refresh interval that was last updated at 'last' is
+// due to be updated again. Widgets that have never been updated are due immediately.
+fn time_until_refresh(interval: Duration, last: Option<Instant>, now: Instant) -> Duration {
+    match last {
+        Some(t) => interval.saturating_sub(now.saturating_duration_since(t)),
+        None => Duration::ZERO,
+    }
+}
+
+// Convert a window relative point into one relative to the widget at 'offset' with width 'w',
+// clamped to the bounds of the widget. Points outside of the window arrive from X as negative
+// i16 values that have been cast to u32 so the sign is recovered before clamping.
+fn drag_point(p: Point, offset: f64, w: f64, h: f64) -> Point {
+    let x = (p.x as i32 as f64 - offset).clamp(0.0, (w - 1.0).max(0.0));
+    let y = (p.y as i32 as f64).clamp(0.0, (h - 1.0).max(0.0));
+
+    Point::new(x as u32, y as u32)
+}
+
 macro_rules! __impl_status_bar_as_hook {
     {
         $($name:ident => $($a:ident: $t:ty),*;)+
@@ -288,7 +1395,7 @@ macro_rules! __impl_status_bar_as_hook {
         impl<C, D, X> Hook<X> for StatusBar<C, D, X>
         where
             C: DrawContext,
-            D: Draw<Ctx = C>,
+            D: XDraw<Ctx = C>,
             X: XConn,
         {
             $(fn $name(&mut self, wm: &mut WindowManager<X>, $($a: $t),*) -> crate::Result<()> {
@@ -337,6 +1444,7 @@ macro_rules! __impl_status_bar_as_hook {
 __impl_status_bar_as_hook! {
     client_name_updated => id: Xid, name: &str, is_root: bool;
     client_added_to_workspace => id: Xid, wix: usize;
+    client_closed => id: Xid;
     focus_change => id: Xid;
     layout_applied => workspace_index: usize, screen_index: usize;
     layout_change => workspace_index: usize, screen_index: usize;
@@ -346,3 +1454,491 @@ __impl_status_bar_as_hook! {
     workspace_change => prev: usize, new: usize;
     workspaces_updated => names: &[&str], active: usize;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct StubContext;
+
+    impl DrawContext for StubContext {
+        fn font(&mut self, _: &str, _: i32) -> Result<()> {
+            Ok(())
+        }
+        fn color(&mut self, _: &Color) {}
+        fn clear(&mut self) -> Result<()> {
+            Ok(())
+        }
+        fn reset(&self) {}
+        fn save(&self) -> Result<()> {
+            Ok(())
+        }
+        fn restore(&self) -> Result<()> {
+            Ok(())
+        }
+        fn translate(&self, _: f64, _: f64) {}
+        fn set_x_offset(&self, _: f64) {}
+        fn set_y_offset(&self, _: f64) {}
+        fn device_align(&self, coord: f64) -> f64 {
+            coord
+        }
+        fn set_line_width(&self, _: f64) {}
+        fn set_dash(&self, _: &[f64], _: f64) {}
+        fn rectangle(&self, _: f64, _: f64, _: f64, _: f64) -> Result<()> {
+            Ok(())
+        }
+        fn arc(&self, _: f64, _: f64, _: f64, _: f64, _: f64) -> Result<()> {
+            Ok(())
+        }
+        fn outline(&self, _: f64, _: f64, _: f64, _: f64) -> Result<()> {
+            Ok(())
+        }
+        fn text(&self, _: &str, _: f64, _: (f64, f64)) -> Result<(f64, f64)> {
+            Ok((0.0, 0.0))
+        }
+        fn text_outlined(
+            &self,
+            _: &str,
+            _: f64,
+            _: (f64, f64),
+            _: Color,
+            _: f64,
+            _: Color,
+        ) -> Result<(f64, f64)> {
+            Ok((0.0, 0.0))
+        }
+        fn text_detailed(&self, _: &str, _: f64, _: (f64, f64)) -> Result<RenderedText> {
+            Ok(RenderedText {
+                width: 0.0,
+                height: 0.0,
+                lines: 0,
+                truncated: false,
+            })
+        }
+        fn text_scrolled(&self, _: &str, w: f64, _: f64, _: (f64, f64)) -> Result<(f64, f64)> {
+            Ok((w, 0.0))
+        }
+        fn text_clamped(&self, _: &str, _: f64, _: usize, _: (f64, f64)) -> Result<RenderedText> {
+            Ok(RenderedText {
+                width: 0.0,
+                height: 0.0,
+                lines: 0,
+                truncated: false,
+            })
+        }
+        fn text_wrapped(&self, _: &str, w: f64, _: (f64, f64, f64, f64)) -> Result<(f64, f64)> {
+            Ok((w, 0.0))
+        }
+        fn xy_to_index(&self, _: &str, _: f64, _: f64) -> Result<usize> {
+            Ok(0)
+        }
+        fn text_extent(&self, _: &str) -> Result<(f64, f64)> {
+            Ok((0.0, 0.0))
+        }
+        fn image_rgba(&self, _: &[u8], _: usize, _: usize, _: f64, _: f64) -> Result<()> {
+            Ok(())
+        }
+        fn fill_pattern(&self, _: Region, _: &Path) -> Result<()> {
+            Ok(())
+        }
+        fn image(&self, _: &Path, _: f64, _: f64, _: Option<f64>, _: Option<f64>) -> Result<()> {
+            Ok(())
+        }
+        fn font_metrics(&self) -> Result<FontMetrics> {
+            Ok(FontMetrics {
+                ascent: 0.0,
+                descent: 0.0,
+            })
+        }
+        fn font_has_char(&self, _: char) -> Result<bool> {
+            Ok(true)
+        }
+        fn flush(&self) {}
+    }
+
+    struct FixedWidget {
+        width: f64,
+        greedy: bool,
+        visible: bool,
+        margin: f64,
+    }
+
+    impl Widget for FixedWidget {
+        fn draw(
+            &mut self,
+            _: &mut dyn DrawContext,
+            _: usize,
+            _: bool,
+            _: f64,
+            _: f64,
+        ) -> Result<()> {
+            Ok(())
+        }
+        fn current_extent(&mut self, _: &mut dyn DrawContext, h: f64) -> Result<(f64, f64)> {
+            Ok((self.width, h))
+        }
+        fn require_draw(&self) -> bool {
+            false
+        }
+        fn is_greedy(&self) -> bool {
+            self.greedy
+        }
+        fn is_visible(&self) -> bool {
+            self.visible
+        }
+        fn margin(&self) -> f64 {
+            self.margin
+        }
+    }
+
+    fn fixed(width: f64, greedy: bool, visible: bool) -> Box<FixedWidget> {
+        Box::new(FixedWidget {
+            width,
+            greedy,
+            visible,
+            margin: 0.0,
+        })
+    }
+
+    #[test]
+    fn margins_are_included_in_total_width() {
+        let mut widgets = vec![
+            fixed(10.0, false, true),
+            fixed(0.0, true, true),
+            fixed(20.0, false, false),
+            fixed(10.0, false, true),
+        ];
+        widgets.iter_mut().for_each(|w| w.margin = 2.0);
+        let (extents, margins) = widget_extents(&mut widgets, &mut StubContext, 5.0, 50.0).unwrap();
+
+        assert_eq!(
+            margins,
+            vec![(0.0, 2.0), (2.0, 2.0), (0.0, 0.0), (2.0, 0.0)]
+        );
+        assert_eq!(extents[1], (22.0, 5.0));
+
+        let total: f64 = extents.iter().map(|(w, _)| w).sum::<f64>()
+            + margins.iter().map(|(l, r)| l + r).sum::<f64>();
+        assert_eq!(total, 50.0);
+    }
+
+    test_cases! {
+        widget_positions;
+        args: (
+            extents: &[f64],
+            right_start: usize,
+            expected: &[(f64, f64)]
+        );
+
+        case: left_only => (&[10.0, 20.0], 2, &[(0.0, 10.0), (10.0, 20.0)]);
+        case: right_only => (&[10.0, 20.0], 0, &[(70.0, 10.0), (80.0, 20.0)]);
+        case: left_and_right => (
+            &[10.0, 20.0, 15.0],
+            2,
+            &[(0.0, 10.0), (10.0, 20.0), (85.0, 15.0)]
+        );
+        case: left_truncated => (
+            &[50.0, 30.0, 40.0],
+            2,
+            &[(0.0, 50.0), (50.0, 10.0), (60.0, 40.0)]
+        );
+        case: left_pushed_out => (
+            &[70.0, 20.0, 40.0],
+            2,
+            &[(0.0, 60.0), (70.0, 0.0), (60.0, 40.0)]
+        );
+
+        body: {
+            let extents: Vec<(f64, f64)> = extents.iter().map(|&w| (w, 10.0)).collect();
+            let margins = vec![(0.0, 0.0); extents.len()];
+            assert_eq!(widget_positions(&extents, &margins, right_start, 100.0), expected);
+        }
+    }
+
+    #[test]
+    fn widget_positions_respect_margins() {
+        let extents = [(10.0, 10.0), (10.0, 10.0), (10.0, 10.0)];
+        let margins = [(0.0, 2.0), (2.0, 2.0), (2.0, 0.0)];
+        let positions = widget_positions(&extents, &margins, 2, 100.0);
+
+        assert_eq!(positions, vec![(0.0, 10.0), (14.0, 10.0), (90.0, 10.0)]);
+    }
+
+    test_cases! {
+        bar_region;
+        args: (position: Position, screen: Region, expected: Region);
+
+        case: top => (Position::Top, Region::new(0, 0, 1920, 1080), Region::new(0, 0, 1920, 20));
+        case: bottom => (
+            Position::Bottom,
+            Region::new(0, 0, 1920, 1080),
+            Region::new(0, 1060, 1920, 20)
+        );
+        case: top_of_lower_screen => (
+            Position::Top,
+            Region::new(0, 1080, 1920, 1080),
+            Region::new(0, 1080, 1920, 20)
+        );
+        case: bottom_of_lower_screen => (
+            Position::Bottom,
+            Region::new(0, 1080, 1920, 1080),
+            Region::new(0, 2140, 1920, 20)
+        );
+        case: taller_than_screen => (
+            Position::Bottom,
+            Region::new(0, 0, 1920, 10),
+            Region::new(0, 0, 1920, 10)
+        );
+
+        body: {
+            assert_eq!(bar_region(position, screen, 20), expected);
+        }
+    }
+
+    test_cases! {
+        strut_partial;
+        args: (position: Position, r: Region, expected: [u32; 12]);
+
+        case: top => (
+            Position::Top,
+            Region::new(0, 0, 1920, 20),
+            [0, 0, 20, 0, 0, 0, 0, 0, 0, 1919, 0, 0]
+        );
+        case: top_second_screen => (
+            Position::Top,
+            Region::new(1920, 0, 1920, 20),
+            [0, 0, 20, 0, 0, 0, 0, 0, 1920, 3839, 0, 0]
+        );
+        case: bottom => (
+            Position::Bottom,
+            Region::new(0, 1060, 1920, 20),
+            [0, 0, 0, 20, 0, 0, 0, 0, 0, 0, 0, 1919]
+        );
+        case: bottom_of_shorter_screen => (
+            Position::Bottom,
+            Region::new(1920, 748, 1366, 20),
+            [0, 0, 0, 332, 0, 0, 0, 0, 0, 0, 1920, 3285]
+        );
+
+        body: {
+            assert_eq!(strut_partial(position, r, 1080), expected);
+        }
+    }
+
+    test_cases! {
+        widget_margins;
+        args: (margins: &[Option<f64>], expected: Vec<(f64, f64)>);
+
+        case: empty => (&[], vec![]);
+        case: single_widget_has_no_margins => (&[Some(3.0)], vec![(0.0, 0.0)]);
+        case: outer_edges_collapse => (
+            &[Some(3.0), Some(1.0), Some(3.0)],
+            vec![(0.0, 3.0), (1.0, 1.0), (3.0, 0.0)]
+        );
+        case: hidden_widgets_are_skipped => (
+            &[None, Some(3.0), None, Some(1.0), None],
+            vec![(0.0, 0.0), (0.0, 3.0), (0.0, 0.0), (1.0, 0.0), (0.0, 0.0)]
+        );
+
+        body: {
+            assert_eq!(widget_margins(margins), expected);
+        }
+    }
+
+    #[test]
+    fn hidden_widgets_take_up_no_space() {
+        let mut widgets = vec![
+            fixed(10.0, false, true),
+            fixed(20.0, false, false),
+            fixed(0.0, true, true),
+            fixed(0.0, true, false),
+        ];
+        let (extents, _) = widget_extents(&mut widgets, &mut StubContext, 5.0, 50.0).unwrap();
+
+        assert_eq!(
+            extents,
+            vec![(10.0, 5.0), (0.0, 0.0), (40.0, 5.0), (0.0, 0.0)]
+        );
+    }
+
+    #[test]
+    fn remaining_width_is_split_by_weight() {
+        let mut extents = vec![(10.0, 5.0), (0.0, 5.0), (20.0, 5.0), (0.0, 5.0)];
+        distribute_remaining_width(&mut extents, &[(1, 1.0), (3, 2.0)], 60.0);
+
+        assert_eq!(
+            extents,
+            vec![(10.0, 5.0), (10.0, 5.0), (20.0, 5.0), (20.0, 5.0)]
+        );
+    }
+
+    #[test]
+    fn equal_weights_split_evenly() {
+        let mut extents = vec![(10.0, 5.0), (0.0, 5.0), (0.0, 5.0)];
+        distribute_remaining_width(&mut extents, &[(1, 1.0), (2, 1.0)], 30.0);
+
+        assert_eq!(extents, vec![(10.0, 5.0), (10.0, 5.0), (10.0, 5.0)]);
+    }
+
+    #[test]
+    fn overflowing_widgets_are_left_untouched() {
+        let mut extents = vec![(40.0, 5.0), (0.0, 5.0)];
+        distribute_remaining_width(&mut extents, &[(1, 1.0)], 30.0);
+
+        assert_eq!(extents, vec![(40.0, 5.0), (0.0, 5.0)]);
+    }
+
+    test_cases! {
+        widget_at;
+        args: (x: f64, expected: Option<usize>);
+
+        case: first => (0.0, Some(0));
+        case: inside_second => (15.0, Some(1));
+        case: boundary_belongs_to_next => (10.0, Some(1));
+        case: past_the_end => (40.0, None);
+
+        body: {
+            let positions = [(0.0, 10.0), (10.0, 20.0), (30.0, 10.0)];
+            assert_eq!(widget_at(&positions, x), expected);
+        }
+    }
+
+    test_cases! {
+        easing;
+        args: (easing: Easing, t: f64, expected: f64);
+
+        case: linear_start => (Easing::Linear, 0.0, 0.0);
+        case: linear_mid => (Easing::Linear, 0.5, 0.5);
+        case: linear_end => (Easing::Linear, 1.0, 1.0);
+        case: ease_out_start => (Easing::EaseOut, 0.0, 0.0);
+        case: ease_out_mid => (Easing::EaseOut, 0.5, 0.75);
+        case: ease_out_end => (Easing::EaseOut, 1.0, 1.0);
+        case: clamped_below => (Easing::EaseOut, -1.0, 0.0);
+        case: clamped_above => (Easing::Linear, 2.0, 1.0);
+
+        body: {
+            assert_eq!(easing.apply(t), expected);
+        }
+    }
+
+    test_cases! {
+        animator_value_at;
+        args: (easing: Easing, elapsed_ms: u64, expected: f64);
+
+        case: linear_start => (Easing::Linear, 0, 10.0);
+        case: linear_mid => (Easing::Linear, 100, 20.0);
+        case: linear_end => (Easing::Linear, 200, 30.0);
+        case: past_the_end => (Easing::Linear, 300, 30.0);
+        case: ease_out_mid => (Easing::EaseOut, 100, 25.0);
+
+        body: {
+            let anim = Animator::new(10.0, 30.0, Duration::from_millis(200), easing);
+            assert_eq!(anim.value_at(Duration::from_millis(elapsed_ms)), expected);
+        }
+    }
+
+    #[test]
+    fn zero_duration_animations_finish_immediately() {
+        let anim = Animator::new(1.0, 2.0, Duration::ZERO, Easing::Linear);
+
+        assert_eq!(anim.value_at(Duration::ZERO), 2.0);
+        assert!(anim.is_finished(Duration::ZERO));
+    }
+
+    test_cases! {
+        time_until_refresh;
+        args: (since_last: Option<u64>, expected: u64);
+
+        case: never_updated => (None, 0);
+        case: just_updated => (Some(0), 1000);
+        case: part_way => (Some(400), 600);
+        case: due => (Some(1000), 0);
+        case: overdue => (Some(2500), 0);
+
+        body: {
+            let now = Instant::now() + Duration::from_secs(10);
+            let last = since_last.map(|ms| now - Duration::from_millis(ms));
+            let remaining = time_until_refresh(Duration::from_secs(1), last, now);
+            assert_eq!(remaining, Duration::from_millis(expected));
+        }
+    }
+
+    test_cases! {
+        drag_point;
+        args: (x: i16, y: i16, expected: Point);
+
+        case: inside => (15, 5, Point::new(5, 5));
+        case: left_of_widget => (3, 5, Point::new(0, 5));
+        case: right_of_widget => (40, 5, Point::new(19, 5));
+        case: outside_window => (-30, -2, Point::new(0, 0));
+        case: below_bar => (15, 25, Point::new(5, 19));
+
+        body: {
+            let p = Point::new(x as u32, y as u32);
+            assert_eq!(drag_point(p, 10.0, 20.0, 20.0), expected);
+        }
+    }
+
+    test_cases! {
+        widget_zones;
+        args: (greedy: &[bool], expected: &[Zone]);
+
+        case: no_greedy => (&[false, false], &[Zone::Left, Zone::Left]);
+        case: single_greedy => (
+            &[false, true, false],
+            &[Zone::Left, Zone::Center, Zone::Right]
+        );
+        case: between_greedy => (
+            &[true, false, true, false],
+            &[Zone::Center, Zone::Center, Zone::Center, Zone::Right]
+        );
+
+        body: {
+            assert_eq!(widget_zones(greedy), expected);
+        }
+    }
+
+    #[test]
+    fn zone_spans_tile_the_bar() {
+        let zones = [Zone::Left, Zone::Left, Zone::Center, Zone::Right];
+        let extents = [(10.0, 5.0), (20.0, 5.0), (40.0, 5.0), (15.0, 5.0)];
+        let margins = [(0.0, 2.0), (2.0, 2.0), (2.0, 2.0), (2.0, 0.0)];
+        let spans = zone_spans(&zones, &extents, &margins, 100.0);
+
+        assert_eq!(spans[&Zone::Left], (0.0, 36.0));
+        assert_eq!(spans[&Zone::Center], (36.0, 44.0));
+        assert_eq!(spans[&Zone::Right], (80.0, 20.0));
+    }
+
+    #[test]
+    fn zone_spans_are_clipped_to_the_bar() {
+        let zones = [Zone::Left, Zone::Right];
+        let extents = [(80.0, 5.0), (40.0, 5.0)];
+        let spans = zone_spans(&zones, &extents, &[(0.0, 0.0), (0.0, 0.0)], 100.0);
+
+        assert_eq!(spans[&Zone::Left], (0.0, 80.0));
+        assert_eq!(spans[&Zone::Right], (80.0, 20.0));
+    }
+
+    test_cases! {
+        content_area;
+        args: (border: Option<Border>, expected: (f64, f64));
+
+        case: no_border => (None, (0.0, 20.0));
+        case: top => (Some((Edge::Top, 2.0, Color::from(0xff0000))), (2.0, 18.0));
+        case: bottom => (Some((Edge::Bottom, 2.0, Color::from(0xff0000))), (0.0, 18.0));
+        case: thicker_than_bar => (Some((Edge::Top, 30.0, Color::from(0xff0000))), (20.0, 0.0));
+
+        body: {
+            assert_eq!(content_area(border, 20.0), expected);
+        }
+    }
+
+    #[test]
+    fn zero_total_weight_is_ignored() {
+        let mut extents = vec![(10.0, 5.0), (0.0, 5.0)];
+        distribute_remaining_width(&mut extents, &[(1, 0.0)], 30.0);
+
+        assert_eq!(extents, vec![(10.0, 5.0), (0.0, 5.0)]);
+    }
+}
@@ -0,0 +1,1266 @@
+/*!
+ * A backend neutral [DrawContext] implementation using pango and cairo.
+ *
+ * [CairoDrawContext] renders to any cairo surface so it is shared by the XCB and Wayland
+ * [Draw][crate::draw::Draw] implementations: each backend is only responsible for creating the
+ * surfaces that it draws to and presenting them on screen.
+ */
+use crate::{
+    core::data_types::Region,
+    draw::{dither_mask, Color, DrawContext, DrawError, FontMetrics, RenderedText, Result},
+};
+
+use pango::prelude::{FontExt, FontMapExt};
+use pangocairo::functions::{create_context, create_layout, layout_path, show_layout};
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    fs::File,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+// Outside of strict mode, failures are logged and replaced with None
+fn lenient<T>(strict: bool, res: Result<T>) -> Result<Option<T>> {
+    match res {
+        Ok(t) => Ok(Some(t)),
+        Err(e) if strict => Err(e),
+        Err(e) => {
+            warn!("ignoring draw error in non-strict mode: {}", e);
+            Ok(None)
+        }
+    }
+}
+
+fn pango_layout(ctx: &cairo::Context) -> Result<pango::Layout> {
+    // Nothing can be rendered using a context that is in an error state
+    ctx.status()?;
+    create_layout(ctx).ok_or_else(|| DrawError::Pango("unable to create layout".into()))
+}
+
+// Images loaded for use with DrawContext::fill_pattern, shared between all contexts
+pub(crate) type PatternCache = Rc<RefCell<HashMap<PathBuf, cairo::ImageSurface>>>;
+
+/// Compute the average color of the pixels within `region` of an ARGB32 or RGB24 surface.
+///
+/// The region is clipped to the bounds of the surface. Cairo stores color values premultiplied
+/// by alpha, so pixels are weighted by their alpha value when averaging: a fully transparent
+/// pixel does not contribute to the resulting color, only to its alpha. This can be used along
+/// with [XcbDraw::capture_root][crate::xcb::XcbDraw::capture_root] to pick a color that will be
+/// readable over the wallpaper.
+pub fn average_color(surface: &cairo::ImageSurface, region: Region) -> Result<Color> {
+    let (x, y, w, h) = region.values();
+    let (sw, sh) = (surface.width() as u32, surface.height() as u32);
+    let (x0, y0) = (x.min(sw) as usize, y.min(sh) as usize);
+    let (x1, y1) = ((x + w).min(sw) as usize, (y + h).min(sh) as usize);
+
+    if x0 >= x1 || y0 >= y1 {
+        return Err(DrawError::InvalidImageData(format!(
+            "{:?} does not overlap a {}x{} surface",
+            region, sw, sh
+        )));
+    }
+
+    let has_alpha = match surface.format() {
+        cairo::Format::ARgb32 => true,
+        cairo::Format::Rgb24 => false,
+        f => {
+            return Err(DrawError::InvalidImageData(format!(
+                "unable to average pixels in format {:?}",
+                f
+            )))
+        }
+    };
+
+    let stride = surface.stride() as usize;
+    let mut sums = [0.0; 4]; // a, r, g, b
+    surface.with_data(|data| {
+        for row in data.chunks(stride).skip(y0).take(y1 - y0) {
+            for p in row[x0 * 4..x1 * 4].chunks_exact(4) {
+                let pixel = u32::from_ne_bytes([p[0], p[1], p[2], p[3]]);
+                let a = if has_alpha { pixel >> 24 } else { 0xFF };
+                for (i, v) in [a, pixel >> 16, pixel >> 8, pixel].iter().enumerate() {
+                    sums[i] += (v & 0xFF) as f64;
+                }
+            }
+        }
+    })?;
+
+    let n = ((x1 - x0) * (y1 - y0)) as f64;
+    let [a, r, g, b] = sums;
+    if a == 0.0 {
+        return Ok(Color::from((0.0, 0.0, 0.0, 0.0)));
+    }
+
+    Ok(Color::from((r / a, g / a, b / a, a / (n * 255.0))))
+}
+
+// Generic family names that fontconfig will always resolve to some installed font
+const GENERIC_FONT_FAMILIES: &[&str] = &[
+    "cursive",
+    "emoji",
+    "fantasy",
+    "math",
+    "mono",
+    "monospace",
+    "sans",
+    "sans-serif",
+    "serif",
+    "system-ui",
+];
+
+// Fontconfig will always fall back to _some_ font so we need to check that the family we got
+// back is one of those that were requested (font descriptions may list several families).
+fn font_family_resolved(requested: &str, resolved: &str) -> bool {
+    if resolved.is_empty() {
+        return false;
+    }
+
+    requested.split(',').map(|f| f.trim()).any(|f| {
+        f.eq_ignore_ascii_case(resolved)
+            || GENERIC_FONT_FAMILIES
+                .iter()
+                .any(|g| f.eq_ignore_ascii_case(g))
+    })
+}
+
+// Parse 'font_name' as a pango font description, failing if it does not match an installed font
+pub(crate) fn load_font(font_name: &str) -> Result<pango::FontDescription> {
+    let desc = pango::FontDescription::from_string(font_name);
+    let requested = desc.family().map(|f| f.to_string()).unwrap_or_default();
+    let resolved = pangocairo::FontMap::default()
+        .and_then(|map| map.create_context())
+        .and_then(|ctx| ctx.load_font(&desc))
+        .and_then(|font| font.describe())
+        .and_then(|d| d.family())
+        .map(|f| f.to_string())
+        .unwrap_or_default();
+
+    if !font_family_resolved(&requested, &resolved) {
+        return Err(DrawError::FontNotFound(font_name.into()));
+    }
+
+    Ok(desc)
+}
+
+// The (x, y) scale factors needed to draw an image of size 'native' at the requested size,
+// preserving the aspect ratio of the image if only one dimension is given
+fn image_scale(native: (f64, f64), w: Option<f64>, h: Option<f64>) -> (f64, f64) {
+    let (nw, nh) = native;
+    if nw <= 0.0 || nh <= 0.0 {
+        return (1.0, 1.0);
+    }
+
+    match (w, h) {
+        (Some(w), Some(h)) => (w / nw, h / nh),
+        (Some(w), None) => (w / nw, w / nw),
+        (None, Some(h)) => (h / nh, h / nh),
+        (None, None) => (1.0, 1.0),
+    }
+}
+
+// Pango reports positions as the byte index of a char along with how many chars past it the
+// position is (0 for the leading edge): convert that to a byte offset clamped to 's'
+fn cursor_byte_offset(s: &str, index: i32, trailing: i32) -> usize {
+    let index = (index.max(0) as usize).min(s.len());
+    let advance: usize = s[index..]
+        .chars()
+        .take(trailing.max(0) as usize)
+        .map(char::len_utf8)
+        .sum();
+
+    index + advance
+}
+
+// A pango family list trying each of 'fallbacks' in turn after 'family'
+fn family_with_fallbacks(family: &str, fallbacks: &[&str]) -> String {
+    let mut families: Vec<&str> = vec![];
+    for f in family.split(',').chain(fallbacks.iter().copied()) {
+        let f = f.trim();
+        if !f.is_empty() && !families.contains(&f) {
+            families.push(f);
+        }
+    }
+
+    families.join(",")
+}
+
+/// A [DrawContext] that renders to a cairo surface using pango for text layout
+#[derive(Clone, Debug)]
+pub struct CairoDrawContext {
+    ctx: cairo::Context,
+    font: Option<pango::FontDescription>,
+    font_key: Option<(String, i32)>,
+    fonts: HashMap<String, pango::FontDescription>,
+    // font metrics are requested frequently for the same font so we cache them per size
+    metrics: RefCell<HashMap<(String, i32), FontMetrics>>,
+    patterns: PatternCache,
+    strict: bool,
+    // the number of states saved on the cairo context that have not yet been restored
+    saved: Cell<usize>,
+    fallback_fonts: Vec<String>,
+    global_fallback_fonts: Vec<String>,
+    // device pixels per logical pixel
+    scale: f64,
+}
+
+impl CairoDrawContext {
+    // A new strict context drawing with 'ctx' at a scale of 1.0 with no font set
+    pub(crate) fn new(
+        ctx: cairo::Context,
+        fonts: HashMap<String, pango::FontDescription>,
+        patterns: PatternCache,
+        global_fallback_fonts: Vec<String>,
+    ) -> Self {
+        Self {
+            ctx,
+            font: None,
+            font_key: None,
+            fonts,
+            metrics: RefCell::new(HashMap::new()),
+            patterns,
+            strict: true,
+            saved: Cell::new(0),
+            fallback_fonts: vec![],
+            global_fallback_fonts,
+            scale: 1.0,
+        }
+    }
+
+    /// Set whether failing to create a pango layout when rendering text is an error.
+    ///
+    /// Contexts are strict by default. When not in strict mode, text that can not be laid out
+    /// is logged and skipped (taking up no space) rather than failing the whole render.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Render using `factor` device pixels per logical pixel, such as the value returned by
+    /// [XcbDraw::scale_factor][crate::xcb::XcbDraw::scale_factor] for the screen being drawn to.
+    /// Non-positive factors are ignored.
+    ///
+    /// All coordinates passed to this context and all extents returned by it (including those
+    /// for text) are in logical pixels, so widget layout can be carried out without regard for
+    /// the scale factor while text and shapes are still rendered at the full device resolution.
+    /// Setting the scale resets any translation of the context.
+    pub fn set_scale(&mut self, factor: f64) {
+        if factor > 0.0 {
+            self.scale = factor;
+            self.ctx.set_matrix(self.base_matrix());
+        }
+    }
+
+    /// The number of device pixels per logical pixel for this context: see
+    /// [CairoDrawContext::set_scale].
+    pub fn scale(&self) -> f64 {
+        self.scale
+    }
+
+    /// Set font families to fall back to for this context when the current font is missing a
+    /// glyph, tried in the order given. These take precedence over any global fallbacks set using
+    /// the [Draw][crate::draw::Draw] implementation that created this context, such as
+    /// [XcbDraw::set_global_fallback_fonts][crate::xcb::XcbDraw::set_global_fallback_fonts].
+    pub fn set_fallback_fonts(&mut self, families: &[&str]) {
+        self.fallback_fonts = families.iter().map(|f| f.to_string()).collect();
+    }
+
+    fn pattern_image(&self, path: &Path) -> Result<cairo::ImageSurface> {
+        if let Some(surface) = self.patterns.borrow().get(path) {
+            return Ok(surface.clone());
+        }
+
+        let invalid = |e: &dyn std::fmt::Display| {
+            DrawError::InvalidImageData(format!("unable to load {}: {}", path.display(), e))
+        };
+        let mut f = File::open(path).map_err(|e| invalid(&e))?;
+        let surface = cairo::ImageSurface::create_from_png(&mut f).map_err(|e| invalid(&e))?;
+        self.patterns
+            .borrow_mut()
+            .insert(path.to_path_buf(), surface.clone());
+
+        Ok(surface)
+    }
+
+    // A new pango layout using the current font, or None if we are not in strict mode and one
+    // could not be created
+    fn layout(&self) -> Result<Option<pango::Layout>> {
+        let layout = lenient(self.strict, pango_layout(&self.ctx))?;
+        if let (Some(layout), Some(font)) = (&layout, &self.font) {
+            let fallbacks: Vec<&str> = self
+                .fallback_fonts
+                .iter()
+                .chain(self.global_fallback_fonts.iter())
+                .map(|f| f.as_str())
+                .collect();
+
+            if fallbacks.is_empty() {
+                layout.set_font_description(Some(font));
+            } else {
+                let mut font = font.clone();
+                let family = font.family().map(|f| f.to_string()).unwrap_or_default();
+                font.set_family(&family_with_fallbacks(&family, &fallbacks));
+                layout.set_font_description(Some(&font));
+            }
+        }
+
+        Ok(layout)
+    }
+
+    // The size of 'layout' in logical pixels. When rendering at a fractional scale the integer
+    // pixel size reported by pango is rounded in logical space, so we use the exact size instead
+    // to avoid misaligning content once it is scaled to the device.
+    fn layout_size(&self, layout: &pango::Layout) -> (f64, f64) {
+        if self.scale == 1.0 {
+            let (w, h) = layout.pixel_size();
+            return (w as f64, h as f64);
+        }
+
+        let (w, h) = layout.size();
+        let scale = pango::SCALE as f64;
+
+        (w as f64 / scale, h as f64 / scale)
+    }
+
+    // The transform for this context with no translation applied
+    fn base_matrix(&self) -> cairo::Matrix {
+        cairo::Matrix::new(self.scale, 0.0, 0.0, self.scale, 0.0, 0.0)
+    }
+
+    // A layout for 'txt' using the current font: shared between measuring and rendering text so
+    // that the two always agree on its size
+    fn text_layout(&self, txt: &str) -> Result<Option<pango::Layout>> {
+        let layout = self.layout()?;
+        if let Some(layout) = &layout {
+            layout.set_text(txt);
+            layout.set_ellipsize(pango::EllipsizeMode::End);
+        }
+
+        Ok(layout)
+    }
+
+    /// The cairo surface that this context is drawing to.
+    ///
+    /// This can be passed to [paint_surface][CairoDrawContext::paint_surface] on another context
+    /// in order to composite content that has been rendered off-screen.
+    pub fn target(&self) -> cairo::Surface {
+        self.ctx.target()
+    }
+
+    /// Paint the contents of `surface` onto this context with its origin at (x, y).
+    ///
+    /// The current clip and operator for this context are respected. The source surface must
+    /// remain alive for the duration of the call.
+    pub fn paint_surface(&self, surface: &cairo::Surface, x: f64, y: f64) -> Result<()> {
+        self.ctx.save()?;
+        self.ctx.set_source_surface(surface, x, y)?;
+        self.ctx.paint()?;
+        self.ctx.restore()?;
+
+        Ok(())
+    }
+}
+
+impl DrawContext for CairoDrawContext {
+    fn font(&mut self, font_name: &str, point_size: i32) -> Result<()> {
+        let mut font = self
+            .fonts
+            .get_mut(font_name)
+            .ok_or_else(|| DrawError::UnknownFont(font_name.into()))?
+            .clone();
+        font.set_size(point_size * pango::SCALE);
+        self.font = Some(font);
+        self.font_key = Some((font_name.to_string(), point_size));
+
+        Ok(())
+    }
+
+    fn color(&mut self, color: &Color) {
+        let (r, g, b, a) = color.rgba();
+        self.ctx.set_source_rgba(r, g, b, a);
+    }
+
+    fn clear(&mut self) -> Result<()> {
+        self.ctx.save()?;
+        self.ctx.set_operator(cairo::Operator::Clear);
+        self.ctx.paint()?;
+        self.ctx.restore()?;
+
+        Ok(())
+    }
+
+    fn reset(&self) {
+        self.ctx.set_matrix(self.base_matrix());
+        self.ctx.reset_clip();
+        self.ctx.set_operator(cairo::Operator::Over);
+        self.ctx.set_dash(&[], 0.0);
+    }
+
+    fn save(&self) -> Result<()> {
+        self.ctx.save()?;
+        self.saved.set(self.saved.get() + 1);
+
+        Ok(())
+    }
+
+    fn restore(&self) -> Result<()> {
+        // Restoring without a matching save puts the cairo context into an error state
+        if self.saved.get() == 0 {
+            return Ok(());
+        }
+
+        self.ctx.restore()?;
+        self.saved.set(self.saved.get() - 1);
+
+        Ok(())
+    }
+
+    fn translate(&self, dx: f64, dy: f64) {
+        self.ctx.translate(dx, dy)
+    }
+
+    fn set_x_offset(&self, x: f64) {
+        let (_, y_offset) = self.ctx.matrix().transform_point(0.0, 0.0);
+        self.ctx.set_matrix(self.base_matrix());
+        self.ctx.translate(x, y_offset / self.scale);
+    }
+
+    fn set_y_offset(&self, y: f64) {
+        let (x_offset, _) = self.ctx.matrix().transform_point(0.0, 0.0);
+        self.ctx.set_matrix(self.base_matrix());
+        self.ctx.translate(x_offset / self.scale, y);
+    }
+
+    fn device_align(&self, coord: f64) -> f64 {
+        let (dx, dy) = self.ctx.user_to_device(coord, 0.0);
+        match self.ctx.device_to_user(dx.round(), dy) {
+            Ok((x, _)) => x,
+            Err(_) => coord,
+        }
+    }
+
+    fn fill_dithered(&mut self, region: Region, color: Color, alpha: f64) -> Result<()> {
+        let mask = dither_mask(alpha);
+        let (r, g, b) = color.rgb();
+        let pixel = u32::from_be_bytes([
+            0xFF,
+            (r * 255.0) as u8,
+            (g * 255.0) as u8,
+            (b * 255.0) as u8,
+        ]);
+
+        let mut tile = cairo::ImageSurface::create(cairo::Format::ARgb32, 4, 4)?;
+        let stride = tile.stride() as usize;
+        {
+            let mut data = tile.data()?;
+            for (y, row) in mask.iter().enumerate() {
+                for (x, filled) in row.iter().enumerate() {
+                    if *filled {
+                        let i = y * stride + x * 4;
+                        data[i..i + 4].copy_from_slice(&pixel.to_ne_bytes());
+                    }
+                }
+            }
+        }
+
+        let pattern = cairo::SurfacePattern::create(&tile);
+        pattern.set_extend(cairo::Extend::Repeat);
+        pattern.set_filter(cairo::Filter::Nearest);
+
+        let (x, y, w, h) = region.values();
+        self.ctx.save()?;
+        self.ctx.set_source(&pattern)?;
+        self.ctx.rectangle(x as f64, y as f64, w as f64, h as f64);
+        self.ctx.fill()?;
+        self.ctx.restore()?;
+
+        Ok(())
+    }
+
+    fn fill_pattern(&self, region: Region, image: &Path) -> Result<()> {
+        let surface = self.pattern_image(image)?;
+        let (x, y, w, h) = region.values();
+        let (x, y) = (x as f64, y as f64);
+
+        let pattern = cairo::SurfacePattern::create(&surface);
+        pattern.set_extend(cairo::Extend::Repeat);
+        // Align the tiles with the region rather than the origin of the context
+        pattern.set_matrix(cairo::Matrix::new(1.0, 0.0, 0.0, 1.0, -x, -y));
+
+        self.ctx.save()?;
+        self.ctx.set_source(&pattern)?;
+        self.ctx.rectangle(x, y, w as f64, h as f64);
+        self.ctx.fill()?;
+        self.ctx.restore()?;
+
+        Ok(())
+    }
+
+    fn image(&self, image: &Path, x: f64, y: f64, w: Option<f64>, h: Option<f64>) -> Result<()> {
+        let surface = self.pattern_image(image)?;
+        let native = (surface.width() as f64, surface.height() as f64);
+        let (sx, sy) = image_scale(native, w, h);
+
+        self.ctx.save()?;
+        self.ctx.translate(x, y);
+        self.ctx.scale(sx, sy);
+        self.ctx.set_source_surface(&surface, 0.0, 0.0)?;
+        self.ctx.paint()?;
+        self.ctx.restore()?;
+
+        Ok(())
+    }
+
+    fn set_line_width(&self, w: f64) {
+        self.ctx.set_line_width(w);
+    }
+
+    fn set_dash(&self, dashes: &[f64], offset: f64) {
+        self.ctx.set_dash(dashes, offset);
+    }
+
+    fn rectangle(&self, x: f64, y: f64, w: f64, h: f64) -> Result<()> {
+        self.ctx.rectangle(x, y, w, h);
+        self.ctx.fill()?;
+
+        Ok(())
+    }
+
+    fn arc(&self, x: f64, y: f64, radius: f64, start: f64, end: f64) -> Result<()> {
+        // Start from the centre so that partial arcs are filled as a sector
+        self.ctx.new_path();
+        self.ctx.move_to(x, y);
+        self.ctx.arc(x, y, radius, start, end);
+        self.ctx.close_path();
+        self.ctx.fill()?;
+
+        Ok(())
+    }
+
+    fn outline(&self, x: f64, y: f64, w: f64, h: f64) -> Result<()> {
+        self.ctx.rectangle(x, y, w, h);
+        self.ctx.stroke()?;
+
+        Ok(())
+    }
+
+    fn text(&self, txt: &str, h_offset: f64, padding: (f64, f64)) -> Result<(f64, f64)> {
+        let rendered = self.text_detailed(txt, h_offset, padding)?;
+
+        Ok((rendered.width, rendered.height))
+    }
+
+    fn text_detailed(&self, txt: &str, h_offset: f64, padding: (f64, f64)) -> Result<RenderedText> {
+        let (l, r) = padding;
+        let layout = match self.text_layout(txt)? {
+            Some(layout) => layout,
+            None => {
+                return Ok(RenderedText {
+                    width: l + r,
+                    height: 0.0,
+                    lines: 0,
+                    truncated: false,
+                })
+            }
+        };
+
+        let (w, h) = self.layout_size(&layout);
+        self.ctx.translate(l, h_offset);
+        show_layout(&self.ctx, &layout);
+        self.ctx.translate(-l, -h_offset);
+
+        Ok(RenderedText {
+            width: w + l + r,
+            height: h,
+            lines: layout.line_count().max(0) as usize,
+            truncated: layout.is_ellipsized(),
+        })
+    }
+
+    fn text_outlined(
+        &self,
+        txt: &str,
+        h_offset: f64,
+        padding: (f64, f64),
+        outline: Color,
+        width: f64,
+        fill: Color,
+    ) -> Result<(f64, f64)> {
+        let (l, r) = padding;
+        let layout = match self.text_layout(txt)? {
+            Some(layout) => layout,
+            None => return Ok((l + r, 0.0)),
+        };
+
+        let (w, h) = self.layout_size(&layout);
+        self.ctx.save()?;
+        self.ctx.translate(l, h_offset);
+        layout_path(&self.ctx, &layout);
+
+        // The fill covers the inner half of the stroke so it needs to be twice the outline width
+        let (or, og, ob, oa) = outline.rgba();
+        self.ctx.set_source_rgba(or, og, ob, oa);
+        self.ctx.set_line_width(2.0 * width.max(0.0));
+        self.ctx.set_line_join(cairo::LineJoin::Round);
+        self.ctx.stroke_preserve()?;
+
+        let (fr, fg, fb, fa) = fill.rgba();
+        self.ctx.set_source_rgba(fr, fg, fb, fa);
+        self.ctx.fill()?;
+        self.ctx.restore()?;
+
+        Ok((w + l + r, h))
+    }
+
+    fn text_scrolled(
+        &self,
+        txt: &str,
+        region_width: f64,
+        offset_px: f64,
+        padding: (f64, f64),
+    ) -> Result<(f64, f64)> {
+        let layout = match self.text_layout(txt)? {
+            Some(layout) => layout,
+            None => return Ok((region_width, 0.0)),
+        };
+
+        let (w, h) = self.layout_size(&layout);
+        let (l, r) = padding;
+        let visible = (region_width - l - r).max(0.0);
+
+        self.ctx.save()?;
+        self.ctx.rectangle(l, 0.0, visible, h);
+        self.ctx.clip();
+
+        if w <= visible || w <= 0.0 {
+            self.ctx.translate(l, 0.0);
+            show_layout(&self.ctx, &layout);
+        } else {
+            // Draw the layout twice so that the start of the text follows on from the end
+            let offset = offset_px.rem_euclid(w);
+            self.ctx.translate(l - offset, 0.0);
+            show_layout(&self.ctx, &layout);
+            self.ctx.translate(w, 0.0);
+            show_layout(&self.ctx, &layout);
+        }
+
+        self.ctx.restore()?;
+
+        Ok((region_width, h))
+    }
+
+    fn text_clamped(
+        &self,
+        txt: &str,
+        max_width: f64,
+        max_lines: usize,
+        padding: (f64, f64),
+    ) -> Result<RenderedText> {
+        let (l, r) = padding;
+        let layout = match self.layout()? {
+            Some(layout) => layout,
+            None => {
+                return Ok(RenderedText {
+                    width: l + r,
+                    height: 0.0,
+                    lines: 0,
+                    truncated: false,
+                })
+            }
+        };
+
+        // A negative height is interpreted by pango as the maximum number of lines to show
+        let max_lines = max_lines.clamp(1, i32::MAX as usize) as i32;
+        layout.set_width(((max_width - l - r).max(0.0) * pango::SCALE as f64) as i32);
+        layout.set_height(-max_lines);
+        layout.set_wrap(pango::WrapMode::WordChar);
+        layout.set_ellipsize(pango::EllipsizeMode::End);
+        layout.set_text(txt);
+
+        let (w, h) = self.layout_size(&layout);
+        self.ctx.translate(l, 0.0);
+        show_layout(&self.ctx, &layout);
+        self.ctx.translate(-l, 0.0);
+
+        Ok(RenderedText {
+            width: w + l + r,
+            height: h,
+            lines: layout.line_count().max(0) as usize,
+            truncated: layout.is_ellipsized(),
+        })
+    }
+
+    fn text_wrapped(
+        &self,
+        txt: &str,
+        width: f64,
+        padding: (f64, f64, f64, f64),
+    ) -> Result<(f64, f64)> {
+        let (l, r, t, b) = padding;
+        let layout = match self.layout()? {
+            Some(layout) => layout,
+            None => return Ok((l + r, t + b)),
+        };
+
+        layout.set_width(((width - l - r).max(0.0) * pango::SCALE as f64) as i32);
+        layout.set_wrap(pango::WrapMode::Word);
+        layout.set_ellipsize(pango::EllipsizeMode::None);
+        layout.set_text(txt);
+
+        let (w, h) = self.layout_size(&layout);
+        self.ctx.translate(l, t);
+        show_layout(&self.ctx, &layout);
+        self.ctx.translate(-l, -t);
+
+        Ok((w + l + r, h + t + b))
+    }
+
+    fn xy_to_index(&self, s: &str, x: f64, y: f64) -> Result<usize> {
+        let layout = match self.text_layout(s)? {
+            Some(layout) => layout,
+            None => return Ok(0),
+        };
+
+        let scale = pango::SCALE as f64;
+        let (_, index, trailing) = layout.xy_to_index((x * scale) as i32, (y * scale) as i32);
+
+        Ok(cursor_byte_offset(s, index, trailing))
+    }
+
+    fn text_extent(&self, s: &str) -> Result<(f64, f64)> {
+        let layout = match self.text_layout(s)? {
+            Some(layout) => layout,
+            None => return Ok((0.0, 0.0)),
+        };
+        let (w, h) = self.layout_size(&layout);
+
+        Ok((w, h))
+    }
+
+    fn image_rgba(&self, data: &[u8], width: usize, height: usize, x: f64, y: f64) -> Result<()> {
+        if data.len() != width * height * 4 {
+            return Err(DrawError::InvalidImageData(format!(
+                "expected {} bytes for a {}x{} image but got {}",
+                width * height * 4,
+                width,
+                height,
+                data.len()
+            )));
+        }
+
+        if width == 0 || height == 0 {
+            return Ok(());
+        }
+
+        let format = cairo::Format::ARgb32;
+        let stride = format.stride_for_width(width as u32)? as usize;
+
+        // cairo expects each pixel as a native endian u32 in ARGB order and rows may be padded
+        let mut buf = vec![0; stride * height];
+        for (src, dst) in data
+            .chunks_exact(width * 4)
+            .zip(buf.chunks_exact_mut(stride))
+        {
+            for (p, out) in src.chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+                let argb = u32::from_be_bytes([p[3], p[0], p[1], p[2]]);
+                out.copy_from_slice(&argb.to_ne_bytes());
+            }
+        }
+
+        let surface = cairo::ImageSurface::create_for_data(
+            buf,
+            format,
+            width as i32,
+            height as i32,
+            stride as i32,
+        )?;
+
+        self.ctx.save()?;
+        self.ctx.set_source_surface(&surface, x, y)?;
+        self.ctx.paint()?;
+        self.ctx.restore()?;
+
+        Ok(())
+    }
+
+    fn font_metrics(&self) -> Result<FontMetrics> {
+        if let Some(metrics) = self
+            .font_key
+            .as_ref()
+            .and_then(|k| self.metrics.borrow().get(k).copied())
+        {
+            return Ok(metrics);
+        }
+
+        let pango_ctx = create_context(&self.ctx)
+            .ok_or_else(|| DrawError::Pango("unable to create context".into()))?;
+        let m = pango_ctx
+            .metrics(self.font.as_ref(), None)
+            .ok_or_else(|| DrawError::Pango("unable to fetch font metrics".into()))?;
+
+        let scale = pango::SCALE as f64;
+        let metrics = FontMetrics {
+            ascent: m.ascent() as f64 / scale,
+            descent: m.descent() as f64 / scale,
+        };
+
+        if let Some(key) = self.font_key.clone() {
+            self.metrics.borrow_mut().insert(key, metrics);
+        }
+
+        Ok(metrics)
+    }
+
+    fn font_has_char(&self, c: char) -> Result<bool> {
+        let desc = self.font.as_ref().ok_or(DrawError::NoFontSet)?;
+        let pango_ctx = create_context(&self.ctx)
+            .ok_or_else(|| DrawError::Pango("unable to create context".into()))?;
+        let font = pango_ctx
+            .load_font(desc)
+            .ok_or_else(|| DrawError::Pango("unable to load font".into()))?;
+        let coverage = font
+            .coverage(&pango::Language::default())
+            .ok_or_else(|| DrawError::Pango("unable to fetch font coverage".into()))?;
+
+        Ok(coverage.get(c as i32) == pango::CoverageLevel::Exact)
+    }
+
+    fn flush(&self) {
+        self.ctx.target().flush();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_surface(color: (f64, f64, f64, f64), w: i32, h: i32) -> cairo::ImageSurface {
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, w, h).unwrap();
+        let ctx = cairo::Context::new(&surface).unwrap();
+        let (r, g, b, a) = color;
+        ctx.set_source_rgba(r, g, b, a);
+        ctx.set_operator(cairo::Operator::Source);
+        ctx.paint().unwrap();
+        drop(ctx);
+        surface.flush();
+
+        surface
+    }
+
+    test_cases! {
+        average_color;
+        args: (color: (f64, f64, f64, f64), region: Region);
+
+        case: opaque => ((1.0, 0.0, 0.0, 1.0), Region::new(0, 0, 10, 10));
+        case: translucent => ((0.0, 1.0, 0.0, 0.6), Region::new(2, 3, 4, 5));
+        case: clipped_to_surface => ((0.0, 0.0, 1.0, 1.0), Region::new(5, 5, 100, 100));
+
+        body: {
+            let surface = solid_surface(color, 10, 10);
+            let (r, g, b, a) = average_color(&surface, region).unwrap().rgba();
+            let (er, eg, eb, ea) = color;
+
+            for (actual, expected) in [(r, er), (g, eg), (b, eb), (a, ea)] {
+                assert!((actual - expected).abs() < 0.01, "{} != {}", actual, expected);
+            }
+        }
+    }
+
+    fn image_context(w: i32, h: i32) -> CairoDrawContext {
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, w, h).unwrap();
+
+        context_on(&surface)
+    }
+
+    fn context_on(surface: &cairo::ImageSurface) -> CairoDrawContext {
+        CairoDrawContext::new(
+            cairo::Context::new(surface).unwrap(),
+            HashMap::new(),
+            Rc::new(RefCell::new(HashMap::new())),
+            vec![],
+        )
+    }
+
+    // The color of the pixel at (x, y) after drawing to a 20x20 context using 'draw'
+    fn pixel_after(
+        x: i32,
+        y: i32,
+        draw: impl FnOnce(&mut CairoDrawContext),
+    ) -> (f64, f64, f64, f64) {
+        let mut ctx = image_context(20, 20);
+        draw(&mut ctx);
+        ctx.flush();
+        let surface = cairo::ImageSurface::try_from(ctx.target()).unwrap();
+        drop(ctx);
+
+        average_color(&surface, Region::new(x as u32, y as u32, 1, 1))
+            .unwrap()
+            .rgba()
+    }
+
+    test_cases! {
+        circle;
+        args: (translate: f64, x: i32, y: i32, expected: (f64, f64, f64, f64));
+
+        case: centre => (0.0, 10, 10, (1.0, 0.0, 0.0, 1.0));
+        case: outside => (0.0, 1, 1, (0.0, 0.0, 0.0, 0.0));
+        case: translated => (5.0, 15, 15, (1.0, 0.0, 0.0, 1.0));
+        case: translated_away => (5.0, 5, 5, (0.0, 0.0, 0.0, 0.0));
+
+        body: {
+            let rgba = pixel_after(x, y, |ctx| {
+                ctx.translate(translate, translate);
+                ctx.color(&Color::from(0xff0000ff));
+                ctx.circle(10.0, 10.0, 4.0).unwrap();
+            });
+
+            assert_eq!(rgba, expected);
+        }
+    }
+
+    #[test]
+    fn arc_fills_a_sector() {
+        // Quarter circle from the positive x axis clockwise to the positive y axis
+        let draw = |ctx: &mut CairoDrawContext| {
+            ctx.color(&Color::from(0x00ff00ff));
+            ctx.arc(10.0, 10.0, 8.0, 0.0, std::f64::consts::FRAC_PI_2)
+                .unwrap();
+        };
+
+        assert_eq!(pixel_after(13, 13, draw), (0.0, 1.0, 0.0, 1.0));
+        assert_eq!(pixel_after(6, 6, draw), (0.0, 0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn text_wrapped_grows_vertically() {
+        let ctx = image_context(200, 200);
+        let txt = "some text that is far too long to fit on a single line";
+        let (_, line_h) = ctx.text_extent("some").unwrap();
+        let (w, h) = ctx.text_wrapped(txt, 60.0, (2.0, 3.0, 4.0, 5.0)).unwrap();
+
+        assert!(w <= 60.0);
+        assert!(h >= 2.0 * line_h + 9.0);
+    }
+
+    #[test]
+    fn text_extent_matches_rendered_text() {
+        let ctx = image_context(200, 50);
+        let (w, h) = ctx.text_extent("some text").unwrap();
+        let rendered = ctx.text_detailed("some text", 0.0, (3.0, 5.0)).unwrap();
+
+        assert_eq!((rendered.width, rendered.height), (w + 8.0, h));
+    }
+
+    #[test]
+    fn font_has_char_requires_a_font() {
+        let ctx = image_context(10, 10);
+
+        assert!(matches!(ctx.font_has_char('a'), Err(DrawError::NoFontSet)));
+    }
+
+    #[test]
+    fn restore_returns_to_the_saved_state() {
+        let ctx = image_context(10, 10);
+        ctx.translate(1.0, 2.0);
+        let before = ctx.ctx.matrix();
+
+        ctx.save().unwrap();
+        ctx.translate(3.0, 4.0);
+        ctx.set_line_width(5.0);
+        ctx.restore().unwrap();
+
+        assert_eq!(ctx.ctx.matrix(), before);
+        assert_eq!(ctx.ctx.line_width(), 2.0);
+    }
+
+    #[test]
+    fn restore_without_save_is_a_no_op() {
+        let ctx = image_context(10, 10);
+        ctx.translate(1.0, 2.0);
+        let before = ctx.ctx.matrix();
+
+        ctx.restore().unwrap();
+        ctx.rectangle(0.0, 0.0, 1.0, 1.0).unwrap();
+
+        assert_eq!(ctx.ctx.matrix(), before);
+        assert!(ctx.ctx.status().is_ok());
+    }
+
+    #[test]
+    fn scaled_offsets_are_in_logical_pixels() {
+        let mut ctx = image_context(30, 30);
+        ctx.set_scale(1.5);
+        ctx.set_x_offset(4.0);
+        ctx.set_y_offset(2.0);
+
+        assert_eq!(ctx.ctx.matrix().transform_point(0.0, 0.0), (6.0, 3.0));
+        assert_eq!(ctx.ctx.matrix().transform_distance(2.0, 2.0), (3.0, 3.0));
+    }
+
+    #[test]
+    fn reset_keeps_the_scale_factor() {
+        let mut ctx = image_context(30, 30);
+        ctx.set_scale(2.0);
+        ctx.translate(3.0, 4.0);
+        ctx.reset();
+
+        assert_eq!(
+            ctx.ctx.matrix(),
+            cairo::Matrix::new(2.0, 0.0, 0.0, 2.0, 0.0, 0.0)
+        );
+    }
+
+    #[test]
+    fn non_positive_scale_factors_are_ignored() {
+        let mut ctx = image_context(30, 30);
+        ctx.set_scale(0.0);
+        ctx.set_scale(-1.0);
+
+        assert_eq!(ctx.scale(), 1.0);
+    }
+
+    #[test]
+    fn reset_restores_a_clean_context() {
+        let ctx = image_context(10, 10);
+        ctx.translate(3.0, 4.0);
+        ctx.ctx.rectangle(0.0, 0.0, 1.0, 1.0);
+        ctx.ctx.clip();
+        ctx.ctx.set_operator(cairo::Operator::Source);
+        ctx.set_dash(&[2.0, 1.0], 0.5);
+
+        ctx.reset();
+
+        assert_eq!(ctx.ctx.matrix(), cairo::Matrix::identity());
+        assert_eq!(ctx.ctx.clip_extents().unwrap(), (0.0, 0.0, 10.0, 10.0));
+        assert_eq!(ctx.ctx.operator(), cairo::Operator::Over);
+        assert_eq!(ctx.ctx.dash_count(), 0);
+    }
+
+    #[test]
+    fn fill_pattern_caches_loaded_images() {
+        let path = std::env::temp_dir().join("penrose-fill-pattern-test.png");
+        let tile = solid_surface((1.0, 0.0, 0.0, 1.0), 2, 2);
+        tile.write_to_png(&mut File::create(&path).unwrap())
+            .unwrap();
+
+        let ctx = image_context(10, 10);
+        ctx.fill_pattern(Region::new(1, 1, 8, 8), &path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(ctx.patterns.borrow().contains_key(&path));
+        ctx.fill_pattern(Region::new(1, 1, 8, 8), &path).unwrap();
+    }
+
+    #[test]
+    fn fill_pattern_with_missing_image_is_an_error() {
+        let ctx = image_context(10, 10);
+        let res = ctx.fill_pattern(Region::new(0, 0, 5, 5), Path::new("/not/a/real/image.png"));
+
+        assert!(matches!(res, Err(DrawError::InvalidImageData(_))));
+    }
+
+    test_cases! {
+        family_with_fallbacks;
+        args: (family: &str, fallbacks: &[&str], expected: &str);
+
+        case: no_fallbacks => ("mono", &[], "mono");
+        case: fallbacks_in_order => ("mono", &["CJK", "emoji"], "mono,CJK,emoji");
+        case: existing_family_list => ("mono, serif", &["emoji"], "mono,serif,emoji");
+        case: duplicates_removed => ("mono", &["emoji", "mono", "emoji"], "mono,emoji");
+        case: no_family => ("", &["emoji"], "emoji");
+
+        body: {
+            assert_eq!(family_with_fallbacks(family, fallbacks), expected);
+        }
+    }
+
+    test_cases! {
+        cursor_byte_offset;
+        args: (index: i32, trailing: i32, expected: usize);
+
+        case: leading_edge => (0, 0, 0);
+        case: trailing_edge => (0, 1, 1);
+        case: multibyte_trailing_edge => (1, 1, 3);
+        case: past_the_end => (3, 1, 4);
+        case: index_out_of_range => (10, 0, 4);
+        case: negative_index => (-1, 0, 0);
+
+        body: {
+            assert_eq!(cursor_byte_offset("añb", index, trailing), expected);
+        }
+    }
+
+    #[test]
+    fn xy_to_index_clamps_to_the_text() {
+        let ctx = image_context(200, 50);
+        let (w, h) = ctx.text_extent("abc").unwrap();
+
+        assert_eq!(ctx.xy_to_index("abc", -10.0, -10.0).unwrap(), 0);
+        assert_eq!(ctx.xy_to_index("abc", w + 10.0, h / 2.0).unwrap(), 3);
+        assert_eq!(ctx.xy_to_index("abc", w + 10.0, h + 10.0).unwrap(), 3);
+    }
+
+    test_cases! {
+        image_scale;
+        args: (w: Option<f64>, h: Option<f64>, expected: (f64, f64));
+
+        case: native => (None, None, (1.0, 1.0));
+        case: both => (Some(10.0), Some(40.0), (0.5, 2.0));
+        case: width_only => (Some(10.0), None, (0.5, 0.5));
+        case: height_only => (None, Some(40.0), (2.0, 2.0));
+
+        body: {
+            assert_eq!(image_scale((20.0, 20.0), w, h), expected);
+        }
+    }
+
+    #[test]
+    fn image_restores_the_transform() {
+        let path = std::env::temp_dir().join("penrose-image-test.png");
+        let img = solid_surface((0.0, 1.0, 0.0, 1.0), 4, 4);
+        img.write_to_png(&mut File::create(&path).unwrap()).unwrap();
+
+        let ctx = image_context(10, 10);
+        ctx.translate(1.0, 2.0);
+        let before = ctx.ctx.matrix();
+        let res = ctx.image(&path, 3.0, 3.0, Some(2.0), None);
+        std::fs::remove_file(&path).unwrap();
+
+        res.unwrap();
+        assert_eq!(ctx.ctx.matrix(), before);
+    }
+
+    #[test]
+    fn image_with_missing_file_is_an_error() {
+        let ctx = image_context(10, 10);
+        let res = ctx.image(Path::new("/not/a/real/image.png"), 0.0, 0.0, None, None);
+
+        assert!(matches!(res, Err(DrawError::InvalidImageData(_))));
+    }
+
+    test_cases! {
+        text_clamped;
+        args: (txt: &str, max_lines: usize, expected_lines: usize, truncated: bool);
+
+        case: fits => ("short", 2, 1, false);
+        case: truncated_to_one_line => ("a b c d e f g h i j k l m n o p q r s t u v w x y z", 1, 1, true);
+        case: truncated_to_two_lines => ("a b c d e f g h i j k l m n o p q r s t u v w x y z", 2, 2, true);
+
+        body: {
+            let mut ctx = image_context(400, 400);
+            ctx.fonts.insert("monospace".into(), pango::FontDescription::from_string("monospace"));
+            ctx.font("monospace", 10).unwrap();
+            // Wide enough for "short" on a single line but not for the whole alphabet
+            let (w, _) = ctx.text_extent("short").unwrap();
+            let rendered = ctx.text_clamped(txt, w.ceil() + 1.0, max_lines, (0.0, 0.0)).unwrap();
+
+            assert_eq!(rendered.lines, expected_lines);
+            assert_eq!(rendered.truncated, truncated);
+        }
+    }
+
+    #[test]
+    fn text_outlined_matches_text_extent_and_restores_state() {
+        let ctx = image_context(200, 50);
+        let before = ctx.ctx.matrix();
+        let line_width = ctx.ctx.line_width();
+
+        let extent = ctx
+            .text_outlined(
+                "abc",
+                0.0,
+                (2.0, 3.0),
+                0x000000ff.into(),
+                1.5,
+                0xffffffff.into(),
+            )
+            .unwrap();
+        let (w, h) = ctx.text_extent("abc").unwrap();
+
+        assert_eq!(extent, (w + 5.0, h));
+        assert_eq!(ctx.ctx.matrix(), before);
+        assert_eq!(ctx.ctx.line_width(), line_width);
+    }
+
+    test_cases! {
+        font_family_resolved;
+        args: (requested: &str, resolved: &str, expected: bool);
+
+        case: exact => ("ProFont For Powerline", "ProFont For Powerline", true);
+        case: case_insensitive => ("fira code", "Fira Code", true);
+        case: one_of_several => ("Bogus, Fira Code", "Fira Code", true);
+        case: generic => ("monospace", "DejaVu Sans Mono", true);
+        case: bogus => ("NotARealFont1234", "DejaVu Sans", false);
+        case: nothing_resolved => ("Fira Code", "", false);
+
+        body: {
+            assert_eq!(font_family_resolved(requested, resolved), expected);
+        }
+    }
+
+    test_cases! {
+        lenient;
+        args: (strict: bool, fails: bool, expected: Option<Option<u8>>);
+
+        case: strict_ok => (true, false, Some(Some(1)));
+        case: strict_err => (true, true, None);
+        case: lenient_ok => (false, false, Some(Some(1)));
+        case: lenient_err => (false, true, Some(None));
+
+        body: {
+            let res: Result<u8> = if fails {
+                Err(DrawError::Pango("unable to create layout".into()))
+            } else {
+                Ok(1)
+            };
+
+            assert_eq!(lenient(strict, res).ok(), expected);
+        }
+    }
+
+    test_cases! {
+        text_when_layout_fails;
+        args: (strict: bool);
+
+        case: strict => (true);
+        case: lenient => (false);
+
+        body: {
+            // An errored context has a nil target so keep hold of the real surface to check
+            let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, 20, 20).unwrap();
+            let mut ctx = context_on(&surface);
+            ctx.set_strict(strict);
+            ctx.color(&Color::from(0xffffffff));
+            // Restoring without a matching save puts the cairo context into an error state
+            assert!(ctx.ctx.restore().is_err());
+
+            let res = ctx.text_detailed("some text", 0.0, (2.0, 3.0));
+            drop(ctx);
+            surface.flush();
+
+            if strict {
+                assert!(res.is_err());
+            } else {
+                let expected = RenderedText {
+                    width: 5.0,
+                    height: 0.0,
+                    lines: 0,
+                    truncated: false,
+                };
+                assert_eq!(res.unwrap(), expected);
+            }
+
+            let (_, _, _, a) = average_color(&surface, Region::new(0, 0, 20, 20))
+                .unwrap()
+                .rgba();
+            assert_eq!(a, 0.0);
+        }
+    }
+
+    #[test]
+    fn average_color_of_region_outside_surface_is_an_error() {
+        let surface = solid_surface((1.0, 1.0, 1.0, 1.0), 10, 10);
+
+        assert!(average_color(&surface, Region::new(20, 20, 5, 5)).is_err());
+    }
+}
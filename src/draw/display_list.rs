@@ -0,0 +1,291 @@
+//! Recording drawing commands for replaying later
+//!
+//! A [DisplayList] sits between immediate mode drawing and caching rendered output off-screen:
+//! a widget whose content rarely changes can record the commands needed to draw it once and then
+//! [replay][crate::draw::DrawContext::replay] them each time it needs to be rendered.
+use crate::draw::{Color, DrawContext, Result};
+
+/// A single recorded drawing operation along with the state needed to replay it.
+#[derive(Clone, Debug, PartialEq)]
+pub enum DrawCommand {
+    /// A filled rectangle: see [DrawContext::rectangle]
+    Rectangle {
+        /// x coordinate of the top left corner
+        x: f64,
+        /// y coordinate of the top left corner
+        y: f64,
+        /// width of the rectangle
+        w: f64,
+        /// height of the rectangle
+        h: f64,
+        /// The color that was set when this command was recorded
+        color: Option<Color>,
+    },
+    /// The outline of a rectangle: see [DrawContext::outline]
+    Outline {
+        /// x coordinate of the top left corner
+        x: f64,
+        /// y coordinate of the top left corner
+        y: f64,
+        /// width of the rectangle
+        w: f64,
+        /// height of the rectangle
+        h: f64,
+        /// The line width used for the outline
+        line_width: f64,
+        /// The color that was set when this command was recorded
+        color: Option<Color>,
+    },
+    /// A piece of text: see [DrawContext::text]
+    Text {
+        /// The text to render
+        txt: String,
+        /// Vertical offset of the text
+        h_offset: f64,
+        /// Padding either side of the text
+        padding: (f64, f64),
+        /// The font name and point size that were set when this command was recorded
+        font: Option<(String, i32)>,
+        /// The color that was set when this command was recorded
+        color: Option<Color>,
+    },
+    /// A translation of the context: see [DrawContext::translate]
+    Translate {
+        /// Horizontal translation
+        dx: f64,
+        /// Vertical translation
+        dy: f64,
+    },
+}
+
+/// A list of recorded drawing commands that can be replayed on any [DrawContext].
+///
+/// The color and font in use at the time each command is recorded are stored along side it so
+/// that replaying the list gives the same result regardless of the state of the target context.
+/// Commands recorded before any color (or font) has been set use whatever the target context
+/// has set when the list is replayed.
+///
+/// ```
+/// # use penrose::draw::DisplayList;
+/// let mut list = DisplayList::new();
+/// list.color(0x282828ff)
+///     .rectangle(0.0, 0.0, 100.0, 20.0)
+///     .font("mono", 10)
+///     .color(0xebdbb2ff)
+///     .text("cached", 2.0, (5.0, 5.0));
+///
+/// assert_eq!(list.commands().len(), 2);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DisplayList {
+    commands: Vec<DrawCommand>,
+    color: Option<Color>,
+    font: Option<(String, i32)>,
+    line_width: f64,
+}
+
+impl DisplayList {
+    /// Create a new, empty, [DisplayList]
+    pub fn new() -> Self {
+        Self {
+            commands: vec![],
+            color: None,
+            font: None,
+            line_width: 1.0,
+        }
+    }
+
+    /// The commands that have been recorded so far, in the order they will be replayed
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// Whether or not any commands have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    /// Remove all recorded commands so that the list can be recorded again
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Set the color used for subsequently recorded commands
+    pub fn color(&mut self, color: impl Into<Color>) -> &mut Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    /// Set the font used for subsequently recorded text
+    pub fn font(&mut self, font_name: impl Into<String>, point_size: i32) -> &mut Self {
+        self.font = Some((font_name.into(), point_size));
+        self
+    }
+
+    /// Set the line width used for subsequently recorded outlines
+    pub fn line_width(&mut self, w: f64) -> &mut Self {
+        self.line_width = w;
+        self
+    }
+
+    /// Record a filled rectangle
+    pub fn rectangle(&mut self, x: f64, y: f64, w: f64, h: f64) -> &mut Self {
+        let color = self.color;
+        self.commands
+            .push(DrawCommand::Rectangle { x, y, w, h, color });
+        self
+    }
+
+    /// Record the outline of a rectangle
+    pub fn outline(&mut self, x: f64, y: f64, w: f64, h: f64) -> &mut Self {
+        let (color, line_width) = (self.color, self.line_width);
+        self.commands.push(DrawCommand::Outline {
+            x,
+            y,
+            w,
+            h,
+            line_width,
+            color,
+        });
+        self
+    }
+
+    /// Record a piece of text
+    pub fn text(
+        &mut self,
+        txt: impl Into<String>,
+        h_offset: f64,
+        padding: (f64, f64),
+    ) -> &mut Self {
+        self.commands.push(DrawCommand::Text {
+            txt: txt.into(),
+            h_offset,
+            padding,
+            font: self.font.clone(),
+            color: self.color,
+        });
+        self
+    }
+
+    /// Record a translation of the context
+    pub fn translate(&mut self, dx: f64, dy: f64) -> &mut Self {
+        self.commands.push(DrawCommand::Translate { dx, dy });
+        self
+    }
+
+    // Called by DrawContext::replay
+    pub(crate) fn replay_on<C>(&self, ctx: &mut C) -> Result<()>
+    where
+        C: DrawContext + ?Sized,
+    {
+        for cmd in self.commands.iter() {
+            match cmd {
+                DrawCommand::Rectangle { x, y, w, h, color } => {
+                    if let Some(c) = color {
+                        ctx.color(c);
+                    }
+                    ctx.rectangle(*x, *y, *w, *h)?;
+                }
+
+                DrawCommand::Outline {
+                    x,
+                    y,
+                    w,
+                    h,
+                    line_width,
+                    color,
+                } => {
+                    if let Some(c) = color {
+                        ctx.color(c);
+                    }
+                    ctx.set_line_width(*line_width);
+                    ctx.outline(*x, *y, *w, *h)?;
+                }
+
+                DrawCommand::Text {
+                    txt,
+                    h_offset,
+                    padding,
+                    font,
+                    color,
+                } => {
+                    if let Some((name, size)) = font {
+                        ctx.font(name, *size)?;
+                    }
+                    if let Some(c) = color {
+                        ctx.color(c);
+                    }
+                    ctx.text(txt, *h_offset, *padding)?;
+                }
+
+                DrawCommand::Translate { dx, dy } => ctx.translate(*dx, *dy),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Default for DisplayList {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_capture_the_current_color() {
+        let (red, blue) = (Color::from(0xff0000ff), Color::from(0x0000ffff));
+        let mut list = DisplayList::new();
+        list.rectangle(0.0, 0.0, 1.0, 1.0)
+            .color(red)
+            .rectangle(1.0, 1.0, 1.0, 1.0)
+            .color(blue)
+            .outline(2.0, 2.0, 1.0, 1.0);
+
+        let colors: Vec<Option<Color>> = list
+            .commands()
+            .iter()
+            .map(|c| match c {
+                DrawCommand::Rectangle { color, .. } | DrawCommand::Outline { color, .. } => *color,
+                _ => panic!("unexpected command: {:?}", c),
+            })
+            .collect();
+
+        assert_eq!(colors, vec![None, Some(red), Some(blue)]);
+    }
+
+    #[test]
+    fn text_captures_the_current_font() {
+        let mut list = DisplayList::new();
+        list.text("a", 0.0, (0.0, 0.0))
+            .font("mono", 10)
+            .text("b", 0.0, (0.0, 0.0));
+
+        let fonts: Vec<Option<(String, i32)>> = list
+            .commands()
+            .iter()
+            .map(|c| match c {
+                DrawCommand::Text { font, .. } => font.clone(),
+                _ => panic!("unexpected command: {:?}", c),
+            })
+            .collect();
+
+        assert_eq!(fonts, vec![None, Some(("mono".to_string(), 10))]);
+    }
+
+    #[test]
+    fn clear_resets_recorded_state() {
+        let mut list = DisplayList::new();
+        list.color(0xff0000ff)
+            .line_width(3.0)
+            .rectangle(0.0, 0.0, 1.0, 1.0);
+        list.clear();
+
+        assert!(list.is_empty());
+        assert_eq!(list, DisplayList::new());
+    }
+}
@@ -0,0 +1,117 @@
+//! Compact formatting of numeric values for display in widgets
+//!
+//! These helpers are intended to keep the output of status bar widgets short and consistent:
+//! `format_si(1234.0, 1)` gives "1.2k" and `format_percent(0.991, 0)` gives "99%".
+use std::time::Duration;
+
+const SI_SUFFIXES: &[&str] = &["", "k", "M", "G", "T", "P", "E"];
+
+/// Format a ratio in the range 0.0-1.0 as a percentage with `precision` decimal places.
+///
+/// Values outside of the range 0.0-1.0 are not clamped.
+pub fn format_percent(ratio: f64, precision: usize) -> String {
+    format!("{:.*}%", precision, ratio * 100.0)
+}
+
+/// Format a value using SI suffixes (k, M, G, ...) with `precision` decimal places.
+///
+/// Values below 1000 are shown without a suffix. Rounding is taken into account when selecting
+/// the suffix so 999.99 with a precision of 1 is shown as "1.0k" rather than "1000.0".
+pub fn format_si(value: f64, precision: usize) -> String {
+    let sign = if value < 0.0 { "-" } else { "" };
+    let mut value = value.abs();
+    let mut ix = 0;
+
+    while ix < SI_SUFFIXES.len() - 1 && round_to(value, precision) >= 1000.0 {
+        value /= 1000.0;
+        ix += 1;
+    }
+
+    format!("{}{:.*}{}", sign, precision, value, SI_SUFFIXES[ix])
+}
+
+/// Format a duration using its `precision` most significant non-zero units out of days, hours,
+/// minutes and seconds: a duration of 3725 seconds with a precision of 2 is shown as "1h 2m".
+///
+/// Durations of less than a second are shown as "0s". A precision of 0 is treated as 1.
+pub fn format_duration(d: Duration, precision: usize) -> String {
+    let mut secs = d.as_secs();
+    let mut parts = vec![];
+
+    for (unit, size) in [("d", 86_400), ("h", 3_600), ("m", 60), ("s", 1)] {
+        let n = secs / size;
+        secs %= size;
+        if n > 0 {
+            parts.push(format!("{}{}", n, unit));
+        }
+    }
+
+    if parts.is_empty() {
+        return "0s".to_string();
+    }
+
+    parts.truncate(precision.max(1));
+    parts.join(" ")
+}
+
+fn round_to(value: f64, precision: usize) -> f64 {
+    let factor = 10f64.powi(precision as i32);
+    (value * factor).round() / factor
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_cases! {
+        format_percent;
+        args: (ratio: f64, precision: usize, expected: &str);
+
+        case: zero => (0.0, 0, "0%");
+        case: full => (1.0, 0, "100%");
+        case: rounds_down => (0.994, 0, "99%");
+        case: rounds_up => (0.996, 0, "100%");
+        case: with_precision => (0.1234, 1, "12.3%");
+
+        body: {
+            assert_eq!(format_percent(ratio, precision), expected);
+        }
+    }
+
+    test_cases! {
+        format_si;
+        args: (value: f64, precision: usize, expected: &str);
+
+        case: small => (999.0, 0, "999");
+        case: thousand => (1000.0, 1, "1.0k");
+        case: kilo => (1234.0, 1, "1.2k");
+        case: mega => (3_400_000.0, 1, "3.4M");
+        case: giga => (5_000_000_000.0, 0, "5G");
+        case: rounding_moves_to_next_suffix => (999.96, 1, "1.0k");
+        case: rounding_within_suffix => (999.94, 1, "999.9");
+        case: negative => (-2500.0, 1, "-2.5k");
+        case: largest_suffix => (1e21, 0, "1000E");
+
+        body: {
+            assert_eq!(format_si(value, precision), expected);
+        }
+    }
+
+    test_cases! {
+        format_duration;
+        args: (secs: u64, precision: usize, expected: &str);
+
+        case: zero => (0, 2, "0s");
+        case: seconds => (42, 2, "42s");
+        case: minute_boundary => (60, 2, "1m");
+        case: hours_and_minutes => (3725, 2, "1h 2m");
+        case: all_units => (90_061, 4, "1d 1h 1m 1s");
+        case: truncated => (90_061, 1, "1d");
+        case: zero_precision => (3725, 0, "1h");
+        case: skips_zero_units => (86_405, 2, "1d 5s");
+
+        body: {
+            assert_eq!(format_duration(Duration::from_secs(secs), precision), expected);
+        }
+    }
+}
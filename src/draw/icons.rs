@@ -0,0 +1,57 @@
+//! Codepoints for commonly used glyphs in [Nerd Fonts][1]
+//!
+//! Nerd Fonts patch icon sets into the Unicode private use area of existing fonts, so these
+//! glyphs can only be displayed when the current font is a Nerd Font. The codepoints here are
+//! from the Font Awesome and Linux logo sets, which are stable across Nerd Font releases. They
+//! are intended for use with [DrawContext::icon][crate::draw::DrawContext::icon].
+//!
+//! [1]: https://www.nerdfonts.com
+
+/// Arch Linux logo
+pub const ARCH_LINUX: char = '\u{f303}';
+/// Battery: empty
+pub const BATTERY_EMPTY: char = '\u{f244}';
+/// Battery: one quarter charged
+pub const BATTERY_QUARTER: char = '\u{f243}';
+/// Battery: half charged
+pub const BATTERY_HALF: char = '\u{f242}';
+/// Battery: three quarters charged
+pub const BATTERY_THREE_QUARTERS: char = '\u{f241}';
+/// Battery: fully charged
+pub const BATTERY_FULL: char = '\u{f240}';
+/// Calendar
+pub const CALENDAR: char = '\u{f073}';
+/// Filled circle
+pub const CIRCLE: char = '\u{f111}';
+/// Outlined circle
+pub const CIRCLE_OUTLINE: char = '\u{f10c}';
+/// Clock face
+pub const CLOCK: char = '\u{f017}';
+/// Source code brackets
+pub const CODE: char = '\u{f121}';
+/// Settings cog
+pub const COG: char = '\u{f013}';
+/// Microchip, typically used for CPU usage
+pub const CPU: char = '\u{f2db}';
+/// Envelope, typically used for mail
+pub const ENVELOPE: char = '\u{f0e0}';
+/// Firefox logo
+pub const FIREFOX: char = '\u{f269}';
+/// Closed folder
+pub const FOLDER: char = '\u{f07b}';
+/// Padlock
+pub const LOCK: char = '\u{f023}';
+/// Musical note
+pub const MUSIC: char = '\u{f001}';
+/// Power button
+pub const POWER: char = '\u{f011}';
+/// Terminal prompt
+pub const TERMINAL: char = '\u{f120}';
+/// Speaker with no sound
+pub const VOLUME_OFF: char = '\u{f026}';
+/// Speaker at low volume
+pub const VOLUME_DOWN: char = '\u{f027}';
+/// Speaker at high volume
+pub const VOLUME_UP: char = '\u{f028}';
+/// Wifi signal
+pub const WIFI: char = '\u{f1eb}';
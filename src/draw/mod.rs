@@ -5,23 +5,37 @@
 //! for writing a full GUI application, the [Draw] and [DrawContext] traits are enough for setting
 //! up simple text based UI elements such as status bars and menus.
 pub mod bar;
+#[cfg(any(feature = "xcb_draw", feature = "wayland"))]
+pub mod cairo_context;
+pub mod display_list;
+pub mod format;
+pub mod icons;
 pub mod widget;
 
 #[doc(inline)]
 pub use bar::*;
 
+use bar::{bar_region, strut_partial};
+
+#[cfg(any(feature = "xcb_draw", feature = "wayland"))]
+#[doc(inline)]
+pub use cairo_context::CairoDrawContext;
+
+#[doc(inline)]
+pub use display_list::{DisplayList, DrawCommand};
+
 #[doc(inline)]
-pub use widget::{HookableWidget, KeyboardControlled, Widget};
+pub use widget::{ClickTarget, HookableWidget, KeyboardControlled, Widget};
 
 use crate::core::{
     data_types::{Region, WinType},
-    xconnection::{XClientHandler, XClientProperties, XKeyboardHandler, Xid},
+    xconnection::{Atom, Prop, XClientHandler, XClientProperties, XKeyboardHandler, Xid},
 };
 
 #[cfg(feature = "xcb")]
 use crate::xcb::XcbError;
 
-use std::{convert::TryFrom, convert::TryInto};
+use std::{convert::TryFrom, convert::TryInto, path::Path, str::FromStr};
 
 /// Enum to store the various ways that operations can fail when rendering windows
 #[derive(thiserror::Error, Debug)]
@@ -34,29 +48,72 @@ pub enum DrawError {
     #[error("Invalid Hex color code")]
     ParseInt(#[from] std::num::ParseIntError),
 
+    /// A CSS style `rgb(...)` / `rgba(...)` color string was malformed
+    #[error("Invalid color string '{0}': {1}")]
+    InvalidColorString(String, String),
+
     /// A generic error type for use in user code when needing to construct
     /// a simple [DrawError].
     #[error("Unhandled error: {0}")]
     Raw(String),
 
+    /// A font was registered that does not match any font installed on the system
+    #[error("'{0}' does not match any installed font family")]
+    FontNotFound(String),
+
     /// An attempt was made to use a font that had not beed registered
     #[error("'{0}' is has not been registered as a font")]
     UnknownFont(String),
 
+    /// An operation requiring a font was attempted before a font was set on a [DrawContext]
+    #[error("no font has been set for this context")]
+    NoFontSet,
+
     /// Wrapper around XCB implementation errors for [draw][crate::draw] traits
     #[cfg(feature = "xcb")]
     #[error(transparent)]
     Xcb(#[from] XcbError),
 
+    /// Wrapper around Wayland implementation errors for [draw][crate::draw] traits
+    #[cfg(feature = "wayland")]
+    #[error(transparent)]
+    Wayland(#[from] crate::wayland::WaylandError),
+
+    /// A screen index was requested that does not match any connected screen
+    #[error("The requested screen index was out of bounds: {0} > {1}")]
+    UnknownScreen(usize, usize),
+
+    /// A required field was not set when building a [StatusBar]
+    #[error("Unable to build StatusBar: '{0}' was not set")]
+    MissingField(&'static str),
+
+    /// Raw image data did not match the dimensions it was provided with
+    #[error("Invalid image data: {0}")]
+    InvalidImageData(String),
+
+    /// The connection to the X server was lost and could not be re-established
+    #[error("Unable to reconnect to the X server: {0}")]
+    ReconnectFailed(String),
+
     /// Something went wrong when communicating with the X server
     #[error(transparent)]
     X(#[from] crate::core::xconnection::XError),
 
-    /// An attempt to use the cairo C API failed when using an XCB implementation
+    /// An attempt to use the cairo C API failed when using an XCB or Wayland implementation
     /// of [Draw] or [DrawContext]
-    #[cfg(feature = "xcb")]
+    #[cfg(any(feature = "xcb", feature = "wayland"))]
     #[error("Error calling Cairo API: {0}")]
     Cairo(#[from] cairo::Error),
+
+    /// Unable to access the pixel data of a cairo image surface
+    #[cfg(any(feature = "xcb", feature = "wayland"))]
+    #[error("Unable to access image surface data: {0}")]
+    ImageSurfaceData(#[from] cairo::BorrowError),
+
+    /// A call to pango failed when using a [CairoDrawContext]
+    #[cfg(any(feature = "xcb_draw", feature = "wayland"))]
+    #[error("Error calling Pango API: {0}")]
+    Pango(String),
 }
 
 /// Result type for fallible methods on [Draw] and [DrawContext]
@@ -88,6 +145,10 @@ pub struct Color {
     a: f64,
 }
 
+// Saturation and lightness used by Color::from_hash
+const HASH_SATURATION: f64 = 0.6;
+const HASH_LIGHTNESS: f64 = 0.55;
+
 // helper for methods in Color
 macro_rules! _f2u { { $f:expr, $s:expr } => { (($f * 255.0) as u32) << $s } }
 
@@ -104,6 +165,52 @@ impl Color {
         Self { r, g, b, a }
     }
 
+    /// Create a new Color from a hex encoded u32 with the alpha channel in the most significant
+    /// byte: 0xAARRGGBB. This is the ARGB layout used by X11 and cairo for 32 bit pixels.
+    pub fn new_from_hex_alpha(hex: u32) -> Self {
+        Self::new_from_hex(hex.rotate_left(8))
+    }
+
+    /// Create a new, fully opaque, Color from a hue in degrees along with saturation and
+    /// lightness in the range 0.0-1.0. Hues outside of 0-360 wrap around and saturation and
+    /// lightness are clamped.
+    pub fn from_hsl(h: f64, s: f64, l: f64) -> Self {
+        let h = h.rem_euclid(360.0) / 60.0;
+        let (s, l) = (s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+        let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        let x = c * (1.0 - (h % 2.0 - 1.0).abs());
+        let m = l - c / 2.0;
+
+        let (r, g, b) = match h as u32 {
+            0 => (c, x, 0.0),
+            1 => (x, c, 0.0),
+            2 => (0.0, c, x),
+            3 => (0.0, x, c),
+            4 => (x, 0.0, c),
+            _ => (c, 0.0, x),
+        };
+
+        Self {
+            r: r + m,
+            g: g + m,
+            b: b + m,
+            a: 1.0,
+        }
+    }
+
+    /// Deterministically pick a color for the given string, such as a workspace or tag name.
+    ///
+    /// The hue is the 32-bit FNV-1a hash of the UTF-8 bytes of `s` modulo 360, with a fixed
+    /// saturation of 0.6 and lightness of 0.55. This will not change between releases so a given
+    /// string will always map to the same color.
+    pub fn from_hash(s: &str) -> Self {
+        let hash = s.bytes().fold(0x811c9dc5_u32, |h, b| {
+            (h ^ b as u32).wrapping_mul(0x01000193)
+        });
+
+        Self::from_hsl((hash % 360) as f64, HASH_SATURATION, HASH_LIGHTNESS)
+    }
+
     /// The RGB information of this color as 0.0-1.0 range floats representing
     /// proportions of 255 for each of R, G, B
     pub fn rgb(&self) -> (f64, f64, f64) {
@@ -121,6 +228,16 @@ impl Color {
         format!("#{:x}", self.rgb_u32())
     }
 
+    /// Render this color as a hex color string that can be parsed back into the same Color:
+    /// `#rrggbb` if the color is fully opaque and `#rrggbbaa` otherwise.
+    pub fn to_hex_string(&self) -> String {
+        if self.a >= 1.0 {
+            format!("#{:06x}", self.rgb_u32())
+        } else {
+            format!("#{:08x}", self.rgba_u32())
+        }
+    }
+
     /// 0xRRGGBB representation of this Color (no alpha information)
     pub fn rgb_u32(&self) -> u32 {
         _f2u!(self.r, 16) + _f2u!(self.g, 8) + _f2u!(self.b, 0)
@@ -130,6 +247,137 @@ impl Color {
     pub fn rgba_u32(&self) -> u32 {
         _f2u!(self.r, 24) + _f2u!(self.g, 16) + _f2u!(self.b, 8) + _f2u!(self.a, 0)
     }
+
+    /// The relative luminance of this Color as defined by the [WCAG][1] (alpha is ignored).
+    ///
+    /// This ranges from 0.0 for black to 1.0 for white and can be used to sort colors by their
+    /// perceived brightness:
+    ///
+    /// ```
+    /// # use penrose::draw::Color;
+    /// let mut palette: Vec<Color> = vec![0xfabd2fff.into(), 0x282828ff.into(), 0x458588ff.into()];
+    /// palette.sort_by(|a, b| a.luminance().total_cmp(&b.luminance()));
+    ///
+    /// assert_eq!(palette[0], Color::from(0x282828ff));
+    /// ```
+    ///
+    /// [1]: https://www.w3.org/TR/WCAG21/#dfn-relative-luminance
+    pub fn luminance(&self) -> f64 {
+        let linear = |c: f64| {
+            if c <= 0.03928 {
+                c / 12.92
+            } else {
+                ((c + 0.055) / 1.055).powf(2.4)
+            }
+        };
+
+        0.2126 * linear(self.r) + 0.7152 * linear(self.g) + 0.0722 * linear(self.b)
+    }
+
+    /// The WCAG contrast ratio between this Color and `other`, ranging from 1.0 (no contrast)
+    /// to 21.0 (black on white).
+    pub fn contrast_ratio(&self, other: &Color) -> f64 {
+        let (a, b) = (self.luminance(), other.luminance());
+        let (light, dark) = if a > b { (a, b) } else { (b, a) };
+
+        (light + 0.05) / (dark + 0.05)
+    }
+
+    /// A copy of this Color that has been lightened or darkened as little as possible in order
+    /// to have a contrast ratio of at least `min_ratio` against `bg`.
+    ///
+    /// If `min_ratio` can not be reached then whichever of black or white has the higher contrast
+    /// against `bg` is returned. The alpha channel is left unchanged.
+    pub fn ensure_contrast(&self, bg: &Color, min_ratio: f64) -> Color {
+        if self.contrast_ratio(bg) >= min_ratio {
+            return *self;
+        }
+
+        let blend = |target: f64, t: f64| {
+            let mix = |c: f64| c + (target - c) * t;
+            Color {
+                r: mix(self.r),
+                g: mix(self.g),
+                b: mix(self.b),
+                a: self.a,
+            }
+        };
+
+        // Binary search for the smallest blend towards the target that meets min_ratio
+        let search = |target: f64| {
+            if blend(target, 1.0).contrast_ratio(bg) < min_ratio {
+                return None;
+            }
+
+            let (mut lo, mut hi) = (0.0, 1.0);
+            for _ in 0..20 {
+                let mid = (lo + hi) / 2.0;
+                if blend(target, mid).contrast_ratio(bg) >= min_ratio {
+                    hi = mid;
+                } else {
+                    lo = mid;
+                }
+            }
+
+            Some((hi, blend(target, hi)))
+        };
+
+        match (search(1.0), search(0.0)) {
+            (Some((t1, c1)), Some((t2, c2))) => {
+                if t1 <= t2 {
+                    c1
+                } else {
+                    c2
+                }
+            }
+            (Some((_, c)), None) | (None, Some((_, c))) => c,
+            (None, None) => {
+                let (white, black) = (blend(1.0, 1.0), blend(0.0, 1.0));
+                if white.contrast_ratio(bg) >= black.contrast_ratio(bg) {
+                    white
+                } else {
+                    black
+                }
+            }
+        }
+    }
+
+    /// A copy of this Color with each of R, G, B multiplied by `factor` (clamped to the
+    /// 0.0-1.0 range). The alpha channel is left unchanged.
+    pub fn scale_brightness(&self, factor: f64) -> Self {
+        let scale = |c: f64| (c * factor).clamp(0.0, 1.0);
+
+        Self {
+            r: scale(self.r),
+            g: scale(self.g),
+            b: scale(self.b),
+            a: self.a,
+        }
+    }
+
+    /// Linearly interpolate between this Color and `other`, with `t` (clamped to the 0.0-1.0
+    /// range) giving the proportion of `other` in the result. All four channels are
+    /// interpolated.
+    pub fn lerp(&self, other: &Color, t: f64) -> Self {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: f64, b: f64| a + (b - a) * t;
+
+        Self {
+            r: mix(self.r, other.r),
+            g: mix(self.g, other.g),
+            b: mix(self.b, other.b),
+            a: mix(self.a, other.a),
+        }
+    }
+
+    /// A copy of this Color with its alpha channel replaced by `a` (clamped to the 0.0-1.0
+    /// range). R, G and B are left unchanged.
+    pub fn with_alpha(&self, a: f64) -> Self {
+        Self {
+            a: a.clamp(0.0, 1.0),
+            ..*self
+        }
+    }
 }
 
 impl From<u32> for Color {
@@ -160,22 +408,197 @@ impl TryFrom<String> for Color {
     }
 }
 
+// Parses the argument list of a CSS style rgb(r, g, b) or rgba(r, g, b, a) color function
+fn parse_rgb_function(s: &str, args: &str, has_alpha: bool) -> Result<Color> {
+    let err = |msg: String| DrawError::InvalidColorString(s.into(), msg);
+    let parts: Vec<&str> = args.split(',').map(|p| p.trim()).collect();
+    let expected = if has_alpha { 4 } else { 3 };
+
+    if parts.len() != expected {
+        return Err(err(format!(
+            "expected {} values, got {}",
+            expected,
+            parts.len()
+        )));
+    }
+
+    let channels = parts[0..3]
+        .iter()
+        .map(|p| match p.parse::<u32>() {
+            Ok(n) if n <= 255 => Ok(n as f64 / 255.0),
+            Ok(n) => Err(err(format!(
+                "channel value {} is not in the range 0-255",
+                n
+            ))),
+            Err(_) => Err(err(format!("'{}' is not a valid channel value", p))),
+        })
+        .collect::<Result<Vec<f64>>>()?;
+
+    let a = if has_alpha {
+        match parts[3].parse::<f64>() {
+            Ok(a) if (0.0..=1.0).contains(&a) => a,
+            Ok(a) => return Err(err(format!("alpha value {} is not in the range 0-1", a))),
+            Err(_) => return Err(err(format!("'{}' is not a valid alpha value", parts[3]))),
+        }
+    } else {
+        1.0
+    };
+
+    Ok(Color::from((channels[0], channels[1], channels[2], a)))
+}
+
+fn color_function_args<'a>(s: &'a str, name: &str) -> Option<&'a str> {
+    s.strip_prefix(name)?
+        .trim_start()
+        .strip_prefix('(')?
+        .strip_suffix(')')
+}
+
 impl TryFrom<&str> for Color {
     type Error = DrawError;
 
+    /// Parse a color from either a hex string (`RRGGBB` / `RRGGBBAA` with or without a leading
+    /// `#`) or CSS function syntax (`rgb(r, g, b)` / `rgba(r, g, b, a)`) where channels are in
+    /// the range 0-255 and alpha is in the range 0-1.
     fn try_from(s: &str) -> Result<Self> {
-        let hex = u32::from_str_radix(s.strip_prefix('#').unwrap_or(&s), 16)?;
+        let trimmed = s.trim();
+        if let Some(args) = color_function_args(trimmed, "rgba") {
+            return parse_rgb_function(s, args, true);
+        } else if let Some(args) = color_function_args(trimmed, "rgb") {
+            return parse_rgb_function(s, args, false);
+        }
+
+        let digits = trimmed.strip_prefix('#').unwrap_or(trimmed);
+        if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(DrawError::InvalidHexColor(s.into()));
+        }
 
-        if s.len() == 7 {
-            Ok(Self::new_from_hex((hex << 8) + 0xFF))
-        } else if s.len() == 9 {
-            Ok(Self::new_from_hex(hex))
+        match digits.len() {
+            6 => Ok(Self::new_from_hex(
+                (u32::from_str_radix(digits, 16)? << 8) + 0xFF,
+            )),
+            8 => Ok(Self::new_from_hex(u32::from_str_radix(digits, 16)?)),
+            _ => Err(DrawError::InvalidHexColor(s.into())),
+        }
+    }
+}
+
+impl FromStr for Color {
+    type Err = DrawError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        Self::try_from(s)
+    }
+}
+
+/// Details about a piece of text that has been rendered by a [DrawContext]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RenderedText {
+    /// The width taken up by the text, including padding
+    pub width: f64,
+    /// The height taken up by the text
+    pub height: f64,
+    /// The number of visual lines that the text was rendered over
+    pub lines: usize,
+    /// Whether or not the text was ellipsized to fit the available space
+    pub truncated: bool,
+}
+
+/// Vertical metrics for a font, in pixels
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FontMetrics {
+    /// Distance from the baseline to the top of the font
+    pub ascent: f64,
+    /// Distance from the baseline to the bottom of the font
+    pub descent: f64,
+}
+
+impl FontMetrics {
+    /// The total height of a line of text in this font
+    pub fn height(&self) -> f64 {
+        self.ascent + self.descent
+    }
+}
+
+/// Information about an X visual that can be used when creating windows
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct VisualInfo {
+    /// The X id of this visual
+    pub id: Xid,
+    /// The bit depth of this visual (32 for visuals that support an alpha channel)
+    pub depth: u8,
+}
+
+/// A change to the window manager state advertised on the root window using [EWMH][1]
+/// properties.
+///
+/// Status bars running as a separate process can watch for these in order to redraw as soon as
+/// the state of the window manager changes rather than polling it.
+///
+/// [1]: https://specifications.freedesktop.org/wm-spec/wm-spec-1.3.html
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StateChange {
+    /// The focused window changed (`_NET_ACTIVE_WINDOW`)
+    ActiveWindow,
+    /// The active workspace changed (`_NET_CURRENT_DESKTOP`)
+    CurrentDesktop,
+}
+
+impl StateChange {
+    /// The [StateChange] signalled by a change to the named root window property, if any
+    pub fn from_property(atom: &str) -> Option<Self> {
+        if atom == Atom::NetActiveWindow.as_ref() {
+            Some(Self::ActiveWindow)
+        } else if atom == Atom::NetCurrentDesktop.as_ref() {
+            Some(Self::CurrentDesktop)
         } else {
-            Err(DrawError::InvalidHexColor(s.into()))
+            None
         }
     }
 }
 
+// 4x4 ordered (Bayer) dither thresholds
+const BAYER_4X4: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+/// The set of pixels within a repeating 4x4 tile that should be filled in order to approximate
+/// the given alpha value using ordered dithering. Indexed as `mask[y][x]`.
+pub fn dither_mask(alpha: f64) -> [[bool; 4]; 4] {
+    let filled = (alpha.clamp(0.0, 1.0) * 16.0).round() as u8;
+    let mut mask = [[false; 4]; 4];
+    for (y, row) in BAYER_4X4.iter().enumerate() {
+        for (x, threshold) in row.iter().enumerate() {
+            mask[y][x] = *threshold < filled;
+        }
+    }
+
+    mask
+}
+
+/// The path to stroke with a line of `line_width` so that the stroke lies entirely within the
+/// rectangle (x, y, w, h). Strokes are centred on their path so the path is inset by half of the
+/// line width on each side.
+pub fn inset_stroke_path(x: f64, y: f64, w: f64, h: f64, line_width: f64) -> (f64, f64, f64, f64) {
+    let lw = line_width.max(0.0).min(w).min(h);
+    let half = lw / 2.0;
+
+    (x + half, y + half, w - lw, h - lw)
+}
+
+// Offset 'r' to be relative to the origin of screens[ix]
+fn region_on_screen(r: Region, screens: &[Region], ix: usize) -> Result<Region> {
+    let (sx, sy, _, _) = screens
+        .get(ix)
+        .ok_or_else(|| DrawError::UnknownScreen(ix, screens.len().saturating_sub(1)))?
+        .values();
+    let (x, y, w, h) = r.values();
+
+    Ok(Region::new(sx + x, sy + y, w, h))
+}
+
 /// A simple drawing abstraction
 ///
 /// `Draw` is not intended for use in writing full GUI interfaces, rather it is a simple
@@ -183,30 +606,131 @@ impl TryFrom<&str> for Color {
 /// and dialogs. Each `Draw` should also provide an acompanying `DrawContext` that impl that is
 /// used by consumers (such as the status bar) for actually drawing to the screen, which the parent
 /// `Draw` is responsible for resource management and mapping / unmapping the created windows.
-pub trait Draw: XClientHandler + XClientProperties {
+///
+/// `Draw` itself makes no assumptions about the display server being used: functionality that
+/// relies on the created windows being X clients lives in [XDraw].
+pub trait Draw {
     /// The type of drawing context used for drawing
     type Ctx: DrawContext;
 
     /// Create a new client window with a canvas for drawing
     fn new_window(&mut self, ty: WinType, r: Region, managed: bool) -> Result<Xid>;
+    /// Create a new client window with a canvas for drawing on the screen with index
+    /// `screen_ix` (as ordered by [Draw::screen_sizes]). The position of `r` is taken as being
+    /// relative to the top left corner of that screen.
+    ///
+    /// # Errors
+    /// Fails if `screen_ix` does not match a connected screen.
+    fn new_window_on(
+        &mut self,
+        ty: WinType,
+        r: Region,
+        managed: bool,
+        screen_ix: usize,
+    ) -> Result<Xid> {
+        let r = region_on_screen(r, &self.screen_sizes()?, screen_ix)?;
+        self.new_window(ty, r, managed)
+    }
     /// Get the size of the target screen in pixels
     fn screen_sizes(&self) -> Result<Vec<Region>>;
+    /// Select a visual for the given screen: either a 32-bit visual supporting an alpha channel
+    /// (if `want_alpha` is true) or the default visual for the screen.
+    ///
+    /// # Errors
+    /// Fails if there is no matching visual available for the requested screen.
+    fn best_visual(&self, screen_ix: usize, want_alpha: bool) -> Result<VisualInfo>;
     /// Register a font by name for later use
-    fn register_font(&mut self, font_name: &str);
+    ///
+    /// # Errors
+    /// Fails if the font does not match a font installed on the system.
+    fn register_font(&mut self, font_name: &str) -> Result<()>;
+    /// Register a font by name for later use without checking that it is installed.
+    ///
+    /// Resolution of the font is deferred until it is first used for rendering.
+    fn register_font_unchecked(&mut self, font_name: &str);
     /// Get a new [DrawContext] for the target window
     fn context_for(&self, id: Xid) -> Result<Self::Ctx>;
+    /// Get a new [DrawContext] for each of the target windows, in the order they were given.
+    ///
+    /// This is all or nothing: if any of the requested ids are unknown then the whole batch
+    /// fails and no contexts are returned.
+    fn contexts_for(&self, ids: &[Xid]) -> Result<Vec<Self::Ctx>> {
+        ids.iter().map(|&id| self.context_for(id)).collect()
+    }
     /// Get a new temporary [DrawContext] that will be destroyed when dropped
     fn temp_context(&self, w: u32, h: u32) -> Result<Self::Ctx>;
     /// Flush pending actions
     fn flush(&self, id: Xid) -> Result<()>;
+    /// Is the connection to the X server still usable?
+    ///
+    /// Defaults to always returning true for implementations that are unable to detect a lost
+    /// connection.
+    fn connection_alive(&self) -> bool {
+        true
+    }
+    /// Attempt to re-establish a lost connection to the X server. All previously created windows
+    /// should be considered invalid after a successful reconnect.
+    ///
+    /// Defaults to failing for implementations that do not support reconnecting.
+    fn reconnect(&mut self) -> Result<()> {
+        Err(DrawError::ReconnectFailed(
+            "reconnecting is not supported".into(),
+        ))
+    }
+}
+
+/// A [Draw] whose windows are X clients that can be manipulated directly using the
+/// [XClientHandler] and [XClientProperties] traits.
+pub trait XDraw: Draw + XClientHandler + XClientProperties {
+    /// Create a new client window with a canvas for drawing in the same way as
+    /// [Draw::new_window] but without mapping it. The window can be shown later using
+    /// [XClientHandler::map_client], avoiding it briefly appearing before it is needed.
+    /// Drawing to the window and calling [Draw::flush] does not map it, so it can be rendered
+    /// in advance of being shown.
+    ///
+    /// The default implementation creates the window using [Draw::new_window] and then immediately
+    /// unmaps it: implementations are encouraged to avoid mapping the window at all.
+    fn new_unmapped_window(&mut self, ty: WinType, r: Region, managed: bool) -> Result<Xid> {
+        let id = self.new_window(ty, r, managed)?;
+        self.unmap_client(id)?;
+
+        Ok(id)
+    }
+    /// Create a new dock window `thickness` pixels tall spanning the full width of the screen
+    /// with index `screen_ix` (as ordered by [Draw::screen_sizes]) at the given [Position].
+    ///
+    /// Matching `_NET_WM_STRUT` and `_NET_WM_STRUT_PARTIAL` properties are set on the window so
+    /// that EWMH aware window managers leave space for it, guaranteeing that the reserved space
+    /// always agrees with the size of the window.
+    ///
+    /// # Errors
+    /// Fails if `screen_ix` does not match a connected screen.
+    fn new_dock(&mut self, position: Position, thickness: u32, screen_ix: usize) -> Result<Xid> {
+        let screens = self.screen_sizes()?;
+        let screen = *screens
+            .get(screen_ix)
+            .ok_or_else(|| DrawError::UnknownScreen(screen_ix, screens.len().saturating_sub(1)))?;
+        let root_h = screens.iter().map(|s| s.y + s.h).max().unwrap_or(0);
+
+        let r = bar_region(position, screen, thickness);
+        let id = self.new_window(WinType::InputOutput(Atom::NetWindowTypeDock), r, false)?;
+
+        let strut = strut_partial(position, r, root_h);
+        let partial = Prop::Cardinals(strut.to_vec());
+        let strut = Prop::Cardinals(strut[..4].to_vec());
+        self.change_prop(id, Atom::NetWmStrut.as_ref(), strut)?;
+        self.change_prop(id, Atom::NetWmStrutPartial.as_ref(), partial)?;
+
+        Ok(id)
+    }
 }
 
 /// A [Draw] that can return the [KeyPress][1] events from the user for its windows
 ///
 /// [1]: crate::core::bindings::KeyPress
-pub trait KeyPressDraw: Draw + XKeyboardHandler {}
+pub trait KeyPressDraw: XDraw + XKeyboardHandler {}
 
-impl<T> KeyPressDraw for T where T: Draw + XKeyboardHandler {}
+impl<T> KeyPressDraw for T where T: XDraw + XKeyboardHandler {}
 
 /// Used for simple drawing to the screen
 pub trait DrawContext {
@@ -216,21 +740,279 @@ pub trait DrawContext {
     fn color(&mut self, color: &Color);
     /// Clears the context
     fn clear(&mut self) -> Result<()>;
+    /// Return this context to a known clean state without modifying anything that has already
+    /// been drawn. This resets:
+    ///   - the transformation matrix to the identity (undoing any translation, including x and y
+    ///     offsets)
+    ///   - the clip region, so that the whole surface can be drawn to
+    ///   - the compositing operator, to draw over the top of existing content
+    ///   - the dash pattern, so that strokes are solid
+    ///
+    /// The current color, font and line width are left unchanged.
+    fn reset(&self);
+    /// Push a copy of the current drawing state onto a stack so that it can be returned to later
+    /// using [DrawContext::restore]. The saved state includes the transformation matrix (and so
+    /// any x and y offsets), the current color, line width, dash pattern and clip region. The
+    /// current font is not saved.
+    fn save(&self) -> Result<()>;
+    /// Return to the drawing state most recently saved using [DrawContext::save], removing it from
+    /// the stack. Calling restore without a matching call to save is a no-op.
+    fn restore(&self) -> Result<()>;
     /// Translate this context by (dx, dy) from its current position
     fn translate(&self, dx: f64, dy: f64);
     /// Set the x offset for this context absolutely
     fn set_x_offset(&self, x: f64);
     /// Set the y offset for this context absolutely
     fn set_y_offset(&self, y: f64);
+    /// Snap a horizontal user space coordinate to the nearest device pixel boundary.
+    ///
+    /// The coordinate is mapped through the current transform matrix, rounded and then mapped
+    /// back, so the result depends on any translation applied to the context. Filling a 1px wide
+    /// rectangle starting at the aligned coordinate gives a crisp line rather than one smeared
+    /// across two pixels by antialiasing. For 1px strokes, add 0.5 to the aligned coordinate so
+    /// that the line is centred on a pixel.
+    fn device_align(&self, coord: f64) -> f64;
+    /// Set the line width in pixels used for subsequent stroke operations
+    fn set_line_width(&self, w: f64);
+    /// Set the dash pattern used for subsequent stroke operations as alternating on / off
+    /// lengths in pixels, starting `offset` pixels into the pattern. An empty pattern gives a
+    /// solid line.
+    ///
+    /// Advancing `offset` between frames when calling [DrawContext::outline] moves the dashes
+    /// around the rectangle, giving a "marching ants" effect.
+    fn set_dash(&self, dashes: &[f64], offset: f64);
     /// Draw a filled rectangle using the current color
     fn rectangle(&self, x: f64, y: f64, w: f64, h: f64) -> Result<()>;
+    /// Draw the outline of a rectangle using the current color and line width
+    fn outline(&self, x: f64, y: f64, w: f64, h: f64) -> Result<()>;
+    /// Draw a filled circular sector centred on (x, y) using the current color, sweeping
+    /// clockwise from the angle `start` to `end` (in radians, with 0 pointing along the positive
+    /// x axis).
+    fn arc(&self, x: f64, y: f64, radius: f64, start: f64, end: f64) -> Result<()>;
+    /// Draw a filled circle centred on (x, y) using the current color
+    fn circle(&self, x: f64, y: f64, radius: f64) -> Result<()> {
+        self.arc(x, y, radius, 0.0, 2.0 * std::f64::consts::PI)
+    }
     /// Render 's' using the current font with the supplied padding. returns the extent taken
     /// up by the rendered text
     fn text(&self, s: &str, h_offset: f64, padding: (f64, f64)) -> Result<(f64, f64)>;
-    /// Determine the pixel width of a given piece of text using the current font
+    /// Render 's' in the same way as [DrawContext::text] but return full details of the
+    /// rendered text rather than just its extent
+    fn text_detailed(&self, s: &str, h_offset: f64, padding: (f64, f64)) -> Result<RenderedText>;
+    /// Render 's' in the same way as [DrawContext::text] but with the glyphs filled using `fill`
+    /// and outlined using `outline`, leaving an outline `width` pixels wide around each glyph.
+    /// This gives legible text over arbitrary backgrounds without the blur of a drop shadow.
+    ///
+    /// The current color of the context is left unchanged. Returns the extent taken up by the
+    /// rendered text, not including the outline.
+    fn text_outlined(
+        &self,
+        s: &str,
+        h_offset: f64,
+        padding: (f64, f64),
+        outline: Color,
+        width: f64,
+        fill: Color,
+    ) -> Result<(f64, f64)>;
+    /// Render 's' using the current font, clipped to `region_width` and scrolled horizontally
+    /// by `offset_px`. The offset wraps at the width of the text so incrementing it each frame
+    /// gives a looping marquee. Text that fits within the region is rendered without scrolling.
+    /// Returns the extent taken up by the rendered text.
+    fn text_scrolled(
+        &self,
+        s: &str,
+        region_width: f64,
+        offset_px: f64,
+        padding: (f64, f64),
+    ) -> Result<(f64, f64)>;
+    /// Render 's' using the current font, wrapping it to fit within `max_width` and showing at
+    /// most `max_lines` lines. If the text does not fit, the last line shown is ellipsized. The
+    /// returned height is the vertical space used by the lines that were rendered.
+    fn text_clamped(
+        &self,
+        s: &str,
+        max_width: f64,
+        max_lines: usize,
+        padding: (f64, f64),
+    ) -> Result<RenderedText>;
+    /// Render 's' using the current font, word wrapping it to fit within `width` and using as
+    /// many lines as are needed. Unlike [DrawContext::text], text is never ellipsized.
+    ///
+    /// `padding` is given as (left, right, top, bottom) and is applied inside of `width`. Returns
+    /// the extent taken up by the rendered text, including padding.
+    fn text_wrapped(
+        &self,
+        s: &str,
+        width: f64,
+        padding: (f64, f64, f64, f64),
+    ) -> Result<(f64, f64)>;
+    /// The byte offset within 's' of the cursor position closest to the point (x, y), where (x, y)
+    /// is relative to the top left corner of the text when rendered using the current font with
+    /// no padding or offset. Points outside of the text are clamped to the nearest position
+    /// within it, so the result is always a valid char boundary in the range `0..=s.len()`.
+    fn xy_to_index(&self, s: &str, x: f64, y: f64) -> Result<usize>;
+    /// Determine the pixel width and height of a given piece of text using the current font,
+    /// without rendering it.
+    ///
+    /// As with all extents returned by a [DrawContext], these are logical pixels: for contexts
+    /// that render at a scale factor other than 1.0 the text will cover a different number of
+    /// device pixels, and the extent may be fractional so that layout stays aligned once scaled.
     fn text_extent(&self, s: &str) -> Result<(f64, f64)>;
+    /// Render an image from raw pixel data with its top left corner at (x, y).
+    ///
+    /// `data` must contain `width * height` pixels, each given as 4 bytes in R, G, B, A order
+    /// with rows running from top to bottom. Color values must be premultiplied by their alpha
+    /// value (e.g. 50% transparent white is `[128, 128, 128, 128]`) as required by cairo.
+    fn image_rgba(&self, data: &[u8], width: usize, height: usize, x: f64, y: f64) -> Result<()>;
+    /// Fill the given region by tiling the PNG image found at `image`, starting from the top
+    /// left corner of the region. Loaded images are cached by path.
+    fn fill_pattern(&self, region: Region, image: &Path) -> Result<()>;
+    /// Render the PNG image found at `image` with its top left corner at (x, y).
+    ///
+    /// If `w` and / or `h` are provided the image is scaled to that size, preserving its aspect
+    /// ratio if only one of the two is given. Otherwise it is drawn at its native size. Loaded
+    /// images are cached by path.
+    fn image(&self, image: &Path, x: f64, y: f64, w: Option<f64>, h: Option<f64>) -> Result<()>;
+    /// The vertical metrics of the current font, for use when centering text
+    fn font_metrics(&self) -> Result<FontMetrics>;
+    /// Whether or not the current font contains a glyph for `c`.
+    ///
+    /// # Errors
+    /// Fails if no font has been set for this context.
+    fn font_has_char(&self, c: char) -> Result<bool>;
     /// Flush pending actions
     fn flush(&self);
+
+    /// Render a single glyph from an icon font (such as a [Nerd Font][1]) centered within a
+    /// square cell the height of the glyph, vertically centered within `h`. The current font
+    /// is assumed to be an icon font: codepoints for common icons are provided in
+    /// [icons][crate::draw::icons]. Returns the extent taken up by the cell and its padding.
+    ///
+    /// [1]: https://www.nerdfonts.com
+    fn icon(&self, codepoint: char, h: f64, padding: (f64, f64)) -> Result<(f64, f64)> {
+        let s = codepoint.to_string();
+        let (gw, gh) = self.text_extent(&s)?;
+        let cell = gw.max(gh);
+        let (l, r) = padding;
+        let dx = (cell - gw) / 2.0;
+
+        self.translate(dx, 0.0);
+        let res = self.text(&s, ((h - gh) / 2.0).max(0.0), (l, 0.0));
+        self.translate(-dx, 0.0);
+        res?;
+
+        Ok((cell + l + r, gh))
+    }
+
+    /// Render `codepoint` as an [icon][DrawContext::icon] if the current font contains it,
+    /// otherwise render `fallback` as text vertically centered within `h`. This avoids drawing
+    /// an empty box in place of the glyph when the required icon font is not installed.
+    fn icon_or_text(
+        &self,
+        codepoint: char,
+        fallback: &str,
+        h: f64,
+        padding: (f64, f64),
+    ) -> Result<(f64, f64)> {
+        if self.font_has_char(codepoint)? {
+            return self.icon(codepoint, h, padding);
+        }
+
+        let (_, th) = self.text_extent(fallback)?;
+        self.text(fallback, ((h - th) / 2.0).max(0.0), padding)
+    }
+
+    /// Draw a rectangle filled with `fill` and outlined with a `line_width` border of `stroke`.
+    /// The border is inset so that it is drawn entirely within (x, y, w, h) rather than being
+    /// clipped by half of its width at the edges.
+    ///
+    /// The current color and line width are left set to `stroke` and `line_width`.
+    fn filled_outlined_rectangle(
+        &mut self,
+        x: f64,
+        y: f64,
+        w: f64,
+        h: f64,
+        fill: Color,
+        stroke: Color,
+        line_width: f64,
+    ) -> Result<()> {
+        self.color(&fill);
+        self.rectangle(x, y, w, h)?;
+
+        let (sx, sy, sw, sh) = inset_stroke_path(x, y, w, h, line_width);
+        self.color(&stroke);
+        self.set_line_width(line_width);
+        self.outline(sx, sy, sw, sh)
+    }
+
+    /// Replay the commands recorded in `list` on this context, in the order they were recorded.
+    ///
+    /// Recorded colors and fonts are applied as each command is replayed, so the current color
+    /// and font of this context may be changed.
+    fn replay(&mut self, list: &DisplayList) -> Result<()> {
+        list.replay_on(self)
+    }
+
+    /// Draw a vertical text cursor one pixel wide at `x`, running from `top` for `height` pixels.
+    /// The cursor is aligned to the device pixel grid so that it is rendered crisply. Blinking
+    /// the cursor is left to the caller.
+    ///
+    /// The current color is left set to `color`.
+    fn draw_cursor(&mut self, x: f64, top: f64, height: f64, color: Color) -> Result<()> {
+        self.color(&color);
+        self.rectangle(self.device_align(x), top, 1.0, height)
+    }
+
+    /// Fill the given region with a dithered pattern of `color`, approximating a translucent fill
+    /// of the given `alpha` by only filling a proportion of the pixels.
+    ///
+    /// This is a fallback for when no visual supporting an alpha channel is available (see
+    /// [Draw::best_visual]) and should not be used in place of real transparency. The default
+    /// implementation fills each pixel individually: implementations are encouraged to provide a
+    /// more efficient version.
+    fn fill_dithered(&mut self, region: Region, color: Color, alpha: f64) -> Result<()> {
+        let mask = dither_mask(alpha);
+        let (x, y, w, h) = region.values();
+        self.color(&color);
+
+        for py in y..y + h {
+            for px in x..x + w {
+                if mask[py as usize % 4][px as usize % 4] {
+                    self.rectangle(px as f64, py as f64, 1.0, 1.0)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Fill the given region with a light / dark checkerboard pattern.
+    ///
+    /// This is a development aid for visually confirming that translucent content is being
+    /// rendered with transparency: anything drawn over the top of the checkerboard with an alpha
+    /// value of less than 1.0 will let the pattern show through. Only available when the
+    /// `draw_debug` feature is enabled.
+    #[cfg(feature = "draw_debug")]
+    fn draw_transparency_check(&mut self, region: Region) -> Result<()> {
+        const TILE: u32 = 8;
+        let (light, dark) = (
+            Color::new_from_hex(0xCCCCCCFF),
+            Color::new_from_hex(0x888888FF),
+        );
+        let (x, y, w, h) = region.values();
+
+        for (row, ty) in (y..y + h).step_by(TILE as usize).enumerate() {
+            for (col, tx) in (x..x + w).step_by(TILE as usize).enumerate() {
+                self.color(if (row + col) % 2 == 0 { &light } else { &dark });
+                let tw = TILE.min(x + w - tx) as f64;
+                let th = TILE.min(y + h - ty) as f64;
+                self.rectangle(tx as f64, ty as f64, tw, th)?;
+            }
+        }
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -283,6 +1065,316 @@ mod tests {
         }
     }
 
+    test_cases! {
+        color_from_str;
+        args: (s: &str, expected: u32);
+
+        case: rgb => ("#1a1b26", 0x1a1b26ff);
+        case: rgb_upper => ("#1A1B26", 0x1a1b26ff);
+        case: rgb_no_hash => ("1a1b26", 0x1a1b26ff);
+        case: rgba => ("#1a1b2680", 0x1a1b2680);
+        case: rgba_mixed_case => ("#1A1b26Ff", 0x1a1b26ff);
+        case: rgba_no_hash => ("1a1b2680", 0x1a1b2680);
+
+        body: {
+            assert_eq!(s.parse::<Color>().unwrap().rgba_u32(), expected);
+        }
+    }
+
+    test_cases! {
+        invalid_hex_string;
+        args: (s: &str);
+
+        case: empty => ("");
+        case: hash_only => ("#");
+        case: too_short => ("#1a1b2");
+        case: too_long => ("#1a1b2680f");
+        case: between_lengths => ("1a1b268");
+        case: non_hex => ("#1a1b2g");
+        case: sign => ("+1a1b26");
+        case: double_hash => ("##1a1b26");
+
+        body: {
+            assert!(matches!(s.parse::<Color>(), Err(DrawError::InvalidHexColor(_))));
+        }
+    }
+
+    test_cases! {
+        to_hex_string_round_trips;
+        args: (s: &str);
+
+        case: opaque => ("#1a1b26");
+        case: translucent => ("#1a1b2680");
+        case: transparent_black => ("#00000000");
+        case: leading_zeros => ("#000a0b");
+
+        body: {
+            let c: Color = s.parse().unwrap();
+            assert_eq!(c.to_hex_string(), s);
+            assert_eq!(c.to_hex_string().parse::<Color>().unwrap(), c);
+        }
+    }
+
+    test_cases! {
+        color_from_rgb_function;
+        args: (s: &str, floats: (f64, f64, f64, f64));
+
+        case: rgb => ("rgb(255, 0, 255)", (1.0, 0.0, 1.0, 1.0));
+        case: rgb_no_spaces => ("rgb(255,0,255)", (1.0, 0.0, 1.0, 1.0));
+        case: rgb_extra_spaces => ("  rgb ( 255 ,0,  255 ) ", (1.0, 0.0, 1.0, 1.0));
+        case: rgba => ("rgba(0, 255, 0, 0.5)", (0.0, 1.0, 0.0, 0.5));
+        case: rgba_no_spaces => ("rgba(0,255,0,1)", (0.0, 1.0, 0.0, 1.0));
+        case: rgba_extra_spaces => (" rgba(  0,255 , 0,0 )", (0.0, 1.0, 0.0, 0.0));
+
+        body: {
+            assert_eq!(Color::try_from(s).unwrap(), Color::from(floats));
+        }
+    }
+
+    test_cases! {
+        scale_brightness;
+        args: (factor: f64, expected: (f64, f64, f64, f64));
+
+        case: unchanged => (1.0, (0.5, 0.25, 1.0, 0.5));
+        case: halved => (0.5, (0.25, 0.125, 0.5, 0.5));
+        case: black => (0.0, (0.0, 0.0, 0.0, 0.5));
+        case: clamped => (4.0, (1.0, 1.0, 1.0, 0.5));
+
+        body: {
+            let c = Color::from((0.5, 0.25, 1.0, 0.5));
+            assert_eq!(c.scale_brightness(factor), Color::from(expected));
+        }
+    }
+
+    test_cases! {
+        lerp;
+        args: (t: f64, expected: (f64, f64, f64, f64));
+
+        case: start => (0.0, (0.0, 0.0, 1.0, 1.0));
+        case: mid => (0.5, (0.5, 0.25, 0.5, 0.5));
+        case: end => (1.0, (1.0, 0.5, 0.0, 0.0));
+        case: clamped => (2.0, (1.0, 0.5, 0.0, 0.0));
+
+        body: {
+            let (from, to) = (Color::from((0.0, 0.0, 1.0, 1.0)), Color::from((1.0, 0.5, 0.0, 0.0)));
+            assert_eq!(from.lerp(&to, t), Color::from(expected));
+        }
+    }
+
+    test_cases! {
+        with_alpha;
+        args: (a: f64, expected: f64);
+
+        case: transparent => (0.0, 0.0);
+        case: translucent => (0.3, 0.3);
+        case: opaque => (1.0, 1.0);
+        case: clamped_below => (-0.5, 0.0);
+        case: clamped_above => (1.5, 1.0);
+
+        body: {
+            let c = Color::from((0.5, 0.25, 1.0, 0.5)).with_alpha(a);
+            assert_eq!(c.rgba(), (0.5, 0.25, 1.0, expected));
+        }
+    }
+
+    test_cases! {
+        new_from_hex_alpha;
+        args: (hex: u32, expected: (f64, f64, f64, f64));
+
+        case: opaque => (0xFFFF0000, (1.0, 0.0, 0.0, 1.0));
+        case: transparent => (0x0000FF00, (0.0, 1.0, 0.0, 0.0));
+        case: translucent => (0x800000FF, (0.0, 0.0, 1.0, 128.0 / 255.0));
+
+        body: {
+            assert_eq!(Color::new_from_hex_alpha(hex).rgba(), expected);
+        }
+    }
+
+    test_cases! {
+        from_hsl;
+        args: (h: f64, s: f64, l: f64, expected: u32);
+
+        case: red => (0.0, 1.0, 0.5, 0xFF0000);
+        case: green => (120.0, 1.0, 0.5, 0x00FF00);
+        case: blue => (240.0, 1.0, 0.5, 0x0000FF);
+        case: wraps_hue => (480.0, 1.0, 0.5, 0x00FF00);
+        case: grey => (200.0, 0.0, 0.5, 0x7F7F7F);
+        case: white => (0.0, 1.0, 1.0, 0xFFFFFF);
+
+        body: {
+            assert_eq!(Color::from_hsl(h, s, l).rgb_u32(), expected);
+        }
+    }
+
+    #[test]
+    fn from_hash_is_deterministic() {
+        for s in ["work", "web", "1", ""] {
+            assert_eq!(Color::from_hash(s), Color::from_hash(s));
+        }
+        assert_ne!(Color::from_hash("work"), Color::from_hash("web"));
+    }
+
+    #[test]
+    fn from_hash_is_stable() {
+        // FNV-1a("work") = 0x5b98d260 and 0x5b98d260 % 360 = 264
+        assert_eq!(
+            Color::from_hash("work"),
+            Color::from_hsl(264.0, HASH_SATURATION, HASH_LIGHTNESS)
+        );
+    }
+
+    test_cases! {
+        luminance;
+        args: (color: u32, expected: f64);
+
+        case: black => (0x000000FF, 0.0);
+        case: white => (0xFFFFFFFF, 1.0);
+        case: ignores_alpha => (0xFFFFFF00, 1.0);
+        case: pure_green => (0x00FF00FF, 0.7152);
+
+        body: {
+            let l = Color::from(color).luminance();
+            assert!((l - expected).abs() < 1e-9, "{} != {}", l, expected);
+        }
+    }
+
+    test_cases! {
+        contrast_ratio;
+        args: (a: u32, b: u32, expected: f64);
+
+        case: black_on_white => (0x000000FF, 0xFFFFFFFF, 21.0);
+        case: white_on_black => (0xFFFFFFFF, 0x000000FF, 21.0);
+        case: same_color => (0x458588FF, 0x458588FF, 1.0);
+
+        body: {
+            let ratio = Color::from(a).contrast_ratio(&Color::from(b));
+            assert!((ratio - expected).abs() < 0.01, "{} != {}", ratio, expected);
+        }
+    }
+
+    test_cases! {
+        ensure_contrast;
+        args: (fg: u32, bg: u32, min_ratio: f64);
+
+        case: grey_on_grey => (0x777777FF, 0x666666FF, 4.5);
+        case: dark_on_dark => (0x282828FF, 0x3C3836FF, 4.5);
+        case: light_on_light => (0xEBDBB2FF, 0xFFFFFFFF, 3.0);
+        case: already_readable => (0xEBDBB2FF, 0x282828FF, 4.5);
+
+        body: {
+            let (fg, bg) = (Color::from(fg), Color::from(bg));
+            let adjusted = fg.ensure_contrast(&bg, min_ratio);
+            assert!(adjusted.contrast_ratio(&bg) >= min_ratio);
+            if fg.contrast_ratio(&bg) >= min_ratio {
+                assert_eq!(adjusted, fg);
+            }
+        }
+    }
+
+    #[test]
+    fn ensure_contrast_falls_back_to_black_or_white() {
+        let bg = Color::from(0x777777FF);
+        let adjusted = Color::from(0x787878FF).ensure_contrast(&bg, 21.0);
+
+        assert_eq!(adjusted, Color::from(0x000000FF));
+    }
+
+    test_cases! {
+        dither_mask;
+        args: (alpha: f64, expected: usize);
+
+        case: transparent => (0.0, 0);
+        case: quarter => (0.25, 4);
+        case: half => (0.5, 8);
+        case: opaque => (1.0, 16);
+        case: clamped => (1.5, 16);
+
+        body: {
+            let filled = dither_mask(alpha).iter().flatten().filter(|&&b| b).count();
+            assert_eq!(filled, expected);
+        }
+    }
+
+    #[test]
+    fn dither_mask_half_is_a_checkerboard() {
+        let mask = dither_mask(0.5);
+        for (y, row) in mask.iter().enumerate() {
+            for (x, filled) in row.iter().enumerate() {
+                assert_eq!(*filled, (x + y) % 2 == 0, "({}, {})", x, y);
+            }
+        }
+    }
+
+    test_cases! {
+        region_on_screen;
+        args: (ix: usize, expected: Option<Region>);
+
+        case: first_screen => (0, Some(Region::new(10, 20, 100, 50)));
+        case: second_screen => (1, Some(Region::new(1930, 20, 100, 50)));
+        case: out_of_range => (2, None);
+
+        body: {
+            let screens = [Region::new(0, 0, 1920, 1080), Region::new(1920, 0, 1920, 1080)];
+            let res = region_on_screen(Region::new(10, 20, 100, 50), &screens, ix);
+            match expected {
+                Some(r) => assert_eq!(res.unwrap(), r),
+                None => assert!(matches!(res, Err(DrawError::UnknownScreen(2, 1)))),
+            }
+        }
+    }
+
+    test_cases! {
+        state_change_from_property;
+        args: (atom: &str, expected: Option<StateChange>);
+
+        case: active_window => ("_NET_ACTIVE_WINDOW", Some(StateChange::ActiveWindow));
+        case: current_desktop => ("_NET_CURRENT_DESKTOP", Some(StateChange::CurrentDesktop));
+        case: other_ewmh => ("_NET_CLIENT_LIST", None);
+        case: unknown => ("NOT_AN_ATOM", None);
+
+        body: {
+            assert_eq!(StateChange::from_property(atom), expected);
+        }
+    }
+
+    test_cases! {
+        inset_stroke_path;
+        args: (line_width: f64, expected: (f64, f64, f64, f64));
+
+        case: one_px => (1.0, (10.5, 20.5, 99.0, 49.0));
+        case: two_px => (2.0, (11.0, 21.0, 98.0, 48.0));
+        case: zero_width => (0.0, (10.0, 20.0, 100.0, 50.0));
+        case: wider_than_rect => (80.0, (35.0, 45.0, 50.0, 0.0));
+
+        body: {
+            let (x, y, w, h) = inset_stroke_path(10.0, 20.0, 100.0, 50.0, line_width);
+            assert_eq!((x, y, w, h), expected);
+
+            // The outer edge of the stroke never spills outside of the original rect
+            let half = line_width.min(50.0) / 2.0;
+            assert!(x - half >= 10.0 && y - half >= 20.0);
+            assert!(x + w + half <= 110.0 && y + h + half <= 70.0);
+        }
+    }
+
+    test_cases! {
+        invalid_rgb_function;
+        args: (s: &str);
+
+        case: channel_too_large => ("rgb(256, 0, 0)");
+        case: negative_channel => ("rgb(-1, 0, 0)");
+        case: non_numeric_channel => ("rgb(a, b, c)");
+        case: too_few_values => ("rgb(1, 2)");
+        case: alpha_missing => ("rgba(1, 2, 3)");
+        case: alpha_too_large => ("rgba(1, 2, 3, 1.5)");
+        case: alpha_for_rgb => ("rgb(1, 2, 3, 0.5)");
+        case: unclosed => ("rgb(1, 2, 3");
+
+        body: {
+            assert!(Color::try_from(s).is_err());
+        }
+    }
+
     test_cases! {
         color_rgb_u32;
         args: (s: &str, expected: u32);
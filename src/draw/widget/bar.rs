@@ -7,15 +7,32 @@ use crate::{
         ring::Selector,
         xconnection::{XConn, Xid},
     },
-    draw::{widget::Text, Color, DrawContext, Result, TextStyle, Widget},
+    draw::{widget::Text, ClickTarget, Color, DrawContext, Result, TextStyle, Widget},
 };
 
+#[cfg(feature = "chrono")]
+use std::time::Duration;
+
 const PADDING: f64 = 3.0;
+const INDICATOR_THICKNESS: f64 = 2.0;
+
+/// How the active workspace should be marked by a [Workspaces] widget
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum IndicatorStyle {
+    /// Fill the background of the active workspace with the highlight color
+    #[default]
+    Background,
+    /// Draw a line in the highlight color along the bottom of the active workspace
+    Underline,
+    /// Draw an outline in the highlight color around the active workspace
+    Box,
+}
 
 #[derive(Clone, Debug, PartialEq)]
 struct WSMeta {
     name: String,
     occupied: bool,
+    urgent: bool,
     extent: (f64, f64),
 }
 
@@ -25,6 +42,7 @@ fn meta_from_names(names: &[String]) -> Vec<WSMeta> {
         .map(|s| WSMeta {
             name: s.clone(),
             occupied: false,
+            urgent: false,
             extent: (0.0, 0.0),
         })
         .collect()
@@ -43,6 +61,10 @@ pub struct Workspaces {
     fg_2: Color,
     bg_1: Color,
     bg_2: Color,
+    occupied_fg: Color,
+    urgent_bg: Color,
+    indicator: IndicatorStyle,
+    click_regions: Vec<(Region, ClickTarget)>,
 }
 
 impl Workspaces {
@@ -52,6 +74,31 @@ impl Workspaces {
         style: &TextStyle,
         highlight: impl Into<Color>,
         empty_fg: impl Into<Color>,
+    ) -> Self {
+        let highlight = highlight.into();
+
+        Self::new_with_colors(
+            workspace_names,
+            style,
+            highlight,
+            style.fg,
+            empty_fg,
+            highlight,
+        )
+    }
+
+    /// Construct a new WorkspaceWidget with distinct colors for each workspace state:
+    ///   - `focused`: the background of the focused workspace
+    ///   - `occupied`: the text of unfocused workspaces that contain clients
+    ///   - `empty`: the text of unfocused workspaces with no clients
+    ///   - `urgent`: the background of workspaces with a client demanding attention
+    pub fn new_with_colors(
+        workspace_names: &[String],
+        style: &TextStyle,
+        focused: impl Into<Color>,
+        occupied: impl Into<Color>,
+        empty: impl Into<Color>,
+        urgent: impl Into<Color>,
     ) -> Self {
         Self {
             workspaces: meta_from_names(workspace_names),
@@ -61,9 +108,51 @@ impl Workspaces {
             require_draw: false,
             extent: None,
             fg_1: style.fg,
-            fg_2: empty_fg.into(),
-            bg_1: highlight.into(),
+            fg_2: empty.into(),
+            bg_1: focused.into(),
             bg_2: style.bg.unwrap_or_else(|| 0x000000.into()),
+            occupied_fg: occupied.into(),
+            urgent_bg: urgent.into(),
+            indicator: IndicatorStyle::default(),
+            click_regions: vec![],
+        }
+    }
+
+    /// Set how the active workspace is marked (defaults to [IndicatorStyle::Background])
+    pub fn set_indicator_style(&mut self, indicator: IndicatorStyle) {
+        self.indicator = indicator;
+        self.require_draw = true;
+    }
+
+    /// Set the text color used for unfocused workspaces that contain clients (defaults to the
+    /// foreground color of the style the widget was created with)
+    pub fn set_occupied_color(&mut self, color: impl Into<Color>) {
+        self.occupied_fg = color.into();
+        self.require_draw = true;
+    }
+
+    /// Set the background color used for workspaces containing a client that is requesting the
+    /// user's attention (defaults to the highlight color)
+    pub fn set_urgent_color(&mut self, color: impl Into<Color>) {
+        self.urgent_bg = color.into();
+        self.require_draw = true;
+    }
+
+    fn is_active(&self, ix: usize, screen: usize, screen_has_focus: bool) -> bool {
+        screen_has_focus && self.focused_ws.get(screen) == Some(&ix)
+    }
+
+    fn draw_indicator(&self, ctx: &mut dyn DrawContext, w: f64, h: f64) -> Result<()> {
+        let t = INDICATOR_THICKNESS;
+        match self.indicator {
+            IndicatorStyle::Background => ctx.rectangle(0.0, 0.0, w, h),
+            IndicatorStyle::Underline => ctx.rectangle(0.0, h - t, w, t),
+            IndicatorStyle::Box => {
+                ctx.rectangle(0.0, 0.0, w, t)?;
+                ctx.rectangle(0.0, h - t, w, t)?;
+                ctx.rectangle(0.0, 0.0, t, h)?;
+                ctx.rectangle(w - t, 0.0, t, h)
+            }
         }
     }
 
@@ -87,12 +176,29 @@ impl Workspaces {
         }
     }
 
+    fn update_workspace_urgent<X: XConn>(&mut self, wm: &WindowManager<X>) {
+        let urgent: Vec<usize> = wm
+            .urgent_windows()
+            .into_iter()
+            .flat_map(|id| wm.client(&Selector::WinId(id)).map(|c| c.workspace()))
+            .collect();
+
+        for (ix, ws) in self.workspaces.iter_mut().enumerate() {
+            let now_urgent = urgent.contains(&ix);
+            if ws.urgent != now_urgent {
+                self.require_draw = true;
+                ws.urgent = now_urgent;
+            }
+        }
+    }
+
     fn ws_colors(
         &self,
         ix: usize,
         screen: usize,
         screen_has_focus: bool,
         occupied: bool,
+        urgent: bool,
     ) -> (&Color, Option<&Color>) {
         let focused_here = match self.focused_ws.get(screen) {
             Some(&ws) => ix == ws,
@@ -104,6 +210,8 @@ impl Workspaces {
         if focused_here && screen_has_focus {
             let fg = if occupied { &self.fg_1 } else { &self.fg_2 };
             (fg, Some(&self.bg_1))
+        } else if urgent {
+            (&self.fg_1, Some(&self.urgent_bg))
         } else if focused {
             let fg = if focused_other {
                 &self.bg_1
@@ -112,7 +220,11 @@ impl Workspaces {
             };
             (fg, Some(&self.fg_2))
         } else {
-            let fg = if occupied { &self.fg_1 } else { &self.fg_2 };
+            let fg = if occupied {
+                &self.occupied_fg
+            } else {
+                &self.fg_2
+            };
             (fg, None)
         }
     }
@@ -216,6 +328,13 @@ where
         Ok(())
     }
 
+    fn event_handled(&mut self, wm: &mut WindowManager<X>) -> crate::Result<()> {
+        // Urgency is tracked by the WindowManager so this does not need to query the X server
+        self.update_workspace_urgent(wm);
+
+        Ok(())
+    }
+
     fn startup(&mut self, wm: &mut WindowManager<X>) -> crate::Result<()> {
         // NOTE: Following initial workspace placement from WindowManager<X>
         self.update_workspace_occupied(wm);
@@ -239,17 +358,27 @@ impl Widget for Workspaces {
         ctx.font(&self.font, self.point_size)?;
         ctx.translate(PADDING, 0.0);
         let (_, eh) = self.extent.unwrap();
+        let mut x = PADDING;
+        self.click_regions.clear();
 
         for (i, ws) in self.workspaces.iter().enumerate() {
-            let (fg, bg) = self.ws_colors(i, screen, screen_has_focus, ws.occupied);
+            let (fg, bg) = self.ws_colors(i, screen, screen_has_focus, ws.occupied, ws.urgent);
             if let Some(c) = bg {
                 ctx.color(c);
-                ctx.rectangle(0.0, 0.0, ws.extent.0, h)?;
+                if self.is_active(i, screen, screen_has_focus) {
+                    self.draw_indicator(ctx, ws.extent.0, h)?;
+                } else {
+                    ctx.rectangle(0.0, 0.0, ws.extent.0, h)?;
+                }
             }
 
             ctx.color(fg);
             ctx.text(&ws.name, h - eh, (PADDING, PADDING))?;
             ctx.translate(ws.extent.0, 0.0);
+
+            let r = Region::new(x as u32, 0, ws.extent.0 as u32, h as u32);
+            self.click_regions.push((r, ClickTarget::Workspace(i)));
+            x += ws.extent.0;
         }
 
         self.require_draw = false;
@@ -285,6 +414,10 @@ impl Widget for Workspaces {
     fn is_greedy(&self) -> bool {
         false
     }
+
+    fn click_regions(&self) -> Vec<(Region, ClickTarget)> {
+        self.click_regions.clone()
+    }
 }
 
 /// A text widget that is set via updating the root window name a la dwm
@@ -506,3 +639,148 @@ impl Widget for CurrentLayout {
         false
     }
 }
+
+/// A widget that displays the current local time using a [chrono format string][1].
+///
+/// The time is updated once a second by default when the widget is refreshed by
+/// [StatusBar::refresh][crate::draw::StatusBar::refresh]. Only available when the `chrono`
+/// feature is enabled.
+///
+/// [1]: https://docs.rs/chrono/latest/chrono/format/strftime/index.html
+#[cfg(feature = "chrono")]
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClockWidget {
+    txt: Text,
+    format: String,
+    interval: Duration,
+}
+
+#[cfg(feature = "chrono")]
+impl ClockWidget {
+    /// Create a new ClockWidget showing the current time formatted using `format`
+    pub fn new(format: impl Into<String>, style: &TextStyle) -> Self {
+        let mut clock = Self {
+            txt: Text::new("", style, false, false),
+            format: format.into(),
+            interval: Duration::from_secs(1),
+        };
+        clock.update();
+
+        clock
+    }
+
+    /// Set how often the displayed time is updated.
+    ///
+    /// Defaults to once a second: clocks that only show hours and minutes can safely use a
+    /// longer interval.
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+}
+
+// Invalid format strings are shown as is rather than panicking when formatted
+#[cfg(feature = "chrono")]
+fn format_time<Tz>(t: &chrono::DateTime<Tz>, format: &str) -> String
+where
+    Tz: chrono::TimeZone,
+    Tz::Offset: std::fmt::Display,
+{
+    use std::fmt::Write;
+
+    let mut s = String::new();
+    match write!(s, "{}", t.format(format)) {
+        Ok(_) => s,
+        Err(_) => format.to_string(),
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl<X> Hook<X> for ClockWidget where X: XConn {}
+
+#[cfg(feature = "chrono")]
+impl Widget for ClockWidget {
+    fn draw(&mut self, ctx: &mut dyn DrawContext, s: usize, f: bool, w: f64, h: f64) -> Result<()> {
+        self.txt.draw(ctx, s, f, w, h)
+    }
+
+    fn current_extent(&mut self, ctx: &mut dyn DrawContext, h: f64) -> Result<(f64, f64)> {
+        self.txt.current_extent(ctx, h)
+    }
+
+    fn require_draw(&self) -> bool {
+        self.txt.require_draw()
+    }
+
+    fn is_greedy(&self) -> bool {
+        false
+    }
+
+    fn refresh_interval(&self) -> Option<Duration> {
+        Some(self.interval)
+    }
+
+    fn update(&mut self) {
+        self.txt
+            .set_text(format_time(&chrono::Local::now(), &self.format));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_workspaces() -> Workspaces {
+        let style = TextStyle {
+            font: "mono".into(),
+            point_size: 10,
+            fg: 0xffffffff.into(),
+            bg: Some(0x000000ff.into()),
+            padding: (0.0, 0.0),
+        };
+        let names: Vec<String> = ["1", "2", "3"].iter().map(|s| s.to_string()).collect();
+        let mut ws = Workspaces::new_with_colors(
+            &names, &style, 0x0000ffff, 0x00ff00ff, 0x808080ff, 0xff0000ff,
+        );
+        ws.focused_ws = vec![0];
+
+        ws
+    }
+
+    test_cases! {
+        ws_colors;
+        args: (ix: usize, occupied: bool, urgent: bool, expected: (u32, Option<u32>));
+
+        case: focused => (0, true, false, (0xffffffff, Some(0x0000ffff)));
+        case: occupied => (1, true, false, (0x00ff00ff, None));
+        case: empty => (1, false, false, (0x808080ff, None));
+        case: urgent => (2, true, true, (0xffffffff, Some(0xff0000ff)));
+
+        body: {
+            let ws = test_workspaces();
+            let (fg, bg) = ws.ws_colors(ix, 0, true, occupied, urgent);
+            let (efg, ebg) = expected;
+
+            assert_eq!(*fg, Color::from(efg));
+            assert_eq!(bg.copied(), ebg.map(Color::from));
+        }
+    }
+
+    #[cfg(feature = "chrono")]
+    use chrono::{TimeZone, Utc};
+
+    #[cfg(feature = "chrono")]
+    test_cases! {
+        format_time;
+        args: (format: &str, expected: &str);
+
+        case: time => ("%H:%M:%S", "13:05:09");
+        case: date => ("%Y-%m-%d", "2021-03-04");
+        case: literal => ("it is %H o'clock", "it is 13 o'clock");
+        case: invalid => ("%Q", "%Q");
+
+        body: {
+            let t = Utc.with_ymd_and_hms(2021, 3, 4, 13, 5, 9).unwrap();
+            assert_eq!(format_time(&t, format), expected);
+        }
+    }
+}
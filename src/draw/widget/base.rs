@@ -90,6 +90,11 @@ impl Widget for Text {
             ctx.translate(offset, 0.0);
             ctx.text(&self.txt, h - eh, self.padding)?;
             ctx.translate(-offset, 0.0);
+        } else if offset < 0.0 {
+            // We have been given less space than we asked for so ellipsize rather than overflow
+            ctx.translate(0.0, h - eh);
+            ctx.text_clamped(&self.txt, w, 1, self.padding)?;
+            ctx.translate(0.0, eh - h);
         } else {
             ctx.text(&self.txt, h - eh, self.padding)?;
         }
@@ -121,6 +126,97 @@ impl Widget for Text {
     }
 }
 
+/// An empty, greedy widget that expands to push the widgets either side of it apart.
+///
+/// Remaining space in a [StatusBar][crate::draw::StatusBar] is split between all greedy widgets in
+/// proportion to their weight, so a [FlexSpacer] with a weight of 2.0 will grow twice as much as
+/// one with a weight of 1.0. Nothing is rendered in the space taken up by the spacer, leaving the
+/// background of the status bar visible.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FlexSpacer {
+    weight: f64,
+}
+
+impl FlexSpacer {
+    /// Construct a new [FlexSpacer] with the given weight
+    pub fn new(weight: f64) -> Self {
+        Self {
+            weight: weight.max(0.0),
+        }
+    }
+}
+
+impl Default for FlexSpacer {
+    fn default() -> Self {
+        Self::new(1.0)
+    }
+}
+
+impl<X> Hook<X> for FlexSpacer where X: XConn {}
+
+impl Widget for FlexSpacer {
+    fn draw(&mut self, _: &mut dyn DrawContext, _: usize, _: bool, _: f64, _: f64) -> Result<()> {
+        Ok(())
+    }
+
+    fn current_extent(&mut self, _: &mut dyn DrawContext, h: f64) -> Result<(f64, f64)> {
+        Ok((0.0, h))
+    }
+
+    fn require_draw(&self) -> bool {
+        false
+    }
+
+    fn is_greedy(&self) -> bool {
+        true
+    }
+
+    fn greedy_weight(&self) -> f64 {
+        self.weight
+    }
+}
+
+/// A thin vertical line for visually separating adjacent widgets.
+///
+/// The line is snapped to device pixels using [DrawContext::device_align] so that it renders
+/// crisply regardless of the position of the widget within the bar.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Separator {
+    color: Color,
+    padding: f64,
+}
+
+impl Separator {
+    /// Construct a new [Separator] with the given amount of padding either side of the line
+    pub fn new(color: impl Into<Color>, padding: f64) -> Self {
+        Self {
+            color: color.into(),
+            padding: padding.max(0.0),
+        }
+    }
+}
+
+impl<X> Hook<X> for Separator where X: XConn {}
+
+impl Widget for Separator {
+    fn draw(&mut self, ctx: &mut dyn DrawContext, _: usize, _: bool, _: f64, h: f64) -> Result<()> {
+        ctx.color(&self.color);
+        ctx.rectangle(ctx.device_align(self.padding), 0.0, 1.0, h)
+    }
+
+    fn current_extent(&mut self, _: &mut dyn DrawContext, h: f64) -> Result<(f64, f64)> {
+        Ok((2.0 * self.padding + 1.0, h))
+    }
+
+    fn require_draw(&self) -> bool {
+        false
+    }
+
+    fn is_greedy(&self) -> bool {
+        false
+    }
+}
+
 /// A set of lines that highlights the currently selected line.
 #[derive(Clone, Debug, PartialEq)]
 pub struct LinesWithSelection {
@@ -318,10 +414,19 @@ impl KeyboardControlled for LinesWithSelection {
     }
 }
 
+// The byte offset of the char at 'cursor' in 's' (or the length of 's' if past the end)
+fn byte_index(s: &str, cursor: usize) -> usize {
+    s.char_indices().nth(cursor).map_or(s.len(), |(i, _)| i)
+}
+
 /// A simple text box that can be driven by user keyboard input
+///
+/// Text is inserted and deleted at a cursor which can be moved using the Left and Right keys.
 #[derive(Clone, Debug, PartialEq)]
 pub struct InputBox {
     txt: Text,
+    cursor: usize,
+    show_cursor: bool,
 }
 
 impl InputBox {
@@ -329,6 +434,22 @@ impl InputBox {
     pub fn new(style: &TextStyle, is_greedy: bool, right_justified: bool) -> Self {
         Self {
             txt: Text::new("", style, is_greedy, right_justified),
+            cursor: 0,
+            show_cursor: true,
+        }
+    }
+
+    /// The position of the cursor as a number of chars from the start of the text.
+    pub fn cursor_position(&self) -> usize {
+        self.cursor.min(self.get_text().chars().count())
+    }
+
+    /// Show or hide the cursor, triggering a redraw if this changes its visibility. Toggling
+    /// this on a timer gives a blinking cursor.
+    pub fn set_cursor_visible(&mut self, visible: bool) {
+        if self.show_cursor != visible {
+            self.show_cursor = visible;
+            self.txt.force_draw();
         }
     }
 
@@ -342,9 +463,19 @@ impl InputBox {
         self.txt.get_text_mut()
     }
 
-    /// Set the rendered text and trigger a redraw
+    /// Set the rendered text and trigger a redraw. The cursor is moved to the end of the text.
     pub fn set_text(&mut self, txt: impl Into<String>) {
         self.txt.set_text(txt);
+        self.cursor = self.get_text().chars().count();
+    }
+
+    // Edit the text at the byte offset of the char at 'at', replacing it rather than editing in
+    // place so that the cached extent is reset
+    fn edit_text(&mut self, at: usize, f: impl FnOnce(&mut String, usize)) {
+        let mut s = self.get_text().clone();
+        let ix = byte_index(&s, at);
+        f(&mut s, ix);
+        self.txt.set_text(s);
     }
 }
 
@@ -352,7 +483,20 @@ impl<X> Hook<X> for InputBox where X: XConn {}
 
 impl Widget for InputBox {
     fn draw(&mut self, ctx: &mut dyn DrawContext, s: usize, f: bool, w: f64, h: f64) -> Result<()> {
-        self.txt.draw(ctx, s, f, w, h)
+        self.txt.draw(ctx, s, f, w, h)?;
+        if !self.show_cursor {
+            return Ok(());
+        }
+
+        let (ew, eh) = self.txt.current_extent(ctx, h)?;
+        let txt = self.get_text();
+        let (before, _) = ctx.text_extent(&txt[..byte_index(txt, self.cursor_position())])?;
+
+        let offset = w - ew;
+        let right_justify = self.txt.right_justified && self.txt.is_greedy && offset > 0.0;
+        let x = self.txt.padding.0 + before + if right_justify { offset } else { 0.0 };
+
+        ctx.draw_cursor(x, h - eh, eh, self.txt.fg)
     }
 
     fn current_extent(&mut self, ctx: &mut dyn DrawContext, h: f64) -> Result<(f64, f64)> {
@@ -370,17 +514,87 @@ impl Widget for InputBox {
 
 impl KeyboardControlled for InputBox {
     fn handle_keypress(&mut self, k: KeyPress) -> Result<Option<KeyPress>> {
+        let (cursor, len) = (self.cursor_position(), self.get_text().chars().count());
+
         match k {
-            KeyPress::Backspace => {
-                let s = self.get_text_mut();
-                if !s.is_empty() {
-                    s.pop();
-                }
+            KeyPress::Backspace if cursor > 0 => {
+                self.edit_text(cursor - 1, |s, ix| {
+                    s.remove(ix);
+                });
+                self.cursor = cursor - 1;
+            }
+            KeyPress::Delete if cursor < len => self.edit_text(cursor, |s, ix| {
+                s.remove(ix);
+            }),
+            KeyPress::Backspace | KeyPress::Delete => return Ok(None),
+            KeyPress::Left => self.cursor = cursor.saturating_sub(1),
+            KeyPress::Right => self.cursor = (cursor + 1).min(len),
+            KeyPress::Utf8(c) => {
+                self.edit_text(cursor, |s, ix| s.insert_str(ix, &c));
+                self.cursor = cursor + c.chars().count();
             }
-            KeyPress::Utf8(c) => self.get_text_mut().push_str(&c),
             _ => return Ok(Some(k)),
         }
 
+        self.txt.force_draw();
         Ok(None)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input_box(txt: &str) -> InputBox {
+        let style = TextStyle {
+            font: "mono".into(),
+            point_size: 12,
+            fg: 0xffffffff.into(),
+            bg: None,
+            padding: (0.0, 0.0),
+        };
+        let mut ib = InputBox::new(&style, false, false);
+        ib.set_text(txt);
+
+        ib
+    }
+
+    test_cases! {
+        input_box_editing;
+        args: (keys: Vec<KeyPress>, expected: &str, cursor: usize);
+
+        case: insert_at_end => (vec![KeyPress::Utf8("d".into())], "abcd", 4);
+        case: insert_at_cursor => (
+            vec![KeyPress::Left, KeyPress::Utf8("xy".into())], "abxyc", 4
+        );
+        case: backspace_before_cursor => (vec![KeyPress::Left, KeyPress::Backspace], "ac", 1);
+        case: delete_at_cursor => (vec![KeyPress::Left, KeyPress::Delete], "ab", 2);
+        case: delete_at_end => (vec![KeyPress::Delete], "abc", 3);
+        case: backspace_at_start => (
+            vec![KeyPress::Left, KeyPress::Left, KeyPress::Left, KeyPress::Left, KeyPress::Backspace],
+            "abc",
+            0
+        );
+        case: right_stops_at_end => (vec![KeyPress::Right], "abc", 3);
+
+        body: {
+            let mut ib = input_box("abc");
+            for k in keys {
+                assert_eq!(ib.handle_keypress(k).unwrap(), None);
+            }
+
+            assert_eq!(ib.get_text(), expected);
+            assert_eq!(ib.cursor_position(), cursor);
+        }
+    }
+
+    #[test]
+    fn input_box_handles_multibyte_chars() {
+        let mut ib = input_box("añb");
+        ib.handle_keypress(KeyPress::Left).unwrap();
+        ib.handle_keypress(KeyPress::Backspace).unwrap();
+
+        assert_eq!(ib.get_text(), "ab");
+        assert_eq!(ib.cursor_position(), 1);
+    }
+}
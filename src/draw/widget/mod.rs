@@ -5,10 +5,17 @@
 //!
 //! [1]: crate::draw::Draw
 use crate::{
-    core::{bindings::KeyPress, hooks::Hook, xconnection::XConn},
+    core::{
+        bindings::{KeyPress, MouseButton},
+        data_types::{Point, Region},
+        hooks::Hook,
+        xconnection::XConn,
+    },
     draw::{DrawContext, Result},
 };
 
+use std::time::Duration;
+
 pub mod bar;
 pub mod base;
 
@@ -17,6 +24,15 @@ pub use bar::*;
 #[doc(inline)]
 pub use base::*;
 
+/// An element within a [Widget] that can be independently clicked on
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ClickTarget {
+    /// A workspace, identified by its index
+    Workspace(usize),
+    /// A user defined target, identified by a string tag
+    Custom(String),
+}
+
 /// A status bar widget that can be rendered using a [DrawContext]
 pub trait Widget {
     /// Render the current state of the widget to the status bar window.
@@ -38,9 +54,65 @@ pub trait Widget {
     /**
      * If true, this widget will expand to fill remaining available space after layout has been
      * computed. If multiple greedy widgets are present in a given StatusBar then the available
-     * space will be split evenly between all widgets.
+     * space will be split between all widgets in proportion to their [Widget::greedy_weight].
      */
     fn is_greedy(&self) -> bool;
+
+    /// The relative share of remaining space that this widget should receive if it is greedy.
+    ///
+    /// Defaults to 1.0 so that multiple greedy widgets split the available space evenly.
+    fn greedy_weight(&self) -> f64 {
+        1.0
+    }
+
+    /// Space in pixels that should be left either side of this widget when it is placed in a
+    /// [StatusBar][crate::draw::StatusBar]. Margins are dropped at the outer edges of the bar.
+    ///
+    /// Defaults to no margin.
+    fn margin(&self) -> f64 {
+        0.0
+    }
+
+    /// Should this widget currently be shown?
+    ///
+    /// Hidden widgets are skipped entirely by [StatusBar][crate::draw::StatusBar]: they take up
+    /// no space, are not drawn and can not be clicked. Defaults to true.
+    fn is_visible(&self) -> bool {
+        true
+    }
+
+    /// The clickable sub-regions of this widget, relative to the top left corner of the widget
+    /// as of its last call to [Widget::draw].
+    ///
+    /// Defaults to no clickable regions.
+    fn click_regions(&self) -> Vec<(Region, ClickTarget)> {
+        vec![]
+    }
+
+    /// Called by [StatusBar::handle_mouse_event][crate::draw::StatusBar::handle_mouse_event]
+    /// when `button` is pressed over this widget and then again for each pointer motion event
+    /// while it is held, allowing for slider and scrub style widgets.
+    ///
+    /// `p` is relative to the top left corner of the widget: once a drag has started this
+    /// widget continues to receive motion events until the button is released, even if the
+    /// pointer leaves it, with `p` clamped to the bounds of the widget. Defaults to doing nothing.
+    fn on_drag(&mut self, _p: Point, _button: MouseButton) {}
+
+    /// How often this widget should be updated, for widgets whose content changes over time
+    /// (such as a clock) rather than in response to [Hook] calls.
+    ///
+    /// [StatusBar::refresh][crate::draw::StatusBar::refresh] calls [Widget::update] on each
+    /// widget whose interval has elapsed since it was last updated. Defaults to `None`, meaning
+    /// that the widget is never refreshed.
+    fn refresh_interval(&self) -> Option<Duration> {
+        None
+    }
+
+    /// Update the content of this widget, called according to [Widget::refresh_interval].
+    /// Widgets should only mark themselves as requiring a redraw if their content has changed.
+    ///
+    /// Defaults to doing nothing.
+    fn update(&mut self) {}
 }
 
 /**
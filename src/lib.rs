@@ -103,6 +103,25 @@
 //! **NOTE**: in order to use the xcb implementation of penrose, you will need to install the C
 //! libraries that are dependencies (namely xcb, Cairo and Pango).
 //!
+//! # Cargo features
+//!
+//! - `xcb` (default): an [XConn][8] implementation using the [XCB][14] api
+//! - `xcb_draw` (default): a [Draw][16] implementation for `xcb` using Cairo and Pango
+//! - `keysyms` (default): parsing of key bindings using X keysym names
+//! - `x11rb` / `x11rb-xcb`: an alternative [XConn][8] implementation using `x11rb`
+//! - `wayland`: an experimental [Draw][16] implementation for [Wayland][21] layer-shell
+//!   surfaces (rendering only: surfaces are not yet presented by the compositor)
+//! - `serde`: serialization of the core data types
+//! - `ipc`: control of a running window manager over a [Unix domain socket][crate::core::ipc]
+//! - `chrono`: a clock widget for the status bar
+//! - `draw_stats` / `draw_debug`: render timing and debug overlays for the status bar
+//!
+//! None of the X backends are required to build the rest of the crate: with
+//! `default-features = false` the `core` and `draw` modules (including the [ring][17] selection
+//! types, [Color][18], the [formatting helpers][19] and the [Draw][16], [Widget][20] and
+//! status bar layout logic) compile with no C dependencies, for use in headless tools or
+//! alternative backends.
+//!
 //! [1]: https://dwm.suckless.org/
 //! [2]: https://xmonad.org/
 //! [3]: http://www.qtile.org/
@@ -118,6 +137,12 @@
 //! [13]: https://crates.io/crates/simplelog
 //! [14]: https://xcb.freedesktop.org/
 //! [15]: https://www.rust-lang.org
+//! [16]: crate::draw::Draw
+//! [17]: crate::core::ring
+//! [18]: crate::draw::Color
+//! [19]: crate::draw::format
+//! [20]: crate::draw::Widget
+//! [21]: crate::wayland
 #![warn(
     clippy::complexity,
     clippy::correctness,
@@ -155,6 +180,9 @@ pub mod xcb;
 #[cfg(feature = "x11rb")]
 pub mod x11rb;
 
+#[cfg(feature = "wayland")]
+pub mod wayland;
+
 #[doc(hidden)]
 pub mod __test_helpers;
 
@@ -236,6 +264,10 @@ pub enum PenroseError {
     #[error("{0} is not a known client")]
     UnknownClient(Xid),
 
+    /// An attempt was made to reference a workspace index that is out of range
+    #[error("{0} is not a known workspace index")]
+    UnknownWorkspace(usize),
+
     /// A user specified key binding contained an invalid modifier key
     #[error("Unknown modifier key: {0}")]
     UnknownModifier(String),
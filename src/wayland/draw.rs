@@ -0,0 +1,313 @@
+/*!
+ * Experimental API layer implementing [Draw][crate::draw::Draw] and
+ * [DrawContext][crate::draw::DrawContext] for wlr-layer-shell surfaces using pango and cairo.
+ *
+ * Surfaces are rendered into client side image buffers: presenting them via the compositor is
+ * not yet implemented (see the [module level docs][crate::wayland] for details).
+ */
+use crate::{
+    core::{
+        data_types::{Region, WinType},
+        xconnection::{Atom, Xid},
+    },
+    draw::{
+        cairo_context::{load_font, PatternCache},
+        CairoDrawContext, Draw, Result, VisualInfo,
+    },
+    wayland::WaylandError,
+};
+
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+};
+
+/// The wlr-layer-shell layer that a [LayerSurface] is placed on, from bottom to top.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layer {
+    /// Below all regular windows, for wallpapers and desktop widgets
+    Background,
+    /// Below regular windows but above the background
+    Bottom,
+    /// Above regular windows, for status bars and docks
+    Top,
+    /// Above everything, including fullscreen windows, for menus and notifications
+    Overlay,
+}
+
+impl Layer {
+    // Layer surfaces have no window types so pick the layer that places them in the same
+    // position in the stack as the equivalent X window would be.
+    fn for_win_type(ty: &WinType) -> Result<Self> {
+        match ty {
+            WinType::CheckWin | WinType::InputOnly => {
+                Err(WaylandError::Unsupported("input only surfaces").into())
+            }
+            WinType::InputOutput(Atom::NetWindowTypeDesktop) => Ok(Self::Background),
+            WinType::InputOutput(Atom::NetWindowTypeDock) => Ok(Self::Top),
+            WinType::InputOutput(_) => Ok(Self::Overlay),
+        }
+    }
+}
+
+/// A layer-shell surface created by a [WaylandDraw] along with the buffer it is rendered into.
+#[derive(Debug)]
+pub struct LayerSurface {
+    layer: Layer,
+    region: Region,
+    buffer: cairo::ImageSurface,
+    flushes: Cell<usize>,
+}
+
+impl LayerSurface {
+    /// The layer that this surface is placed on
+    pub fn layer(&self) -> Layer {
+        self.layer
+    }
+
+    /// The requested position and size of this surface in global compositor coordinates
+    pub fn region(&self) -> Region {
+        self.region
+    }
+
+    /// The buffer that contexts for this surface draw into
+    pub fn buffer(&self) -> &cairo::ImageSurface {
+        &self.buffer
+    }
+
+    /// The number of times that pending drawing for this surface has been flushed
+    pub fn flushes(&self) -> usize {
+        self.flushes.get()
+    }
+}
+
+/// An experimental Wayland based [Draw] implementation backed by pango and cairo
+///
+/// Each window created by a `WaylandDraw` is a [LayerSurface]: the `managed` flag passed to
+/// [Draw::new_window] is ignored as layer surfaces are always positioned by the client rather
+/// than the compositor.
+#[derive(Debug)]
+pub struct WaylandDraw {
+    outputs: Vec<Region>,
+    fonts: HashMap<String, pango::FontDescription>,
+    surfaces: HashMap<Xid, LayerSurface>,
+    patterns: PatternCache,
+    next_id: Xid,
+}
+
+impl WaylandDraw {
+    /// Create a new empty [WaylandDraw] for a compositor with the given outputs, ordered in the
+    /// same way that they will be returned by [Draw::screen_sizes].
+    pub fn new(outputs: Vec<Region>) -> Self {
+        Self {
+            outputs,
+            fonts: HashMap::new(),
+            surfaces: HashMap::new(),
+            patterns: Rc::new(RefCell::new(HashMap::new())),
+            next_id: 1,
+        }
+    }
+
+    /// The [LayerSurface] with the given id, if one has been created
+    pub fn surface(&self, id: Xid) -> Option<&LayerSurface> {
+        self.surfaces.get(&id)
+    }
+
+    /// Destroy the [LayerSurface] with the given id, releasing its buffer.
+    pub fn destroy_surface(&mut self, id: Xid) -> Result<()> {
+        self.surfaces
+            .remove(&id)
+            .map(|_| ())
+            .ok_or_else(|| WaylandError::UnknownSurface(id).into())
+    }
+
+    fn context(&self, surface: &cairo::ImageSurface) -> Result<WaylandDrawContext> {
+        Ok(CairoDrawContext::new(
+            cairo::Context::new(surface)?,
+            self.fonts.clone(),
+            Rc::clone(&self.patterns),
+            vec![],
+        ))
+    }
+}
+
+impl Draw for WaylandDraw {
+    type Ctx = WaylandDrawContext;
+
+    fn new_window(&mut self, ty: WinType, r: Region, _managed: bool) -> Result<Xid> {
+        let layer = Layer::for_win_type(&ty)?;
+        let (_, _, w, h) = r.values();
+        let buffer = cairo::ImageSurface::create(cairo::Format::ARgb32, w as i32, h as i32)?;
+
+        let id = self.next_id;
+        self.next_id += 1;
+        self.surfaces.insert(
+            id,
+            LayerSurface {
+                layer,
+                region: r,
+                buffer,
+                flushes: Cell::new(0),
+            },
+        );
+
+        Ok(id)
+    }
+
+    fn screen_sizes(&self) -> Result<Vec<Region>> {
+        Ok(self.outputs.clone())
+    }
+
+    // Wayland has no visuals: buffers are always ARGB32
+    fn best_visual(&self, _screen_ix: usize, want_alpha: bool) -> Result<VisualInfo> {
+        Ok(VisualInfo {
+            id: 0,
+            depth: if want_alpha { 32 } else { 24 },
+        })
+    }
+
+    fn register_font(&mut self, font_name: &str) -> Result<()> {
+        self.fonts.insert(font_name.into(), load_font(font_name)?);
+
+        Ok(())
+    }
+
+    fn register_font_unchecked(&mut self, font_name: &str) {
+        self.fonts.insert(
+            font_name.into(),
+            pango::FontDescription::from_string(font_name),
+        );
+    }
+
+    fn context_for(&self, id: Xid) -> Result<Self::Ctx> {
+        let surface = self
+            .surfaces
+            .get(&id)
+            .ok_or(WaylandError::UnknownSurface(id))?;
+
+        self.context(&surface.buffer)
+    }
+
+    fn temp_context(&self, w: u32, h: u32) -> Result<Self::Ctx> {
+        let surface = cairo::ImageSurface::create(cairo::Format::ARgb32, w as i32, h as i32)?;
+
+        self.context(&surface)
+    }
+
+    // There is no compositor connection to attach and commit the buffer to, so this only ensures
+    // that all pending drawing has reached it.
+    fn flush(&self, id: Xid) -> Result<()> {
+        let surface = self
+            .surfaces
+            .get(&id)
+            .ok_or(WaylandError::UnknownSurface(id))?;
+
+        surface.buffer.flush();
+        surface.flushes.set(surface.flushes.get() + 1);
+
+        Ok(())
+    }
+}
+
+/// A cairo and pango backed [DrawContext][crate::draw::DrawContext] for drawing to a
+/// [LayerSurface]: see [CairoDrawContext] for details.
+pub type WaylandDrawContext = CairoDrawContext;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::draw::{Color, DrawContext, DrawError};
+
+    fn draw_with_output() -> WaylandDraw {
+        WaylandDraw::new(vec![Region::new(0, 0, 1366, 768)])
+    }
+
+    test_cases! {
+        layer_for_win_type;
+        args: (ty: WinType, expected: Option<Layer>);
+
+        case: check_win => (WinType::CheckWin, None);
+        case: input_only => (WinType::InputOnly, None);
+        case: desktop => (WinType::InputOutput(Atom::NetWindowTypeDesktop), Some(Layer::Background));
+        case: dock => (WinType::InputOutput(Atom::NetWindowTypeDock), Some(Layer::Top));
+        case: menu => (WinType::InputOutput(Atom::NetWindowTypeMenu), Some(Layer::Overlay));
+
+        body: {
+            let mut drw = draw_with_output();
+            let layer = drw
+                .new_window(ty, Region::new(0, 0, 100, 20), false)
+                .ok()
+                .and_then(|id| drw.surface(id).map(|s| s.layer()));
+
+            assert_eq!(layer, expected);
+        }
+    }
+
+    #[test]
+    fn new_window_allocates_a_buffer_matching_the_region() {
+        let mut drw = draw_with_output();
+        let r = Region::new(10, 20, 300, 25);
+        let id = drw
+            .new_window(WinType::InputOutput(Atom::NetWindowTypeDock), r, false)
+            .unwrap();
+        let surface = drw.surface(id).unwrap();
+
+        assert_eq!(surface.region(), r);
+        assert_eq!(
+            (surface.buffer().width(), surface.buffer().height()),
+            (300, 25)
+        );
+    }
+
+    #[test]
+    fn unknown_surfaces_are_an_error() {
+        let drw = draw_with_output();
+
+        assert!(matches!(
+            drw.context_for(42),
+            Err(DrawError::Wayland(WaylandError::UnknownSurface(42)))
+        ));
+        assert!(drw.flush(42).is_err());
+    }
+
+    #[test]
+    fn flush_is_tracked_per_surface() {
+        let mut drw = draw_with_output();
+        let id = drw
+            .new_window(
+                WinType::InputOutput(Atom::NetWindowTypeMenu),
+                Region::new(0, 0, 200, 50),
+                false,
+            )
+            .unwrap();
+
+        let mut ctx = drw.context_for(id).unwrap();
+        ctx.color(&Color::from(0xffffffff));
+        ctx.text("some text", 0.0, (0.0, 0.0)).unwrap();
+        drw.flush(id).unwrap();
+
+        assert_eq!(drw.surface(id).unwrap().flushes(), 1);
+    }
+
+    #[test]
+    fn generic_font_families_can_be_registered() {
+        let mut drw = draw_with_output();
+
+        assert!(drw.register_font("monospace").is_ok());
+        assert!(matches!(
+            drw.register_font("NotARealFont1234"),
+            Err(DrawError::FontNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn text_extent_matches_rendered_text() {
+        let drw = draw_with_output();
+        let ctx = drw.temp_context(200, 50).unwrap();
+        let (w, h) = ctx.text_extent("some text").unwrap();
+        let rendered = ctx.text_detailed("some text", 0.0, (3.0, 5.0)).unwrap();
+
+        assert_eq!((rendered.width, rendered.height), (w + 8.0, h));
+    }
+}
@@ -0,0 +1,45 @@
+//! Helpers and utilities for using Wayland as a back end for the penrose drawing traits
+//!
+//! **NOTE**: this backend is experimental and does not yet display anything. [WaylandDraw][1]
+//! implements [Draw][2] by rendering into client side [cairo][4] image buffers, one per
+//! [wlr-layer-shell][5] surface, and tracks the layer and region that each surface should be
+//! placed at. Drawing is carried out by the same [CairoDrawContext][3] used by the XCB backend.
+//! The connection to the compositor itself (binding `wl_compositor` and `zwlr_layer_shell_v1`
+//! via [smithay-client-toolkit][6], allocating `wl_shm` pools and attaching the rendered buffers
+//! on [flush][7]) is not yet implemented, so nothing is presented on screen.
+//!
+//! # Available features
+//! - `wayland`: adds `pango` and `cairo` as build dependencies and provides the experimental
+//!   implementation of the [Draw][2] trait.
+//!
+//! Functionality that relies on windows being X clients (docks with struts, unmapped windows and
+//! [KeyPressDraw][8]) is provided by [XDraw][9] and is not available for this backend.
+//!
+//! [1]: crate::wayland::WaylandDraw
+//! [2]: crate::draw::Draw
+//! [3]: crate::draw::CairoDrawContext
+//! [4]: https://www.cairographics.org/
+//! [5]: https://wayland.app/protocols/wlr-layer-shell-unstable-v1
+//! [6]: https://github.com/Smithay/client-toolkit
+//! [7]: crate::draw::Draw::flush
+//! [8]: crate::draw::KeyPressDraw
+//! [9]: crate::draw::XDraw
+use crate::core::xconnection::Xid;
+
+pub mod draw;
+
+#[doc(inline)]
+pub use draw::{Layer, LayerSurface, WaylandDraw, WaylandDrawContext};
+
+/// Enum to store the various ways that operations can fail inside of the
+/// Wayland implementations of penrose traits.
+#[derive(thiserror::Error, Debug)]
+pub enum WaylandError {
+    /// An operation was requested that this backend does not yet support
+    #[error("'{0}' is not supported by the wayland backend")]
+    Unsupported(&'static str),
+
+    /// No layer surface has been created with the requested id
+    #[error("Requested surface does not exist: {0}")]
+    UnknownSurface(Xid),
+}
@@ -15,7 +15,7 @@ use strum::IntoEnumIterator;
 use x11rb::{
     connection::Connection,
     protocol::{
-        xproto::{ClientMessageEvent, ConnectionExt as _, ModMask},
+        xproto::{ClientMessageEvent, ConnectionExt as _, KeyButMask, ModMask},
         Event,
     },
 };
@@ -49,18 +49,21 @@ pub(crate) fn convert_event<C: Connection>(
                 MouseEventKind::Release,
             ))
         })),
-        // FIXME: The 5 is due to https://github.com/sminez/penrose/issues/113
-        Event::MotionNotify(event) => Ok(to_mouse_state(5, event.state).map(|state| {
-            XEvent::MouseEvent(MouseEvent::new(
-                event.event,
-                event.root_x,
-                event.root_y,
-                event.event_x,
-                event.event_y,
-                state,
-                MouseEventKind::Motion,
-            ))
-        })),
+        // Motion events carry the held buttons in their state rather than the detail. Plain
+        // pointer motion with no button held is not reported.
+        Event::MotionNotify(event) => Ok(held_button(event.state)
+            .and_then(|button| to_mouse_state(button, event.state))
+            .map(|state| {
+                XEvent::MouseEvent(MouseEvent::new(
+                    event.event,
+                    event.root_x,
+                    event.root_y,
+                    event.event_x,
+                    event.event_y,
+                    state,
+                    MouseEventKind::Motion,
+                ))
+            })),
         Event::KeyPress(event) => {
             let code = KeyCode {
                 mask: event.state,
@@ -135,6 +138,21 @@ pub(crate) fn convert_event<C: Connection>(
     }
 }
 
+fn held_button(state: u16) -> Option<u8> {
+    let masks = [
+        KeyButMask::BUTTON1,
+        KeyButMask::BUTTON2,
+        KeyButMask::BUTTON3,
+        KeyButMask::BUTTON4,
+        KeyButMask::BUTTON5,
+    ];
+
+    masks
+        .iter()
+        .position(|&m| state & u16::from(m) > 0)
+        .map(|ix| ix as u8 + 1)
+}
+
 fn to_mouse_state(detail: u8, state: u16) -> Option<MouseState> {
     fn is_held(key: &ModifierKey, mask: u16) -> bool {
         mask & u16::from(*key) > 0
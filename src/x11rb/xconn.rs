@@ -145,6 +145,7 @@ impl<C: Connection> XClientConfig for X11rbConnection<C> {
                     aux = aux.x(x as i32).y(y as i32).width(w).height(h);
                 }
                 ClientConfig::StackAbove => aux = aux.stack_mode(StackMode::ABOVE),
+                ClientConfig::StackBelow => aux = aux.stack_mode(StackMode::BELOW),
             }
         }
         self.conn.configure_window(id, &aux)?;
@@ -358,6 +359,8 @@ impl<C: Connection> XClientProperties for X11rbConnection<C> {
 
             Prop::Cardinal(val) => (AtomEnum::CARDINAL, vec![val]),
 
+            Prop::Cardinals(vals) => (AtomEnum::CARDINAL, vals),
+
             Prop::Window(ids) => (AtomEnum::WINDOW, ids),
 
             // FIXME: handle changing WmHints and WmNormalHints correctly in change_prop
@@ -402,6 +405,16 @@ impl<C: Connection> XEventHandler for X11rbConnection<C> {
         }
     }
 
+    fn poll_for_event(&self) -> Result<Option<XEvent>> {
+        while let Some(event) = self.conn.poll_for_event()? {
+            if let Some(event) = super::event::convert_event(self, event)? {
+                return Ok(Some(event));
+            }
+        }
+
+        Ok(None)
+    }
+
     fn send_client_event(&self, msg: ClientMessage) -> Result<()> {
         let type_ = self.atom_id(&msg.dtype)?;
         let data = match msg.data() {
@@ -555,6 +568,21 @@ impl<C: Connection> XConn for X11rbConnection<C> {
 
         Ok(())
     }
+
+    fn grab_keyboard(&self) -> Result<()> {
+        let mode = GrabMode::ASYNC;
+        self.conn
+            .grab_keyboard(true, self.root, CURRENT_TIME, mode, mode)?
+            .reply()?;
+
+        Ok(())
+    }
+
+    fn ungrab_keyboard(&self) -> Result<()> {
+        self.conn.ungrab_keyboard(CURRENT_TIME)?;
+
+        Ok(())
+    }
 }
 
 impl<C: Connection> X11rbConnection<C> {
@@ -12,7 +12,10 @@ use crate::{
             XAtomQuerier, XEvent, Xid,
         },
     },
-    xcb::{Result, XErrorCode, XcbError, XcbGenericEvent},
+    xcb::{
+        conversions::is_drag_motion, helpers::parse_xft_dpi, Result, XErrorCode, XcbError,
+        XcbGenericEvent,
+    },
 };
 use strum::*;
 
@@ -74,6 +77,7 @@ pub fn code_map_from_xmodmap() -> Result<ReverseCodeMap> {
 pub struct Api {
     #[cfg_attr(feature = "serde", serde(skip, default = "default_conn"))]
     conn: xcb::Connection,
+    display: Option<String>,
     root: Xid,
     randr_base: u8,
     atoms: HashMap<Atom, u32>,
@@ -84,6 +88,7 @@ pub struct Api {
 impl fmt::Debug for Api {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("XCB Api")
+            .field("display", &self.display)
             .field("root", &self.root)
             .field("randr_base", &self.randr_base)
             .field("atoms", &self.atoms)
@@ -115,9 +120,23 @@ impl Api {
     /// [1]: http://rtbo.github.io/rust-xcb
     /// [2]: http://rtbo.github.io/rust-xcb/xcb/base/struct.Connection.html
     pub fn new() -> Result<Self> {
-        let (conn, _) = xcb::Connection::connect(None)?;
+        Self::connect(None)
+    }
+
+    /// Connect to the X server running on the given display (e.g. ":1") rather than the one
+    /// specified by `$DISPLAY`.
+    ///
+    /// This is useful when running against a nested X server such as Xephyr for development.
+    /// See [Api::new] for details of the rest of the initialisation process.
+    pub fn new_on_display(display: &str) -> Result<Self> {
+        Self::connect(Some(display))
+    }
+
+    fn connect(display: Option<&str>) -> Result<Self> {
+        let (conn, _) = xcb::Connection::connect(display)?;
         let mut api = Self {
             conn,
+            display: display.map(String::from),
             root: 0,
             randr_base: 0,
             atoms: HashMap::new(),
@@ -129,6 +148,24 @@ impl Api {
         Ok(api)
     }
 
+    /// Check whether the underlying connection to the X server is still usable.
+    ///
+    /// Once the connection has been shut down (e.g. because the X server exited) every
+    /// subsequent request will fail and [Api::reconnect] is needed to recover.
+    pub fn connection_alive(&self) -> bool {
+        self.conn.has_error().is_ok()
+    }
+
+    /// Replace the underlying connection with a freshly established one to the same display.
+    ///
+    /// All ids for windows and other resources created using the previous connection should be
+    /// considered invalid after calling this method.
+    pub fn reconnect(&mut self) -> Result<()> {
+        *self = Self::connect(self.display.as_deref())?;
+
+        Ok(())
+    }
+
     fn init(&mut self) -> Result<()> {
         self.root = match self.conn.get_setup().roots().next() {
             Some(r) => r.root(),
@@ -213,10 +250,69 @@ impl Api {
             .ok_or(XcbError::QueryFailed("visual type"))
     }
 
+    /// Find a TrueColor visual for the given screen along with its depth.
+    ///
+    /// If `want_alpha` is true then this will be a 32-bit visual supporting an alpha channel,
+    /// otherwise it will be the default visual for the screen.
+    pub(crate) fn find_visual(
+        &self,
+        screen_ix: usize,
+        want_alpha: bool,
+    ) -> Result<(u8, xcb::Visualtype)> {
+        let screen = self.screen(screen_ix)?;
+        let root_visual = screen.root_visual();
+
+        for depth in screen.allowed_depths() {
+            if want_alpha && depth.depth() != 32 {
+                continue;
+            }
+
+            let found = depth.visuals().find(|v| {
+                if want_alpha {
+                    v.class() == xcb::VISUAL_CLASS_TRUE_COLOR as u8
+                } else {
+                    v.visual_id() == root_visual
+                }
+            });
+
+            if let Some(visual) = found {
+                return Ok((depth.depth(), visual));
+            }
+        }
+
+        Err(XcbError::QueryFailed(if want_alpha {
+            "32-bit TrueColor visual"
+        } else {
+            "default visual"
+        }))
+    }
+
+    /// Find the details of the visual with the given id along with its depth
+    pub(crate) fn visual_for_id(&self, visual_id: u32) -> Result<(u8, xcb::Visualtype)> {
+        for screen in self.conn.get_setup().roots() {
+            for depth in screen.allowed_depths() {
+                if let Some(v) = depth.visuals().find(|v| v.visual_id() == visual_id) {
+                    return Ok((depth.depth(), v));
+                }
+            }
+        }
+
+        Err(XcbError::QueryFailed("visual for window"))
+    }
+
     /// Fetch the requested property for the target window
     pub fn get_prop(&self, id: Xid, name: &str) -> Result<Prop> {
         let atom = self.atom(name)?;
-        let cookie = xcb::get_property(&self.conn, false, id, atom, xcb::ATOM_ANY, 0, 1024);
+        let cookie = xcb::get_property(
+            &self.conn,
+            false,
+            id,
+            atom,
+            xcb::ATOM_ANY,
+            0,
+            // The server clamps this to the actual length of the property
+            u32::MAX,
+        );
         let r = cookie.get_reply()?;
         let prop_type = self.atom_name(r.type_())?;
 
@@ -277,6 +373,63 @@ impl Api {
         })
     }
 
+    /// Fetch the raw bytes of a property for the target window, requesting that it is of the
+    /// given type (e.g. "CARDINAL" or "UTF8_STRING").
+    ///
+    /// This can be used to read properties that are not explicitly supported by [Prop]. Returns
+    /// [XcbError::MissingProp] if the property is not set on the window and
+    /// [XcbError::InvalidPropertyData] if it is set with a different type.
+    pub fn get_prop_raw(&self, id: Xid, name: &str, type_name: &str) -> Result<Vec<u8>> {
+        let atom = self.atom(name)?;
+        let ty = self.atom(type_name)?;
+        let cookie = xcb::get_property(&self.conn, false, id, atom, ty, 0, u32::MAX);
+        let r = cookie.get_reply()?;
+
+        if r.type_() == xcb::NONE {
+            return Err(XcbError::MissingProp(name.into(), id));
+        }
+
+        // On a type mismatch the server reports the actual type of the property along with an
+        // empty value rather than failing the request.
+        if r.type_() != ty {
+            return Err(XcbError::InvalidPropertyData(format!(
+                "{} has type {} but {} was requested",
+                name,
+                self.atom_name(r.type_())?,
+                type_name
+            )));
+        }
+
+        Ok(r.value::<u8>().to_vec())
+    }
+
+    /// Fetch a property for the target window as a list of u32 values.
+    ///
+    /// Fails if the property data is not a multiple of 4 bytes.
+    pub fn get_prop_u32(&self, id: Xid, name: &str, type_name: &str) -> Result<Vec<u32>> {
+        let bytes = self.get_prop_raw(id, name, type_name)?;
+        if bytes.len() % 4 != 0 {
+            return Err(XcbError::InvalidPropertyData(format!(
+                "{} has {} bytes of data which is not a whole number of u32s",
+                name,
+                bytes.len()
+            )));
+        }
+
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|b| u32::from_ne_bytes([b[0], b[1], b[2], b[3]]))
+            .collect())
+    }
+
+    /// Fetch a property for the target window as a UTF-8 string, with any trailing null bytes
+    /// removed.
+    pub fn get_prop_string(&self, id: Xid, name: &str, type_name: &str) -> Result<String> {
+        let bytes = self.get_prop_raw(id, name, type_name)?;
+
+        Ok(String::from_utf8(bytes)?.trim_end_matches('\0').to_string())
+    }
+
     /// Fetch the `WindowAttributes` data for a target client id
     pub fn get_window_attributes(&self, id: Xid) -> Result<WindowAttributes> {
         let win_attrs = xcb::get_window_attributes(&self.conn, id).get_reply()?;
@@ -323,6 +476,40 @@ impl Api {
         Ok(())
     }
 
+    /// Grab control of all pointer input, reporting button presses and pointer motion relative
+    /// to the window `id`, which must currently be mapped.
+    pub fn grab_pointer(&self, id: Xid) -> Result<()> {
+        let mask = (xcb::EVENT_MASK_BUTTON_PRESS | xcb::EVENT_MASK_POINTER_MOTION) as u16;
+        let reply = xcb::grab_pointer(
+            &self.conn,
+            false, // report all events relative to 'id'
+            id,
+            mask,
+            xcb::GRAB_MODE_ASYNC as u8,
+            xcb::GRAB_MODE_ASYNC as u8,
+            xcb::NONE, // don't confine the cursor to a specific window
+            xcb::NONE, // don't change the cursor type
+            xcb::CURRENT_TIME,
+        )
+        .get_reply()?;
+
+        if reply.status() != xcb::GRAB_STATUS_SUCCESS as u8 {
+            return Err(XcbError::Raw(format!(
+                "unable to grab the pointer: status={}",
+                reply.status()
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Release pointer input
+    pub fn ungrab_pointer(&self) -> Result<()> {
+        xcb::ungrab_pointer_checked(&self.conn, xcb::CURRENT_TIME).request_check()?;
+
+        Ok(())
+    }
+
     /// Poll for the next event from the underlying [XCB Connection][::xcb::Connection],
     /// returning it as an [XKeySym] if it was a user keypress, or an [XEvent] if not.
     ///
@@ -358,7 +545,7 @@ impl Api {
     }
 
     #[cfg(feature = "keysyms")]
-    fn attempt_to_parse_as_keypress(
+    pub(crate) fn attempt_to_parse_as_keypress(
         &self,
         event: XcbGenericEvent,
     ) -> Result<Option<KeyPressParseAttempt>> {
@@ -391,6 +578,9 @@ impl Api {
         }
 
         Ok(match etype {
+            // Pointer motion with no button held is not a drag so there is nothing to report
+            xcb::MOTION_NOTIFY if !is_drag_motion(&event) => None,
+
             xcb::BUTTON_PRESS | xcb::BUTTON_RELEASE | xcb::MOTION_NOTIFY => {
                 match MouseEvent::try_from(event) {
                     Ok(m) => Some(XEvent::MouseEvent(m)),
@@ -486,7 +676,7 @@ impl Api {
 
             xcb::UNMAP_NOTIFY => {
                 let e: &xcb::UnmapNotifyEvent = unsafe { xcb::cast_event(&event) };
-                Some(XEvent::UnmapNotify(e.window()))
+                Some(XEvent::UnmapNotify(e.window(), e.event()))
             }
 
             xcb::CLIENT_MESSAGE => {
@@ -603,6 +793,8 @@ impl Api {
 
             Prop::Cardinal(val) => (xcb::xproto::ATOM_CARDINAL, vec![val]),
 
+            Prop::Cardinals(vals) => (xcb::xproto::ATOM_CARDINAL, vals),
+
             Prop::UTF8String(strs) => {
                 return Ok(xcb::change_property_checked(
                     &self.conn,
@@ -651,6 +843,32 @@ impl Api {
 
     /// Create a new client window
     pub fn create_window(&self, ty: WinType, reg: Region, managed: bool) -> Result<Xid> {
+        self.create_window_with_visual(ty, reg, managed, None)
+    }
+
+    /// Create a new client window using a specific depth and visual.
+    ///
+    /// If `visual` is `None` then the visual with the greatest depth available on the first
+    /// screen will be used. The visual is ignored for window types other than `InputOutput`.
+    pub fn create_window_with_visual(
+        &self,
+        ty: WinType,
+        reg: Region,
+        managed: bool,
+        visual: Option<(u8, &xcb::Visualtype)>,
+    ) -> Result<Xid> {
+        self.create_window_with_options(ty, reg, managed, visual, true)
+    }
+
+    // Create a new window as for create_window_with_visual, only mapping it if 'mapped' is true
+    pub(crate) fn create_window_with_options(
+        &self,
+        ty: WinType,
+        reg: Region,
+        managed: bool,
+        visual: Option<(u8, &xcb::Visualtype)>,
+        mapped: bool,
+    ) -> Result<Xid> {
         let (ty, mut data, class, root, depth, visual_id) = match ty {
             WinType::CheckWin => (
                 None,
@@ -673,8 +891,13 @@ impl Api {
             WinType::InputOutput(a) => {
                 let colormap = self.conn.generate_id();
                 let screen = self.screen(0)?;
-                let depth = self.get_depth(&screen)?;
-                let visual = self.get_visual_type(&depth)?;
+                let (depth, visual) = match visual {
+                    Some((depth, visual)) => (depth, *visual),
+                    None => {
+                        let depth = self.get_depth(&screen)?;
+                        (depth.depth(), self.get_visual_type(&depth)?)
+                    }
+                };
 
                 xcb::xproto::create_colormap(
                     &self.conn,
@@ -691,12 +914,16 @@ impl Api {
                         (xcb::CW_COLORMAP, colormap),
                         (
                             xcb::CW_EVENT_MASK,
-                            xcb::EVENT_MASK_EXPOSURE | xcb::EVENT_MASK_KEY_PRESS,
+                            xcb::EVENT_MASK_EXPOSURE
+                                | xcb::EVENT_MASK_KEY_PRESS
+                                | xcb::EVENT_MASK_BUTTON_PRESS
+                                | xcb::EVENT_MASK_BUTTON_RELEASE
+                                | xcb::EVENT_MASK_BUTTON_MOTION,
                         ),
                     ],
                     xcb::WINDOW_CLASS_INPUT_OUTPUT as u16,
                     screen.root(),
-                    depth.depth(),
+                    depth,
                     visual.visual_id(),
                 )
             }
@@ -730,7 +957,13 @@ impl Api {
         if let Some(atom) = ty {
             let net_name = Atom::NetWmWindowType.as_ref();
             self.change_prop(id, net_name, Prop::Atom(vec![atom.as_ref().into()]))?;
-            self.map_client(id)?;
+            if mapped {
+                self.map_client(id)?;
+            }
+
+            if atom == Atom::NetWindowTypeDesktop {
+                self.configure_client(id, &[ClientConfig::StackBelow])?;
+            }
         }
 
         self.flush();
@@ -807,6 +1040,15 @@ impl Api {
         Ok(xcb::change_window_attributes_checked(&self.conn, id, &data).request_check()?)
     }
 
+    /// Ask to be notified of property changes on the root window.
+    ///
+    /// Unlike [ClientAttr::RootEventMask] this does not request substructure redirection, so it
+    /// can be used by clients that are not the window manager.
+    pub fn select_root_property_changes(&self) -> Result<()> {
+        let data = [(xcb::CW_EVENT_MASK, xcb::EVENT_MASK_PROPERTY_CHANGE)];
+        Ok(xcb::change_window_attributes_checked(&self.conn, self.root, &data).request_check()?)
+    }
+
     /// Find the current size and position of the target window
     pub fn client_geometry(&self, id: Xid) -> Result<Region> {
         let res = xcb::get_geometry(&self.conn, id).get_reply()?;
@@ -852,6 +1094,46 @@ impl Api {
         Ok(screens)
     }
 
+    /// Query the randr API for the physical size in millimeters of each screen, in the same
+    /// order as [Api::current_screens].
+    ///
+    /// Screens that do not report a physical size are returned as (0, 0).
+    pub fn screen_physical_sizes(&self) -> Result<Vec<(u32, u32)>> {
+        let check_win = self.check_window();
+        let resources = xcb::randr::get_screen_resources(&self.conn, check_win);
+
+        let sizes = resources
+            .get_reply()?
+            .crtcs()
+            .iter()
+            .flat_map(|c| xcb::randr::get_crtc_info(&self.conn, *c, 0).get_reply())
+            .filter(|r| r.width() > 0)
+            .map(|r| {
+                r.outputs()
+                    .first()
+                    .and_then(|o| {
+                        xcb::randr::get_output_info(&self.conn, *o, 0)
+                            .get_reply()
+                            .ok()
+                    })
+                    .map(|info| (info.mm_width(), info.mm_height()))
+                    .unwrap_or((0, 0))
+            })
+            .collect();
+
+        self.destroy_client(check_win)?;
+        Ok(sizes)
+    }
+
+    /// The value of `Xft.dpi` set in the X resource database of the root window, if any.
+    pub fn xft_dpi(&self) -> Option<f64> {
+        // STRING properties are Latin-1 rather than UTF-8 so non-ASCII resource values should not
+        // prevent us from finding the DPI
+        self.get_prop_raw(self.root, "RESOURCE_MANAGER", "STRING")
+            .ok()
+            .and_then(|resources| parse_xft_dpi(&String::from_utf8_lossy(&resources)))
+    }
+
     /// Query the randr API for current outputs and return the size of each screen
     pub fn screen_sizes(&self) -> Result<Vec<Region>> {
         self.current_screens()
@@ -1028,13 +1310,15 @@ impl Api {
     ///
     /// This method handles all of the mapping of xcb events to penrose [XEvent] instances,
     /// returning None if there is no pending event and an error if the connection to the X server
-    /// is closed.
+    /// is closed. Pending events that do not map to an [XEvent] are skipped.
     pub fn poll_for_event(&self) -> Result<Option<XEvent>> {
-        if let Some(event) = self.conn.poll_for_event() {
-            self.generic_xcb_to_xevent(event)
-        } else {
-            Ok(self.conn.has_error().map(|_| None)?)
+        while let Some(event) = self.conn.poll_for_event() {
+            if let Some(e) = self.generic_xcb_to_xevent(event)? {
+                return Ok(Some(e));
+            }
         }
+
+        Ok(self.conn.has_error().map(|_| None)?)
     }
 
     /// Move the cursor to the given (x, y) position inside the specified window.
@@ -156,7 +156,8 @@ fn data_from_event(
         xcb::MOTION_NOTIFY => {
             let e: &xcb::MotionNotifyEvent = unsafe { xcb::cast_event(&raw) };
             (
-                e.detail(),
+                held_button(e.state())
+                    .ok_or_else(|| XcbError::Raw("motion notify with no button held".into()))?,
                 e.state(),
                 e.event(),
                 e.root_x(),
@@ -174,6 +175,30 @@ fn data_from_event(
     })
 }
 
+// Whether a motion notify event has a button held, i.e. whether it is part of a drag. Plain pointer
+// motion has no button to report so it is not converted into a MouseEvent.
+pub(crate) fn is_drag_motion(raw: &XcbGenericEvent) -> bool {
+    let e: &xcb::MotionNotifyEvent = unsafe { xcb::cast_event(raw) };
+    held_button(e.state()).is_some()
+}
+
+// Motion events do not carry a button in their detail field so we use the first button that is
+// held according to the state mask instead (None if no button is held)
+fn held_button(state: u16) -> Option<u8> {
+    let masks = [
+        xcb::BUTTON_MASK_1,
+        xcb::BUTTON_MASK_2,
+        xcb::BUTTON_MASK_3,
+        xcb::BUTTON_MASK_4,
+        xcb::BUTTON_MASK_5,
+    ];
+
+    masks
+        .iter()
+        .position(|&m| state & m as u16 > 0)
+        .map(|ix| ix as u8 + 1)
+}
+
 impl From<&ClientConfig> for Vec<(u16, u32)> {
     fn from(w: &ClientConfig) -> Vec<(u16, u32)> {
         match w {
@@ -190,6 +215,9 @@ impl From<&ClientConfig> for Vec<(u16, u32)> {
             ClientConfig::StackAbove => {
                 vec![(xcb::CONFIG_WINDOW_STACK_MODE as u16, xcb::STACK_MODE_ABOVE)]
             }
+            ClientConfig::StackBelow => {
+                vec![(xcb::CONFIG_WINDOW_STACK_MODE as u16, xcb::STACK_MODE_BELOW)]
+            }
         }
     }
 }
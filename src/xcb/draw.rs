@@ -8,21 +8,55 @@
 use crate::{
     core::{
         data_types::{Region, WinType},
-        xconnection::{Prop, WindowState, XClientHandler, Xid},
+        xconnection::{Atom, Prop, WindowState, XClientHandler, XEvent, Xid},
+    },
+    draw::{
+        cairo_context::{load_font, PatternCache},
+        CairoDrawContext, Draw, DrawError, Result, StateChange, VisualInfo, XDraw,
     },
-    draw::{Color, Draw, DrawContext, DrawError, Result},
     xcb::{Api, XcbError},
 };
 
-use pangocairo::functions::{create_layout, show_layout};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    rc::Rc,
+};
 
-use std::collections::HashMap;
+#[doc(inline)]
+pub use crate::draw::cairo_context::average_color;
 
 #[cfg(feature = "keysyms")]
 use crate::core::xconnection::{KeyPressParseAttempt, XKeyboardHandler};
 
-fn pango_layout(ctx: &cairo::Context) -> Result<pango::Layout> {
-    Ok(create_layout(ctx).ok_or_else(|| XcbError::Pango("unable to create layout".into()))?)
+const BASE_DPI: f64 = 96.0;
+const MM_PER_INCH: f64 = 25.4;
+
+// The scale factor of each screen relative to BASE_DPI, preferring Xft.dpi when it is set
+fn detect_scale_factors(api: &Api) -> Result<Vec<f64>> {
+    let sizes = api.screen_sizes()?;
+    let xft_dpi = api.xft_dpi();
+    let physical = match xft_dpi {
+        Some(_) => vec![],
+        None => api.screen_physical_sizes()?,
+    };
+
+    Ok(scale_factors(xft_dpi, &sizes, &physical))
+}
+
+fn scale_factors(xft_dpi: Option<f64>, sizes: &[Region], physical: &[(u32, u32)]) -> Vec<f64> {
+    sizes
+        .iter()
+        .enumerate()
+        .map(|(ix, r)| match (xft_dpi, physical.get(ix)) {
+            (Some(dpi), _) => dpi / BASE_DPI,
+            (None, Some(&(mm_w, _))) if mm_w > 0 => {
+                let (_, _, w, _) = r.values();
+                (w as f64 * MM_PER_INCH / mm_w as f64) / BASE_DPI
+            }
+            _ => 1.0,
+        })
+        .collect()
 }
 
 /// An XCB based [Draw] implementation backed by pango and cairo
@@ -31,6 +65,30 @@ pub struct XcbDraw {
     api: Api,
     fonts: HashMap<String, pango::FontDescription>,
     surfaces: HashMap<Xid, cairo::XCBSurface>,
+    visuals: RefCell<HashMap<(usize, bool), (u8, xcb::ffi::xcb_visualtype_t)>>,
+    scale_overrides: HashMap<usize, f64>,
+    // scale factors detected from the X server for each screen, computed on connection
+    detected_scales: Vec<f64>,
+    scrollable: HashMap<Xid, ScrollRegion>,
+    // windows created unmapped are left for the caller to map rather than being mapped on flush
+    unmapped: HashSet<Xid>,
+    patterns: PatternCache,
+    fallback_fonts: Vec<String>,
+}
+
+// An off-screen surface holding the full content of a scrollable window along with the offset of
+// the portion of it that is currently visible in the window
+#[derive(Debug)]
+struct ScrollRegion {
+    content: cairo::Surface,
+    content_h: f64,
+    view_h: f64,
+    offset: f64,
+}
+
+// Clamp a scroll offset so that the visible portion of the content never extends past its end
+fn clamp_scroll_offset(y: f64, content_h: f64, view_h: f64) -> f64 {
+    y.clamp(0.0, (content_h - view_h).max(0.0))
 }
 
 crate::__xcb_impl_xclienthandler!(XcbDraw);
@@ -41,13 +99,90 @@ crate::__xcb_impl_xkeyboardhandler!(XcbDraw);
 impl XcbDraw {
     /// Create a new empty [XcbDraw]. Fails if unable to connect to the X server
     pub fn new() -> Result<Self> {
+        Self::from_api(Api::new()?)
+    }
+
+    /// Create a new empty [XcbDraw] connected to the X server running on the given display
+    /// (e.g. ":1") rather than the one specified by `$DISPLAY`.
+    pub fn new_on_display(display: &str) -> Result<Self> {
+        Self::from_api(Api::new_on_display(display)?)
+    }
+
+    fn from_api(api: Api) -> Result<Self> {
+        let detected_scales = detect_scale_factors(&api)?;
+
         Ok(Self {
-            api: Api::new()?,
+            api,
             fonts: HashMap::new(),
             surfaces: HashMap::new(),
+            visuals: RefCell::new(HashMap::new()),
+            scale_overrides: HashMap::new(),
+            detected_scales,
+            scrollable: HashMap::new(),
+            unmapped: HashSet::new(),
+            patterns: Rc::new(RefCell::new(HashMap::new())),
+            fallback_fonts: vec![],
         })
     }
 
+    /// Set font families to fall back to when the font in use by a [DrawContext][crate::draw::DrawContext] is missing a
+    /// glyph (e.g. for CJK characters or emoji). The fallbacks apply to all text rendered by
+    /// contexts created after this is called and are tried in the order given.
+    ///
+    /// Fallbacks set for an individual context using [XcbDrawContext::set_fallback_fonts] take
+    /// precedence over these and are tried first.
+    pub fn set_global_fallback_fonts(&mut self, families: &[&str]) {
+        self.fallback_fonts = families.iter().map(|f| f.to_string()).collect();
+    }
+
+    fn context(&self, ctx: cairo::Context) -> XcbDrawContext {
+        CairoDrawContext::new(
+            ctx,
+            self.fonts.clone(),
+            Rc::clone(&self.patterns),
+            self.fallback_fonts.clone(),
+        )
+    }
+
+    // Create a new window with a cairo surface for drawing to, only mapping it if 'mapped' is set
+    fn create_window(
+        &mut self,
+        ty: WinType,
+        r: Region,
+        managed: bool,
+        mapped: bool,
+    ) -> Result<Xid> {
+        let (_, _, w, h) = r.values();
+        let (depth, mut visualtype) = self.preferred_visual_type(0)?;
+        let visual = xcb::Visualtype { base: visualtype };
+        let id =
+            self.api
+                .create_window_with_options(ty, r, managed, Some((depth, &visual)), mapped)?;
+
+        let surface = unsafe {
+            let conn_ptr = self.api.conn().get_raw_conn() as *mut cairo_sys::xcb_connection_t;
+
+            cairo::XCBSurface::create(
+                &cairo::XCBConnection::from_raw_none(conn_ptr),
+                &cairo::XCBDrawable(id),
+                &cairo::XCBVisualType::from_raw_none(
+                    &mut visualtype as *mut xcb::ffi::xcb_visualtype_t
+                        as *mut cairo_sys::xcb_visualtype_t,
+                ),
+                w as i32,
+                h as i32,
+            )?
+        };
+
+        surface.set_size(w as i32, h as i32)?;
+        self.surfaces.insert(id, surface);
+        if !mapped {
+            self.unmapped.insert(id);
+        }
+
+        Ok(id)
+    }
+
     /// Get a handle on the underlying [XCB Connection][::xcb::Connection] used by [Api]
     /// to communicate with the X server.
     pub fn xcb_connection(&self) -> &xcb::Connection {
@@ -60,20 +195,239 @@ impl XcbDraw {
     }
 
     /// Get a mutable handle on the underlying [Api] to communicate with the X server.
+    ///
+    /// If the underlying connection is replaced then [XcbDraw::clear_visual_cache] should be
+    /// called to drop any cached visual information from the previous connection.
     pub fn api_mut(&mut self) -> &mut Api {
         &mut self.api
     }
-}
 
-impl Draw for XcbDraw {
-    type Ctx = XcbDrawContext;
+    /// The raw XCB window id for a window created (or adopted) by this [XcbDraw].
+    ///
+    /// An [Xid] is the X window id itself, so the returned value is always equal to `id`: this
+    /// method exists to check that `id` is actually known to this [XcbDraw] before it is handed
+    /// to raw XCB calls. Fails with [XcbError::UnintialisedSurface] for any other window.
+    pub fn xcb_window(&self, id: Xid) -> Result<xcb::Window> {
+        if self.surfaces.contains_key(&id) {
+            Ok(id)
+        } else {
+            Err(XcbError::UnintialisedSurface(id).into())
+        }
+    }
 
-    fn new_window(&mut self, ty: WinType, r: Region, managed: bool) -> Result<Xid> {
-        let (_, _, w, h) = r.values();
-        let id = self.api.create_window(ty, r, managed)?;
-        let xcb_screen = self.api.screen(0)?;
-        let depth = self.api.get_depth(&xcb_screen)?;
-        let mut visualtype = self.api.get_visual_type(&depth)?;
+    /// The [Xid] for a raw XCB window id if it is a window known to this [XcbDraw].
+    ///
+    /// This is the inverse of [XcbDraw::xcb_window].
+    pub fn window_for_xcb(&self, window: xcb::Window) -> Option<Xid> {
+        self.surfaces.get_key_value(&window).map(|(&id, _)| id)
+    }
+
+    /// Drop all cached visual types, forcing them to be looked up again from the X server the
+    /// next time that a window or surface is created.
+    pub fn clear_visual_cache(&self) {
+        self.visuals.borrow_mut().clear();
+    }
+
+    /// Force the scale factor used for the given screen, ignoring the DPI reported by the X
+    /// server. See [XcbDraw::scale_factor] for details.
+    pub fn set_scale_override(&mut self, screen_ix: usize, factor: f64) {
+        self.scale_overrides.insert(screen_ix, factor);
+    }
+
+    /// Remove a scale factor override previously set using [XcbDraw::set_scale_override].
+    pub fn clear_scale_override(&mut self, screen_ix: usize) {
+        self.scale_overrides.remove(&screen_ix);
+    }
+
+    /// The scale factor to use when rendering to the given screen, relative to 96 DPI.
+    ///
+    /// In order of precedence, this is taken from:
+    ///   - an override set using [XcbDraw::set_scale_override]
+    ///   - the `Xft.dpi` X resource
+    ///   - the DPI computed from the physical size of the screen reported by randr
+    ///
+    /// If none of these are available then a scale factor of 1.0 is used. The values reported by
+    /// the X server are read once when connecting (and again on [XcbDraw::reconnect]).
+    pub fn scale_factor(&self, screen_ix: usize) -> Result<f64> {
+        if let Some(factor) = self.scale_overrides.get(&screen_ix) {
+            return Ok(*factor);
+        }
+
+        Ok(self.detected_scales.get(screen_ix).copied().unwrap_or(1.0))
+    }
+
+    /// Get a new [XcbDrawContext] for the target window that renders at the
+    /// [scale factor][XcbDraw::scale_factor] of the given screen. See [XcbDrawContext::set_scale]
+    /// for how coordinates and extents are handled by scaled contexts.
+    pub fn scaled_context_for(&self, id: Xid, screen_ix: usize) -> Result<XcbDrawContext> {
+        let mut ctx = self.context_for(id)?;
+        ctx.set_scale(self.scale_factor(screen_ix)?);
+
+        Ok(ctx)
+    }
+
+    /// Replace the underlying X connection with a new one.
+    ///
+    /// Any existing windows and surfaces are dropped as they belonged to the previous connection:
+    /// callers will need to create new windows after reconnecting. Registered fonts are kept.
+    pub fn reconnect(&mut self) -> Result<()> {
+        self.api.reconnect()?;
+        self.detected_scales = detect_scale_factors(&self.api)?;
+        self.surfaces.clear();
+        self.scrollable.clear();
+        self.clear_visual_cache();
+
+        Ok(())
+    }
+
+    /// Declare that the given window supports the `WM_DELETE_WINDOW` protocol.
+    ///
+    /// Under the [ICCCM][1], a window manager closing a window that lists `WM_DELETE_WINDOW` in
+    /// its `WM_PROTOCOLS` property will send it a client message asking it to close rather than
+    /// forcibly killing the client. These requests can be detected using
+    /// [XcbDraw::close_requested] or [XcbDraw::poll_close].
+    ///
+    /// [1]: https://tronche.com/gui/x/icccm/sec-4.html#s-4.2.8.1
+    pub fn set_closable(&self, id: Xid) -> Result<()> {
+        Ok(self.api.change_prop(
+            id,
+            Atom::WmProtocols.as_ref(),
+            Prop::Atom(vec![Atom::WmDeleteWindow.as_ref().to_string()]),
+        )?)
+    }
+
+    /// If `event` is a request from the window manager for a window to close, returns the id of
+    /// that window.
+    pub fn close_requested(&self, event: &XEvent) -> Option<Xid> {
+        match event {
+            XEvent::ClientMessage(m) if m.dtype == Atom::WmProtocols.as_ref() => {
+                let delete = self.api.known_atom(Atom::WmDeleteWindow);
+                m.data().as_u32().first().filter(|&&a| a == delete)?;
+                Some(m.id)
+            }
+            _ => None,
+        }
+    }
+
+    /// Check pending X events without blocking, returning the id of the first window that has
+    /// been asked to close (see [XcbDraw::set_closable]).
+    ///
+    /// All other pending events are discarded: if you need to handle other events as well then
+    /// check each event yourself using [XcbDraw::close_requested] instead.
+    pub fn poll_close(&self) -> Result<Option<Xid>> {
+        while let Some(event) = self.api.poll_for_event()? {
+            if let Some(id) = self.close_requested(&event) {
+                return Ok(Some(id));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Start listening for changes to the window manager state advertised on the root window so
+    /// that they can be detected using [XcbDraw::state_change] or [XcbDraw::poll_state_change].
+    pub fn watch_state_changes(&self) -> Result<()> {
+        Ok(self.api.select_root_property_changes()?)
+    }
+
+    /// If `event` is a change to one of the root window properties tracked by [StateChange],
+    /// returns which one changed.
+    pub fn state_change(&self, event: &XEvent) -> Option<StateChange> {
+        match event {
+            XEvent::PropertyNotify(e) if e.is_root => StateChange::from_property(&e.atom),
+            _ => None,
+        }
+    }
+
+    /// Check pending X events without blocking, returning the first change to the window manager
+    /// state (see [XcbDraw::watch_state_changes]).
+    ///
+    /// All other pending events are discarded: if you need to handle other events as well then
+    /// check each event yourself using [XcbDraw::state_change] instead.
+    pub fn poll_state_change(&self) -> Result<Option<StateChange>> {
+        while let Some(event) = self.api.poll_for_event()? {
+            if let Some(change) = self.state_change(&event) {
+                return Ok(Some(change));
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Create a new window of the given type and size and call `draw_fn` to render its contents
+    /// every time that it is exposed.
+    ///
+    /// This blocks the calling thread until the window is closed, either by the window manager
+    /// asking it to close or by the window being destroyed. The window is destroyed before
+    /// returning and any error from `draw_fn` will also close the window.
+    pub fn run_simple_window<F>(
+        &mut self,
+        ty: WinType,
+        w: u32,
+        h: u32,
+        mut draw_fn: F,
+    ) -> Result<()>
+    where
+        F: FnMut(&mut XcbDrawContext) -> Result<()>,
+    {
+        let id = self.new_window(ty, Region::new(0, 0, w, h), true)?;
+        self.set_closable(id)?;
+        self.flush(id)?;
+
+        let res = loop {
+            match self.api.wait_for_event() {
+                Ok(XEvent::Expose(e)) if e.id == id && e.count == 0 => {
+                    let mut ctx = self.context_for(id)?;
+                    if let Err(e) = draw_fn(&mut ctx).and_then(|_| self.flush(id)) {
+                        break Err(e);
+                    }
+                }
+
+                Ok(e) if self.close_requested(&e) == Some(id) => break Ok(()),
+
+                Ok(XEvent::Destroy(destroyed)) if destroyed == id => {
+                    self.forget_window(id);
+                    return Ok(());
+                }
+
+                Ok(_) => (),
+                Err(e) => break Err(e.into()),
+            }
+        };
+
+        self.destroy_window(id)?;
+
+        res
+    }
+
+    /// Destroy a window created by this [XcbDraw] along with its surface.
+    pub fn destroy_window(&mut self, id: Xid) -> Result<()> {
+        self.forget_window(id);
+        self.destroy_client(id)?;
+        self.api.flush();
+
+        Ok(())
+    }
+
+    fn forget_window(&mut self, id: Xid) {
+        self.surfaces.remove(&id);
+        self.scrollable.remove(&id);
+        self.unmapped.remove(&id);
+    }
+
+    /// Create a cairo surface for an existing window that was not created by this [XcbDraw],
+    /// allowing [Draw::context_for] to be used to render to it.
+    ///
+    /// The caller retains ownership of the lifecycle of the window itself: it will not be
+    /// destroyed by this [XcbDraw]. Note that [Draw::flush] will map the window if it is not
+    /// already mapped. Fails if the window does not exist.
+    pub fn adopt_window(&mut self, id: Xid, w: usize, h: usize) -> Result<()> {
+        self.api.client_geometry(id)?;
+        let attrs = xcb::get_window_attributes(self.api.conn(), id)
+            .get_reply()
+            .map_err(XcbError::from)?;
+        let (_, visual) = self.api.visual_for_id(attrs.visual())?;
+        let mut visualtype = visual.base;
 
         let surface = unsafe {
             let conn_ptr = self.api.conn().get_raw_conn() as *mut cairo_sys::xcb_connection_t;
@@ -82,7 +436,7 @@ impl Draw for XcbDraw {
                 &cairo::XCBConnection::from_raw_none(conn_ptr),
                 &cairo::XCBDrawable(id),
                 &cairo::XCBVisualType::from_raw_none(
-                    &mut visualtype.base as *mut xcb::ffi::xcb_visualtype_t
+                    &mut visualtype as *mut xcb::ffi::xcb_visualtype_t
                         as *mut cairo_sys::xcb_visualtype_t,
                 ),
                 w as i32,
@@ -90,17 +444,177 @@ impl Draw for XcbDraw {
             )?
         };
 
-        surface.set_size(w as i32, h as i32)?;
         self.surfaces.insert(id, surface);
 
+        Ok(())
+    }
+
+    /// Create a new window whose content is rendered to an off-screen surface `content_h` pixels
+    /// tall, of which the window shows a slice the height of `r`.
+    ///
+    /// Contexts returned by [Draw::context_for] for this window draw to the full content surface
+    /// and the visible slice is selected using [XcbDraw::set_scroll_offset]. This allows long
+    /// content such as menus to be scrolled without re-rendering it on each step. A content
+    /// height smaller than the window is treated as the window height.
+    pub fn new_scrollable_window(
+        &mut self,
+        ty: WinType,
+        r: Region,
+        content_h: u32,
+        managed: bool,
+    ) -> Result<Xid> {
+        let (_, _, w, h) = r.values();
+        let content_h = content_h.max(h);
+        let id = self.new_window(ty, r, managed)?;
+        let content = self
+            .surfaces
+            .get(&id)
+            .ok_or(XcbError::UnintialisedSurface(id))?
+            .create_similar(cairo::Content::ColorAlpha, w as i32, content_h as i32)?;
+
+        self.scrollable.insert(
+            id,
+            ScrollRegion {
+                content,
+                content_h: content_h as f64,
+                view_h: h as f64,
+                offset: 0.0,
+            },
+        );
+
         Ok(id)
     }
 
+    /// Set the vertical offset into the content of a window created using
+    /// [XcbDraw::new_scrollable_window] that is shown in the window.
+    ///
+    /// The offset is clamped so that the window never shows past the end of the content and the
+    /// clamped offset is returned. The window is updated on the next call to [Draw::flush].
+    pub fn set_scroll_offset(&mut self, id: Xid, y: f64) -> Result<f64> {
+        let region = self
+            .scrollable
+            .get_mut(&id)
+            .ok_or(XcbError::UnintialisedSurface(id))?;
+        region.offset = clamp_scroll_offset(y, region.content_h, region.view_h);
+
+        Ok(region.offset)
+    }
+
+    /// Capture the current contents of the root window within `region` as a cairo image.
+    ///
+    /// Without a compositor this is the wallpaper along with any windows currently visible in
+    /// that region. Only 24 and 32 bit displays using 32 bits per pixel are supported. The
+    /// returned surface is fully opaque.
+    pub fn capture_root(&self, region: Region) -> Result<cairo::ImageSurface> {
+        let (x, y, w, h) = region.values();
+        let conn = self.api.conn();
+        let reply = xcb::get_image(
+            conn,
+            xcb::IMAGE_FORMAT_Z_PIXMAP as u8,
+            self.api.root(),
+            x as i16,
+            y as i16,
+            w as u16,
+            h as u16,
+            u32::MAX,
+        )
+        .get_reply()
+        .map_err(XcbError::from)?;
+
+        let setup = conn.get_setup();
+        let format = setup
+            .pixmap_formats()
+            .find(|f| f.depth() == reply.depth())
+            .ok_or_else(|| XcbError::QueryFailed("pixmap format for root window depth"))?;
+
+        if format.bits_per_pixel() != 32 {
+            return Err(DrawError::InvalidImageData(format!(
+                "unsupported root window format: {} bits per pixel",
+                format.bits_per_pixel()
+            )));
+        }
+
+        let (w, h) = (w as usize, h as usize);
+        let pad = (format.scanline_pad() as usize / 8).max(1);
+        let src_stride = (w * 4).div_ceil(pad) * pad;
+        let msb_first = setup.image_byte_order() == xcb::IMAGE_ORDER_MSB_FIRST as u8;
+
+        let cairo_format = cairo::Format::ARgb32;
+        let stride = cairo_format.stride_for_width(w as u32)? as usize;
+        let data = x_pixels_to_argb32(reply.data(), w, h, src_stride, msb_first, stride)?;
+
+        Ok(cairo::ImageSurface::create_for_data(
+            data,
+            cairo_format,
+            w as i32,
+            h as i32,
+            stride as i32,
+        )?)
+    }
+
+    // Scanning the allowed depths and visuals of a screen is wasteful when creating lots of
+    // windows so we cache the result per screen after the first lookup.
+    fn visual_type(
+        &self,
+        screen_ix: usize,
+        want_alpha: bool,
+    ) -> Result<(u8, xcb::ffi::xcb_visualtype_t)> {
+        let key = (screen_ix, want_alpha);
+        if let Some(visual) = self.visuals.borrow().get(&key) {
+            return Ok(*visual);
+        }
+
+        let (depth, visual) = self.api.find_visual(screen_ix, want_alpha)?;
+        self.visuals.borrow_mut().insert(key, (depth, visual.base));
+
+        Ok((depth, visual.base))
+    }
+
+    // Prefer a visual with an alpha channel, falling back to the screen default
+    fn preferred_visual_type(&self, screen_ix: usize) -> Result<(u8, xcb::ffi::xcb_visualtype_t)> {
+        self.visual_type(screen_ix, true)
+            .or_else(|_| self.visual_type(screen_ix, false))
+    }
+}
+
+/// An XCB based drawing context using pango and cairo: see [CairoDrawContext] for details.
+pub type XcbDrawContext = CairoDrawContext;
+
+impl Draw for XcbDraw {
+    type Ctx = XcbDrawContext;
+
+    fn new_window(&mut self, ty: WinType, r: Region, managed: bool) -> Result<Xid> {
+        self.create_window(ty, r, managed, true)
+    }
+
     fn screen_sizes(&self) -> Result<Vec<Region>> {
         Ok(self.api.screen_sizes()?)
     }
 
-    fn register_font(&mut self, font_name: &str) {
+    fn connection_alive(&self) -> bool {
+        self.api.connection_alive()
+    }
+
+    fn reconnect(&mut self) -> Result<()> {
+        XcbDraw::reconnect(self)
+    }
+
+    fn best_visual(&self, screen_ix: usize, want_alpha: bool) -> Result<VisualInfo> {
+        let (depth, visual) = self.visual_type(screen_ix, want_alpha)?;
+
+        Ok(VisualInfo {
+            id: visual.visual_id,
+            depth,
+        })
+    }
+
+    fn register_font(&mut self, font_name: &str) -> Result<()> {
+        self.fonts.insert(font_name.into(), load_font(font_name)?);
+
+        Ok(())
+    }
+
+    fn register_font_unchecked(&mut self, font_name: &str) {
         self.fonts.insert(
             font_name.into(),
             pango::FontDescription::from_string(font_name),
@@ -108,23 +622,20 @@ impl Draw for XcbDraw {
     }
 
     fn context_for(&self, id: Xid) -> Result<Self::Ctx> {
-        let ctx = cairo::Context::new(
-            self.surfaces
-                .get(&id)
-                .ok_or(XcbError::UnintialisedSurface(id))?,
-        )?;
+        let ctx = match self.scrollable.get(&id) {
+            Some(region) => cairo::Context::new(&region.content)?,
+            None => cairo::Context::new(
+                self.surfaces
+                    .get(&id)
+                    .ok_or(XcbError::UnintialisedSurface(id))?,
+            )?,
+        };
 
-        Ok(Self::Ctx {
-            ctx,
-            font: None,
-            fonts: self.fonts.clone(),
-        })
+        Ok(self.context(ctx))
     }
 
     fn temp_context(&self, w: u32, h: u32) -> Result<Self::Ctx> {
-        let xcb_screen = self.api.screen(0)?;
-        let depth = self.api.get_depth(&xcb_screen)?;
-        let mut visualtype = self.api.get_visual_type(&depth)?;
+        let (_, mut visualtype) = self.preferred_visual_type(0)?;
 
         let surface = unsafe {
             let conn_ptr = self.api.conn().get_raw_conn() as *mut cairo_sys::xcb_connection_t;
@@ -133,7 +644,7 @@ impl Draw for XcbDraw {
                 &cairo::XCBConnection::from_raw_none(conn_ptr),
                 &cairo::XCBDrawable(self.api.root()),
                 &cairo::XCBVisualType::from_raw_none(
-                    &mut visualtype.base as *mut xcb::ffi::xcb_visualtype_t
+                    &mut visualtype as *mut xcb::ffi::xcb_visualtype_t
                         as *mut cairo_sys::xcb_visualtype_t,
                 ),
                 w as i32,
@@ -144,114 +655,115 @@ impl Draw for XcbDraw {
         let surface = surface.create_similar(cairo::Content::Color, w as i32, h as i32)?;
         let ctx = cairo::Context::new(&surface)?;
 
-        Ok(Self::Ctx {
-            ctx,
-            font: None,
-            fonts: self.fonts.clone(),
-        })
+        Ok(self.context(ctx))
     }
 
     fn flush(&self, id: Xid) -> Result<()> {
         if let Some(s) = self.surfaces.get(&id) {
+            if let Some(region) = self.scrollable.get(&id) {
+                region.content.flush();
+                let ctx = cairo::Context::new(s)?;
+                ctx.set_operator(cairo::Operator::Source);
+                ctx.set_source_surface(&region.content, 0.0, -region.offset)?;
+                ctx.paint()?;
+            }
             s.flush()
         };
-        self.map_client(id)?;
+        if maps_on_flush(&self.unmapped, id) {
+            self.map_client(id)?;
+        }
         self.api.flush();
         Ok(())
     }
 }
 
-/// An XCB based drawing context using pango and cairo
-#[derive(Clone, Debug)]
-pub struct XcbDrawContext {
-    ctx: cairo::Context,
-    font: Option<pango::FontDescription>,
-    fonts: HashMap<String, pango::FontDescription>,
+impl XDraw for XcbDraw {
+    fn new_unmapped_window(&mut self, ty: WinType, r: Region, managed: bool) -> Result<Xid> {
+        self.create_window(ty, r, managed, false)
+    }
 }
 
-impl DrawContext for XcbDrawContext {
-    fn font(&mut self, font_name: &str, point_size: i32) -> Result<()> {
-        let mut font = self
-            .fonts
-            .get_mut(font_name)
-            .ok_or_else(|| DrawError::UnknownFont(font_name.into()))?
-            .clone();
-        font.set_size(point_size * pango::SCALE);
-        self.font = Some(font);
-
-        Ok(())
-    }
+// Windows that were created mapped are (re)mapped whenever they are flushed: those created
+// unmapped are only shown when the caller explicitly maps them.
+fn maps_on_flush(unmapped: &HashSet<Xid>, id: Xid) -> bool {
+    !unmapped.contains(&id)
+}
 
-    fn color(&mut self, color: &Color) {
-        let (r, g, b, a) = color.rgba();
-        self.ctx.set_source_rgba(r, g, b, a);
+// Convert 32 bit per pixel X image data into opaque native endian ARGB32 pixels for cairo
+fn x_pixels_to_argb32(
+    data: &[u8],
+    w: usize,
+    h: usize,
+    src_stride: usize,
+    msb_first: bool,
+    stride: usize,
+) -> Result<Vec<u8>> {
+    if data.len() < src_stride * h {
+        return Err(DrawError::InvalidImageData(format!(
+            "expected {} bytes of image data but got {}",
+            src_stride * h,
+            data.len()
+        )));
     }
 
-    fn clear(&mut self) -> Result<()> {
-        self.ctx.save()?;
-        self.ctx.set_operator(cairo::Operator::Clear);
-        self.ctx.paint()?;
-        self.ctx.restore()?;
-
-        Ok(())
+    let mut buf = vec![0; stride * h];
+    if w == 0 {
+        return Ok(buf);
     }
 
-    fn translate(&self, dx: f64, dy: f64) {
-        self.ctx.translate(dx, dy)
+    for (src, dst) in data.chunks(src_stride).zip(buf.chunks_exact_mut(stride)) {
+        for (p, out) in src[..w * 4].chunks_exact(4).zip(dst.chunks_exact_mut(4)) {
+            let bytes = [p[0], p[1], p[2], p[3]];
+            let pixel = if msb_first {
+                u32::from_be_bytes(bytes)
+            } else {
+                u32::from_le_bytes(bytes)
+            };
+            out.copy_from_slice(&(pixel | 0xFF00_0000).to_ne_bytes());
+        }
     }
 
-    fn set_x_offset(&self, x: f64) {
-        let (_, y_offset) = self.ctx.matrix().transform_point(0.0, 0.0);
-        self.ctx.set_matrix(cairo::Matrix::identity());
-        self.ctx.translate(x, y_offset);
-    }
+    Ok(buf)
+}
 
-    fn set_y_offset(&self, y: f64) {
-        let (x_offset, _) = self.ctx.matrix().transform_point(0.0, 0.0);
-        self.ctx.set_matrix(cairo::Matrix::identity());
-        self.ctx.translate(x_offset, y);
-    }
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    fn rectangle(&self, x: f64, y: f64, w: f64, h: f64) -> Result<()> {
-        self.ctx.rectangle(x, y, w, h);
-        self.ctx.fill()?;
+    test_cases! {
+        scale_factors;
+        args: (xft_dpi: Option<f64>, physical: &[(u32, u32)], expected: Vec<f64>);
 
-        Ok(())
-    }
+        case: xft_dpi_wins => (Some(192.0), &[(254, 143), (0, 0)], vec![2.0, 2.0]);
+        case: from_physical_size => (None, &[(254, 143), (508, 286)], vec![2.0, 1.0]);
+        case: unknown_physical_size => (None, &[(0, 0)], vec![1.0, 1.0]);
 
-    fn text(&self, txt: &str, h_offset: f64, padding: (f64, f64)) -> Result<(f64, f64)> {
-        let layout = pango_layout(&self.ctx)?;
-        if let Some(ref font) = self.font {
-            layout.set_font_description(Some(font));
+        body: {
+            let sizes = [Region::new(0, 0, 1920, 1080), Region::new(1920, 0, 1920, 1080)];
+            assert_eq!(scale_factors(xft_dpi, &sizes, physical), expected);
         }
+    }
 
-        layout.set_text(txt);
-        layout.set_ellipsize(pango::EllipsizeMode::End);
-
-        let (w, h) = layout.pixel_size();
-        let (l, r) = padding;
-        self.ctx.translate(l, h_offset);
-        show_layout(&self.ctx, &layout);
-        self.ctx.translate(-l, -h_offset);
-
-        let width = w as f64 + l + r;
-        let height = h as f64;
+    test_cases! {
+        clamp_scroll_offset;
+        args: (y: f64, content_h: f64, expected: f64);
 
-        Ok((width, height))
-    }
+        case: start => (0.0, 300.0, 0.0);
+        case: within_content => (50.0, 300.0, 50.0);
+        case: negative => (-10.0, 300.0, 0.0);
+        case: past_the_end => (500.0, 300.0, 200.0);
+        case: content_fits_in_view => (50.0, 80.0, 0.0);
 
-    fn text_extent(&self, s: &str) -> Result<(f64, f64)> {
-        let layout = pango_layout(&self.ctx)?;
-        if let Some(ref font) = self.font {
-            layout.set_font_description(Some(font));
+        body: {
+            assert_eq!(clamp_scroll_offset(y, content_h, 100.0), expected);
         }
-        layout.set_text(&s);
-        let (w, h) = layout.pixel_size();
-
-        Ok((w as f64, h as f64))
     }
 
-    fn flush(&self) {
-        self.ctx.target().flush();
+    #[test]
+    fn windows_created_unmapped_are_not_mapped_on_flush() {
+        let unmapped: HashSet<Xid> = vec![2].into_iter().collect();
+
+        assert!(maps_on_flush(&unmapped, 1));
+        assert!(!maps_on_flush(&unmapped, 2));
     }
 }
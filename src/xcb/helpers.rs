@@ -42,3 +42,37 @@ pub fn parse_key_binding(pattern: String, known_codes: &CodeMap) -> Option<KeyCo
         None => None,
     }
 }
+
+/// Parse the value of `Xft.dpi` from the contents of the `RESOURCE_MANAGER` property of the
+/// root window (as set by `xrdb`), if present.
+pub fn parse_xft_dpi(resources: &str) -> Option<f64> {
+    resources.lines().find_map(|line| {
+        let (key, val) = line.split_once(':')?;
+        if key.trim() == "Xft.dpi" {
+            val.trim().parse().ok().filter(|dpi: &f64| *dpi > 0.0)
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_cases! {
+        parse_xft_dpi;
+        args: (resources: &str, expected: Option<f64>);
+
+        case: only_dpi => ("Xft.dpi:\t144\n", Some(144.0));
+        case: among_others => ("Xft.antialias:\t1\nXft.dpi:\t120\nXft.hinting:\t1", Some(120.0));
+        case: fractional => ("Xft.dpi: 96.5", Some(96.5));
+        case: missing => ("Xft.antialias:\t1\n", None);
+        case: invalid => ("Xft.dpi:\tlots", None);
+        case: zero => ("Xft.dpi:\t0", None);
+
+        body: {
+            assert_eq!(parse_xft_dpi(resources), expected);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! A simple popup menu rendered using [XcbDraw]
+//!
+//! Only available when both the `xcb_draw` and `keysyms` features are enabled.
+use crate::{
+    core::{
+        bindings::KeyPress,
+        data_types::{Point, Region, WinType},
+        xconnection::{Atom, KeyPressParseAttempt, Prop, XClientProperties, XEvent, Xid},
+    },
+    draw::{Color, Draw, DrawContext, DrawError, Result, TextStyle},
+    xcb::{XcbDraw, XcbDrawContext, XcbError},
+};
+
+const ITEM_V_PAD: f64 = 2.0;
+
+/// A popup menu of selectable entries, such as a right click context menu for a status bar.
+///
+/// The menu is shown in an override-redirect window positioned at the pointer and sized to fit
+/// its longest entry. Entries are highlighted when hovered with the pointer or selected with the
+/// Up and Down keys: clicking on an entry or pressing Return while one is highlighted selects
+/// it. Pressing Escape or clicking outside of the menu dismisses it.
+///
+/// ```no_run
+/// # use penrose::{draw::{Color, Result, TextStyle}, xcb::{Menu, XcbDraw}};
+/// # fn example(style: &TextStyle) -> Result<()> {
+/// let mut drw = XcbDraw::new()?;
+/// let menu = Menu::new(vec!["copy", "paste", "close"], style, 0x458588);
+///
+/// if let Some(ix) = menu.show(&mut drw)? {
+///     println!("selected entry {}", ix);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Menu {
+    entries: Vec<String>,
+    font: String,
+    point_size: i32,
+    fg: Color,
+    bg: Color,
+    selected_bg: Color,
+    padding: (f64, f64),
+}
+
+impl Menu {
+    /// Create a new [Menu] rendering `entries` using `style`, with the highlighted entry drawn
+    /// using a background of `selected_bg`.
+    pub fn new(
+        entries: Vec<impl Into<String>>,
+        style: &TextStyle,
+        selected_bg: impl Into<Color>,
+    ) -> Self {
+        Self {
+            entries: entries.into_iter().map(|e| e.into()).collect(),
+            font: style.font.clone(),
+            point_size: style.point_size,
+            fg: style.fg,
+            bg: style.bg.unwrap_or_else(|| 0x000000.into()),
+            selected_bg: selected_bg.into(),
+            padding: style.padding,
+        }
+    }
+
+    /// Show this menu at the current pointer position and wait for the user to make a selection,
+    /// returning the index of the selected entry or `None` if the menu was dismissed.
+    ///
+    /// ## NOTE
+    /// This method will block the current thread while it runs.
+    pub fn show(&self, drw: &mut XcbDraw) -> Result<Option<usize>> {
+        if self.entries.is_empty() {
+            return Ok(None);
+        }
+
+        drw.register_font(&self.font)?;
+        let (w, item_h) = self.entry_size(drw)?;
+        let h = item_h * self.entries.len() as f64;
+        let p = drw.api().cursor_position()?;
+        let region = fit_on_output(w as u32, h as u32, p, &drw.screen_sizes()?);
+
+        let id = drw.new_window(
+            WinType::InputOutput(Atom::NetWindowTypePopupMenu),
+            region,
+            false,
+        )?;
+        let prop = Prop::UTF8String(vec!["penrose-menu".into()]);
+        for a in &[Atom::NetWmName, Atom::WmName, Atom::WmClass] {
+            drw.change_prop(id, a.as_ref(), prop.clone())?;
+        }
+        drw.flush(id)?;
+
+        let selection = drw
+            .api()
+            .grab_keyboard()
+            .and_then(|_| drw.api().grab_pointer(id))
+            .map_err(DrawError::from)
+            .and_then(|_| self.run(drw, id, w, item_h));
+
+        drw.api().ungrab_pointer()?;
+        drw.api().ungrab_keyboard()?;
+        drw.destroy_window(id)?;
+
+        selection
+    }
+
+    // The width of the menu and height of a single entry
+    fn entry_size(&self, drw: &XcbDraw) -> Result<(f64, f64)> {
+        let mut ctx = drw.temp_context(1, 1)?;
+        ctx.font(&self.font, self.point_size)?;
+
+        let (mut w_max, mut h_max) = (0.0_f64, 0.0_f64);
+        for entry in self.entries.iter() {
+            let (w, h) = ctx.text_extent(entry)?;
+            w_max = w_max.max(w);
+            h_max = h_max.max(h);
+        }
+
+        let (l, r) = self.padding;
+        Ok(((w_max + l + r).ceil(), (h_max + 2.0 * ITEM_V_PAD).ceil()))
+    }
+
+    fn run(&self, drw: &mut XcbDraw, id: Xid, w: f64, item_h: f64) -> Result<Option<usize>> {
+        let n = self.entries.len();
+        let mut selected = None;
+        self.redraw(drw, id, w, item_h, selected)?;
+
+        loop {
+            let event = match drw.api().conn().wait_for_event() {
+                Some(event) => event,
+                None => {
+                    drw.api().conn().has_error().map_err(XcbError::from)?;
+                    continue;
+                }
+            };
+
+            // Pointer events are handled directly as motion without a button held is not
+            // converted into an XEvent
+            let prev = selected;
+            match event.response_type() & 0x7F {
+                xcb::MOTION_NOTIFY => {
+                    let e: &xcb::MotionNotifyEvent = unsafe { xcb::cast_event(&event) };
+                    selected = entry_at(e.event_x(), e.event_y(), w, item_h, n);
+                }
+
+                xcb::BUTTON_PRESS => {
+                    let e: &xcb::ButtonPressEvent = unsafe { xcb::cast_event(&event) };
+                    return Ok(entry_at(e.event_x(), e.event_y(), w, item_h, n));
+                }
+
+                _ => match drw.api().attempt_to_parse_as_keypress(event)? {
+                    Some(KeyPressParseAttempt::KeyPress(k)) => match k {
+                        KeyPress::Escape => return Ok(None),
+                        KeyPress::Return if selected.is_some() => return Ok(selected),
+                        KeyPress::Up => selected = step_selection(selected, n, false),
+                        KeyPress::Down => selected = step_selection(selected, n, true),
+                        _ => (),
+                    },
+
+                    Some(KeyPressParseAttempt::XEvent(XEvent::Expose(e)))
+                        if e.id == id && e.count == 0 =>
+                    {
+                        self.redraw(drw, id, w, item_h, selected)?;
+                    }
+
+                    _ => (),
+                },
+            }
+
+            if selected != prev {
+                self.redraw(drw, id, w, item_h, selected)?;
+            }
+        }
+    }
+
+    fn redraw(
+        &self,
+        drw: &XcbDraw,
+        id: Xid,
+        w: f64,
+        item_h: f64,
+        selected: Option<usize>,
+    ) -> Result<()> {
+        let mut ctx: XcbDrawContext = drw.context_for(id)?;
+        ctx.font(&self.font, self.point_size)?;
+        ctx.color(&self.bg);
+        ctx.rectangle(0.0, 0.0, w, item_h * self.entries.len() as f64)?;
+
+        for (ix, entry) in self.entries.iter().enumerate() {
+            if selected == Some(ix) {
+                ctx.color(&self.selected_bg);
+                ctx.rectangle(0.0, 0.0, w, item_h)?;
+            }
+
+            ctx.color(&self.fg);
+            ctx.text(entry, ITEM_V_PAD, self.padding)?;
+            ctx.translate(0.0, item_h);
+        }
+
+        drw.flush(id)
+    }
+}
+
+// Place a w x h region with its top left corner at 'p', shifted so that it fits within the
+// output containing 'p' wherever possible
+fn fit_on_output(w: u32, h: u32, p: Point, outputs: &[Region]) -> Region {
+    let output = outputs
+        .iter()
+        .find(|r| r.contains_point(&p))
+        .or_else(|| outputs.first());
+
+    let (x, y) = match output {
+        Some(r) => {
+            let (ox, oy, ow, oh) = r.values();
+            let x = p.x.min((ox + ow).saturating_sub(w)).max(ox);
+            let y = p.y.min((oy + oh).saturating_sub(h)).max(oy);
+            (x, y)
+        }
+        None => (p.x, p.y),
+    };
+
+    Region::new(x, y, w, h)
+}
+
+// The index of the entry at the given window relative coordinates, if any
+fn entry_at(x: i16, y: i16, w: f64, item_h: f64, n: usize) -> Option<usize> {
+    let (x, y) = (x as f64, y as f64);
+    if x < 0.0 || x >= w || y < 0.0 || item_h <= 0.0 {
+        return None;
+    }
+
+    let ix = (y / item_h) as usize;
+    if ix < n {
+        Some(ix)
+    } else {
+        None
+    }
+}
+
+// Move the selection up or down by one entry, wrapping at either end
+fn step_selection(current: Option<usize>, n: usize, forward: bool) -> Option<usize> {
+    if n == 0 {
+        return None;
+    }
+
+    Some(match (current, forward) {
+        (None, true) => 0,
+        (None, false) => n - 1,
+        (Some(ix), true) => (ix + 1) % n,
+        (Some(ix), false) => (ix + n - 1) % n,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    test_cases! {
+        fit_on_output;
+        args: (p: Point, expected: Region);
+
+        case: fits_at_pointer => (Point::new(10, 20), Region::new(10, 20, 100, 50));
+        case: shifted_left => (Point::new(1900, 20), Region::new(1820, 20, 100, 50));
+        case: shifted_up => (Point::new(10, 1070), Region::new(10, 1030, 100, 50));
+        case: second_output => (Point::new(3800, 1070), Region::new(3740, 1030, 100, 50));
+
+        body: {
+            let outputs = [Region::new(0, 0, 1920, 1080), Region::new(1920, 0, 1920, 1080)];
+            assert_eq!(fit_on_output(100, 50, p, &outputs), expected);
+        }
+    }
+
+    test_cases! {
+        entry_at;
+        args: (x: i16, y: i16, expected: Option<usize>);
+
+        case: first => (5, 0, Some(0));
+        case: last => (5, 59, Some(2));
+        case: past_the_end => (5, 60, None);
+        case: left_of_menu => (-1, 5, None);
+        case: right_of_menu => (100, 5, None);
+
+        body: {
+            assert_eq!(entry_at(x, y, 100.0, 20.0, 3), expected);
+        }
+    }
+
+    test_cases! {
+        step_selection;
+        args: (current: Option<usize>, forward: bool, expected: Option<usize>);
+
+        case: down_from_none => (None, true, Some(0));
+        case: up_from_none => (None, false, Some(2));
+        case: down_wraps => (Some(2), true, Some(0));
+        case: up_wraps => (Some(0), false, Some(2));
+        case: down => (Some(0), true, Some(1));
+
+        body: {
+            assert_eq!(step_selection(current, 3, forward), expected);
+        }
+    }
+}
@@ -43,6 +43,8 @@ pub mod conversions;
 #[cfg(feature = "xcb_draw")]
 pub mod draw;
 pub mod helpers;
+#[cfg(all(feature = "xcb_draw", feature = "keysyms"))]
+pub mod menu;
 pub mod xconn;
 
 #[doc(inline)]
@@ -51,6 +53,9 @@ pub use api::Api;
 #[cfg(feature = "xcb_draw")]
 pub use draw::{XcbDraw, XcbDrawContext};
 #[doc(inline)]
+#[cfg(all(feature = "xcb_draw", feature = "keysyms"))]
+pub use menu::Menu;
+#[doc(inline)]
 pub use xconn::XcbConnection;
 
 /// A generic event type returned by the xcb library
@@ -306,6 +311,15 @@ macro_rules! __xcb_impl_xeventhandler {
                 Ok(self.api.wait_for_event()?)
             }
 
+            fn poll_for_event(&self) -> $crate::core::xconnection::Result<Option<XEvent>> {
+                Ok(self.api.poll_for_event()?)
+            }
+
+            fn connection_fd(&self) -> Option<::std::os::unix::io::RawFd> {
+                use ::std::os::unix::io::AsRawFd;
+                Some(self.api.conn().as_raw_fd())
+            }
+
             fn send_client_event(&self, msg: ClientMessage) -> $crate::core::xconnection::Result<()> {
                 Ok(self.api.send_client_event(msg)?)
             }
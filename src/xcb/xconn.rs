@@ -137,4 +137,12 @@ impl XConn for XcbConnection {
 
         Ok(())
     }
+
+    fn grab_keyboard(&self) -> Result<()> {
+        Ok(self.api.grab_keyboard()?)
+    }
+
+    fn ungrab_keyboard(&self) -> Result<()> {
+        Ok(self.api.ungrab_keyboard()?)
+    }
 }